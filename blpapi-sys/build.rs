@@ -1,5 +1,5 @@
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 const ENV_WARNING: &'static str = r#"Error while building blpapi-sys.
 
@@ -10,48 +10,213 @@ const ENV_WARNING: &'static str = r#"Error while building blpapi-sys.
 
     Once extracted, the BLPAPI_ROOT environment variable should point to the
     directory containing the extracted package.
+
+    Alternatively, BLPAPI_LIB_DIR and BLPAPI_INCLUDE_DIR can point to the
+    directories of the library and of the headers.
 "#;
 
+/// Features of the SDK versions, with the functions they add to the
+/// headers which exist in older versions
+const SDK_FEATURES: &[(&str, &[&str])] = &[
+    ("sdk-3-18", &["blpapi_SessionOptions_setSessionName", "blpapi_SessionOptions_sessionName"]),
+    ("sdk-3-20", &["blpapi_SessionOptions_setApplicationIdentityKey", "blpapi_SessionOptions_applicationIdentityKey"]),
+    ("sdk-3-24", &[]),
+];
+
 fn main() {
-    let blpapi_root_dir = PathBuf::from(env::var("BLPAPI_ROOT").expect(ENV_WARNING));
+    println!("cargo:rerun-if-env-changed=BLPAPI_ROOT");
+    println!("cargo:rerun-if-env-changed=BLPAPI_LIB_DIR");
+    println!("cargo:rerun-if-env-changed=BLPAPI_INCLUDE_DIR");
 
-    let lib_dir = {
-        let mut dir = blpapi_root_dir.clone();
+    // With `dlopen`, blpapi is loaded at runtime instead, see src/dlopen.rs
+    if !cfg!(feature = "dlopen") {
+        link();
+    }
 
-        if cfg!(target_os = "windows") {
-            dir.push("lib");
-        } else if cfg!(target_os = "linux") {
-            dir.push("Linux");
-        } else if cfg!(target_os = "macos") {
-            dir.push("Darwin");
-        }
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("bindings.rs");
+    let include_dir = include_dir().into_os_string().into_string().unwrap();
+    generate_bindings(&include_dir, &out_path);
 
-        dir.into_os_string().into_string().unwrap()
-    };
+    #[cfg(feature = "dlopen")]
+    dlopen_bindings(&out_path);
+}
 
-    println!("cargo:rustc-link-search={}", lib_dir);
-    println!("cargo:rustc-link-lib=blpapi3_64");
+/// Directory of `var` if set, else `path` of BLPAPI_ROOT
+fn env_dir(var: &str, path: &str) -> PathBuf {
+    match env::var_os(var) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var("BLPAPI_ROOT").expect(ENV_WARNING)).join(path),
+    }
+}
+
+fn include_dir() -> PathBuf {
+    let dir = env_dir("BLPAPI_INCLUDE_DIR", "include");
+    let header = dir.join("blpapi_session.h");
+    if !header.is_file() {
+        panic!(
+            "Error while building blpapi-sys.\n\n    \
+            Cannot find the blpapi headers, probed: {}\n\n    \
+            Check BLPAPI_INCLUDE_DIR, or BLPAPI_ROOT and its include directory.",
+            header.display()
+        );
+    }
+    dir
+}
 
-    let include_dir = {
-        let mut dir = blpapi_root_dir.clone();
-        dir.push("include");
-        dir.into_os_string().into_string().unwrap()
+/// Link blpapi3_32 or blpapi3_64, depending on the target
+fn link() {
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+    let lib_name = match env::var("CARGO_CFG_TARGET_POINTER_WIDTH").unwrap().as_str() {
+        "32" => "blpapi3_32",
+        _ => "blpapi3_64",
+    };
+    let lib_dir = match target_os.as_str() {
+        "windows" => env_dir("BLPAPI_LIB_DIR", "lib"),
+        "macos" => env_dir("BLPAPI_LIB_DIR", "Darwin"),
+        _ => env_dir("BLPAPI_LIB_DIR", "Linux"),
     };
+    let file_names: &[String] = match target_os.as_str() {
+        "windows" => &[format!("{}.lib", lib_name)],
+        "macos" => &[format!("lib{}.dylib", lib_name), format!("lib{}.so", lib_name)],
+        _ => &[format!("lib{}.so", lib_name)],
+    };
+
+    let probed: Vec<PathBuf> = file_names.iter().map(|file_name| lib_dir.join(file_name)).collect();
+    if !probed.iter().any(|path| path.is_file()) {
+        let probed: Vec<String> = probed.iter().map(|path| path.display().to_string()).collect();
+        panic!(
+            "Error while building blpapi-sys.\n\n    \
+            Cannot find {} for {}, probed: {}\n\n    \
+            Check BLPAPI_LIB_DIR, or BLPAPI_ROOT and its library directory.",
+            lib_name,
+            env::var("TARGET").unwrap(),
+            probed.join(", ")
+        );
+    }
 
+    println!("cargo:rustc-link-search={}", lib_dir.display());
+    println!("cargo:rustc-link-lib={}", lib_name);
+}
+
+/// Name of the cargo feature `feature`, as in its environment variable
+fn feature_env_name(feature: &str) -> String {
+    feature.to_uppercase().replace('-', "_")
+}
+
+fn feature_enabled(feature: &str) -> bool {
+    env::var_os(format!("CARGO_FEATURE_{}", feature_env_name(feature))).is_some()
+}
+
+fn generate_bindings(include_dir: &str, out_path: &Path) {
     // Dynamically build bindings.rs based on wrapper.h
     println!("cargo:rerun-if-changed=wrapper.h");
-    let bindings = bindgen::Builder::default()
+    let mut builder = bindgen::Builder::default()
         .header("wrapper.h")
         .clang_arg(format!("-I{}", include_dir))
         .size_t_is_usize(true)
         .derive_default(true)
-        .parse_callbacks(Box::new(bindgen::CargoCallbacks))
+        .parse_callbacks(Box::new(bindgen::CargoCallbacks));
+
+    // Headers of newer SDKs are only included by wrapper.h if enabled, and
+    // the functions they add to existing headers are left out otherwise
+    for (feature, functions) in SDK_FEATURES {
+        if feature_enabled(feature) {
+            builder = builder.clang_arg(format!("-DBLPAPI_RS_{}", feature_env_name(feature)));
+        } else {
+            for function in functions.iter() {
+                builder = builder.blacklist_function(function);
+            }
+        }
+    }
+
+    let bindings = builder
         .generate()
         .expect("Unable to generate bindings");
 
     // Write the bindings to the $OUT_DIR/bindings.rs file.
-    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
     bindings
-        .write_to_file(out_path.join("bindings.rs"))
+        .write_to_file(out_path)
         .expect("Couldn't write bindings!");
+
+}
+
+/// Replace the `extern` declarations of the bindings by functions calling
+/// the symbols of the library loaded at runtime
+#[cfg(feature = "dlopen")]
+fn dlopen_bindings(out_path: &Path) {
+    use quote::quote;
+    use std::fs;
+
+    let source = fs::read_to_string(out_path).expect("Couldn't read bindings!");
+    let file = syn::parse_file(&source).expect("Couldn't parse bindings!");
+
+    let mut items = Vec::new();
+    let mut fields = Vec::new();
+    let mut symbols = Vec::new();
+    let mut functions = Vec::new();
+    for item in file.items {
+        let foreign = match item {
+            syn::Item::ForeignMod(foreign) => foreign,
+            item => {
+                items.push(item);
+                continue;
+            }
+        };
+        let abi = &foreign.abi;
+        for item in foreign.items {
+            let function = match item {
+                syn::ForeignItem::Fn(function) if function.sig.variadic.is_none() => function,
+                _ => continue,
+            };
+            let attrs = &function.attrs;
+            let ident = &function.sig.ident;
+            let inputs = &function.sig.inputs;
+            let output = &function.sig.output;
+            let (args, types): (Vec<_>, Vec<_>) = inputs
+                .iter()
+                .filter_map(|input| match input {
+                    syn::FnArg::Typed(arg) => Some((&arg.pat, &arg.ty)),
+                    syn::FnArg::Receiver(_) => None,
+                })
+                .unzip();
+            let symbol = syn::LitByteStr::new(format!("{}\0", ident).as_bytes(), ident.span());
+            let missing = format!("{} is missing from the loaded blpapi library, see dlopen::has_function", ident);
+
+            fields.push(quote! { #ident: Option<unsafe #abi fn(#(#types),*) #output>, });
+            symbols.push(quote! { #ident: library.get(#symbol).ok().map(|symbol| *symbol), });
+            functions.push(quote! {
+                #(#attrs)*
+                pub unsafe fn #ident(#inputs) #output {
+                    let function = crate::dlopen::library().#ident.expect(#missing);
+                    function(#(#args),*)
+                }
+            });
+        }
+    }
+
+    let bindings = quote! {
+        #(#items)*
+
+        #[doc(hidden)]
+        pub struct Symbols {
+            #(#fields)*
+            library: ::libloading::Library,
+        }
+
+        impl Symbols {
+            pub(crate) unsafe fn new(library: ::libloading::Library) -> Self {
+                Symbols {
+                    #(#symbols)*
+                    library,
+                }
+            }
+
+            pub(crate) fn library(&self) -> &::libloading::Library {
+                &self.library
+            }
+        }
+
+        #(#functions)*
+    };
+    fs::write(out_path, bindings.to_string()).expect("Couldn't write bindings!");
 }
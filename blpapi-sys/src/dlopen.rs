@@ -0,0 +1,126 @@
+//! Loading of blpapi at runtime, with the `dlopen` feature
+//!
+//! The bindings then call the symbols of the library loaded on first use,
+//! so that binaries can be built, and tests not calling blpapi run, without
+//! the library. Calling a binding panics if the library cannot be loaded:
+//! `load` or `is_available` report it as an error beforehand, and
+//! `has_function` the functions missing from older versions.
+use crate::Symbols;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+
+/// Environment variable overriding the path of the library to load
+pub const LIBRARY_PATH_ENV: &str = "BLPAPI_LIBRARY_PATH";
+
+static LIBRARY: OnceLock<Symbols> = OnceLock::new();
+
+/// The library could not be loaded
+#[derive(Debug, Clone)]
+pub struct LoadError {
+    path: OsString,
+    message: String,
+}
+
+impl LoadError {
+    /// The path of the library which could not be loaded
+    pub fn path(&self) -> &OsStr {
+        &self.path
+    }
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "cannot load blpapi from {}: {}", self.path.to_string_lossy(), self.message)
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// File name of blpapi for the target, e.g. `libblpapi3_64.so`
+pub fn library_filename() -> OsString {
+    if cfg!(target_pointer_width = "32") {
+        libloading::library_filename("blpapi3_32")
+    } else {
+        libloading::library_filename("blpapi3_64")
+    }
+}
+
+fn open<P: AsRef<OsStr>>(path: P) -> Result<Symbols, LoadError> {
+    let path = path.as_ref();
+    match unsafe { libloading::Library::new(path) } {
+        Ok(library) => Ok(unsafe { Symbols::new(library) }),
+        Err(err) => Err(LoadError { path: path.to_os_string(), message: err.to_string() }),
+    }
+}
+
+fn open_default() -> Result<Symbols, LoadError> {
+    match std::env::var_os(LIBRARY_PATH_ENV) {
+        Some(path) => open(path),
+        None => open(library_filename()),
+    }
+}
+
+/// Keep the first library loaded, failures are not kept so that loading
+/// can be retried, e.g. from another path
+fn get_or_open<F: FnOnce() -> Result<Symbols, LoadError>>(open: F) -> Result<&'static Symbols, LoadError> {
+    if let Some(symbols) = LIBRARY.get() {
+        return Ok(symbols);
+    }
+    let symbols = open()?;
+    Ok(LIBRARY.get_or_init(|| symbols))
+}
+
+/// Load blpapi from `BLPAPI_LIBRARY_PATH` if set, else from the library
+/// search path, unless it is already loaded
+pub fn load() -> Result<(), LoadError> {
+    get_or_open(open_default).map(|_| ())
+}
+
+/// Load blpapi from `path`, unless it is already loaded
+pub fn load_from<P: AsRef<OsStr>>(path: P) -> Result<(), LoadError> {
+    get_or_open(|| open(path)).map(|_| ())
+}
+
+/// Return true if blpapi is loaded, or can be loaded by `load`
+pub fn is_available() -> bool {
+    load().is_ok()
+}
+
+/// Return true if blpapi is loaded
+pub fn is_loaded() -> bool {
+    LIBRARY.get().is_some()
+}
+
+/// Return true if blpapi can be loaded and exports the function `name`,
+/// e.g. to check for functions of newer versions before calling them
+pub fn has_function(name: &str) -> bool {
+    match get_or_open(open_default) {
+        Ok(symbols) => unsafe { symbols.library().get::<*const ()>(name.as_bytes()) }.is_ok(),
+        Err(_) => false,
+    }
+}
+
+pub(crate) fn library() -> &'static Symbols {
+    match get_or_open(open_default) {
+        Ok(symbols) => symbols,
+        Err(err) => panic!("{}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_library() {
+        let err = open("/nonexistent/libblpapi3_64.so").err().unwrap();
+        assert_eq!(err.path(), "/nonexistent/libblpapi3_64.so");
+        assert!(err.to_string().starts_with("cannot load blpapi from /nonexistent/libblpapi3_64.so"));
+
+        if !is_loaded() {
+            assert!(load_from("/nonexistent/libblpapi3_64.so").is_err());
+            assert!(!is_loaded());
+        }
+    }
+}
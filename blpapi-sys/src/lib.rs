@@ -4,10 +4,21 @@
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
+#[cfg(feature = "dlopen")]
+pub mod dlopen;
+
+#[cfg(feature = "dlopen")]
+pub use dlopen::{is_available, load};
+
 #[test]
 fn test_session_options() {
     use std::ffi::CString;
 
+    #[cfg(feature = "dlopen")]
+    if !is_available() {
+        return;
+    }
+
     unsafe {
         let session_options = blpapi_SessionOptions_create();
         assert!(!session_options.is_null());
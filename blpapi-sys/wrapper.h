@@ -16,6 +16,7 @@
 #include "blpapi_name.h"
 #include "blpapi_providersession.h"
 #include "blpapi_request.h"
+#include "blpapi_requesttemplate.h"
 #include "blpapi_resolutionlist.h"
 #include "blpapi_schema.h"
 #include "blpapi_service.h"
@@ -28,3 +29,7 @@
 #include "blpapi_tlsoptions.h"
 #include "blpapi_topic.h"
 #include "blpapi_topiclist.h"
+#include "blpapi_zfputil.h"
+#ifdef BLPAPI_RS_SDK_3_24
+#include "blpapi_useragentinfo.h"
+#endif
@@ -0,0 +1,82 @@
+//! Compare filling a value with a `FieldMap` with deserializing it through
+//! serde, run with
+//! `cargo bench --features "test-util serialization" --bench field_map`
+use blpapi::{
+    event::EventType,
+    field_map::FieldMap,
+    message::Message,
+    name::Name,
+    serde::deserialization::from_element,
+    testutil::EventBuilder,
+    Error,
+};
+use std::hint::black_box;
+use std::time::Instant;
+
+const MESSAGES: u32 = 100_000;
+
+const MESSAGE: &str = r#"
+    {
+        "resubscriptionId": 123,
+        "receivedFrom": { "address": "12.34.56.78:8194" },
+        "reason": "TestUtil"
+    }
+"#;
+
+#[derive(Default)]
+struct Started {
+    resubscription_id: i32,
+    reason: String,
+    address: String,
+}
+
+#[derive(serde::Deserialize)]
+struct ReceivedFrom {
+    #[serde(rename = "address")]
+    _address: String,
+}
+
+#[derive(serde::Deserialize)]
+struct StartedSerde {
+    #[serde(rename = "resubscriptionId")]
+    _resubscription_id: i32,
+    #[serde(rename = "reason")]
+    _reason: String,
+    #[serde(rename = "receivedFrom")]
+    _received_from: ReceivedFrom,
+}
+
+fn run<F: FnMut(&Message)>(name: &str, message: &Message, mut f: F) {
+    let start = Instant::now();
+    for _ in 0..MESSAGES {
+        f(message);
+    }
+    let elapsed = start.elapsed();
+    println!("{}: {:?} ({:?}/message)", name, elapsed, elapsed / MESSAGES);
+}
+
+fn main() -> Result<(), Error> {
+    let event = EventBuilder::new(EventType::SubscriptionStatus)?
+        .append_message_from_json(Name::new("SubscriptionStarted"), None, MESSAGE)?
+        .build();
+    let message = event.messages().next().unwrap();
+
+    let map = FieldMap::new()
+        .value("resubscriptionId", |started: &mut Started, id| started.resubscription_id = id)
+        .value("reason", |started: &mut Started, reason| started.reason = reason)
+        .field("receivedFrom", |started, element| {
+            started.address = element.get_element("address")?.get_at(0)?;
+            Ok(())
+        });
+    run("field map", &message, |message| {
+        let mut started = Started::default();
+        map.apply_message(message, &mut started).unwrap();
+        black_box(&started);
+    });
+
+    run("serde    ", &message, |message| {
+        let started: StartedSerde = from_element(message.element()).unwrap();
+        black_box(&started);
+    });
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//! Compare decoding ticks into fresh values with decoding them into pooled
+//! values, run with `cargo bench --bench pool`
+use blpapi::pool::{Pool, Recycle};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+const TICKS: usize = 1_000_000;
+
+/// Count the allocations on top of the system allocator
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// A typical decoded market data tick
+#[derive(Default)]
+struct Tick {
+    security: String,
+    conditions: String,
+    sizes: Vec<f64>,
+    price: f64,
+}
+
+impl Recycle for Tick {
+    fn recycle(&mut self) {
+        self.security.clear();
+        self.conditions.clear();
+        self.sizes.clear();
+        self.price = 0.;
+    }
+}
+
+fn decode(tick: &mut Tick, i: usize) {
+    tick.security.push_str("IBM US Equity");
+    tick.conditions.push_str("R6,IS");
+    tick.sizes.extend_from_slice(&[100., 200., 300.]);
+    tick.price = i as f64;
+}
+
+fn run<F: FnMut(usize)>(name: &str, mut f: F) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for i in 0..TICKS {
+        f(i);
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!(
+        "{}: {:?} ({:?}/tick), {} allocations",
+        name,
+        elapsed,
+        elapsed / TICKS as u32,
+        allocations
+    );
+}
+
+fn main() {
+    run("fresh ", |i| {
+        let mut tick = Tick::default();
+        decode(&mut tick, i);
+        black_box(&tick);
+    });
+
+    let pool: Pool<Tick> = Pool::new();
+    run("pooled", |i| {
+        let mut tick = pool.get();
+        decode(&mut tick, i);
+        black_box(&*tick);
+    });
+}
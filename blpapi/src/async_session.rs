@@ -0,0 +1,117 @@
+//! Tokio integration
+//!
+//! An `AsyncSession` is an asynchronous `Session` whose event handler
+//! forwards every event to a bounded channel, exposed as a `Stream`. When
+//! the channel is full the blpapi dispatcher thread waits for the
+//! application to catch up. Dropping the `AsyncSession` closes the channel
+//! first, so that the dispatcher thread is never left waiting.
+use crate::{
+    event::Event,
+    eventdispatcher::EventDispatcher,
+    session::Session,
+    session_options::SessionOptions,
+    Error,
+};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::sync::mpsc;
+use tokio_stream::Stream;
+
+/// Default number of events buffered before the dispatcher thread blocks
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// A `Session` delivering its events as a `Stream`
+pub struct AsyncSession {
+    // declared first so the session is destroyed before its dispatcher
+    session: Pin<Box<Session<'static>>>,
+    _dispatcher: EventDispatcher,
+    receiver: mpsc::Receiver<Event>,
+}
+
+impl AsyncSession {
+    /// Create a session with `options`, buffering at most 1024 events
+    pub fn create(options: SessionOptions) -> Result<Self, Error> {
+        AsyncSession::with_capacity(options, DEFAULT_CAPACITY)
+    }
+
+    /// Create a session with `options`, buffering at most `capacity`
+    /// events
+    pub fn with_capacity(options: SessionOptions, capacity: usize) -> Result<Self, Error> {
+        let (sender, receiver) = mpsc::channel(capacity);
+        let dispatcher = EventDispatcher::new(1);
+        let handler = move |event: &Event| {
+            // the stream may have been dropped, the events are discarded then
+            let _ = sender.blocking_send(event.clone());
+        };
+        let session = Session::create(options, Some(handler), Some(&dispatcher));
+        dispatcher.start()?;
+        Ok(AsyncSession {
+            session,
+            _dispatcher: dispatcher,
+            receiver,
+        })
+    }
+
+    /// The underlying session, to start it, open services, subscribe, ...
+    pub fn session(&mut self) -> &mut Session<'static> {
+        &mut self.session
+    }
+
+    /// The events of the session, in the order they were received
+    pub fn events(&mut self) -> impl Stream<Item = Event> + '_ {
+        EventStream { receiver: &mut self.receiver }
+    }
+
+    /// Wait for the next event of the session
+    pub async fn next_event(&mut self) -> Option<Event> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for AsyncSession {
+    fn drop(&mut self) {
+        // the session waits for its dispatcher thread when destroyed, which
+        // may be blocked on a full channel: discard the pending events
+        self.receiver.close();
+        while self.receiver.try_recv().is_ok() {}
+    }
+}
+
+struct EventStream<'s> {
+    receiver: &'s mut mpsc::Receiver<Event>,
+}
+
+impl Stream for EventStream<'_> {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Event>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    #[test]
+    fn test_drop_with_full_channel() -> Result<(), Error> {
+        let options = SessionOptions::default()
+            .with_server_host("localhost")?
+            .with_server_port(1)?
+            .with_num_start_attempts(1);
+        let mut session = AsyncSession::with_capacity(options, 1)?;
+        assert!(session.session().start_async());
+
+        // the startup failure and the termination don't fit in the channel
+        std::thread::sleep(Duration::from_millis(500));
+        let (sender, receiver) = channel();
+        std::thread::spawn(move || {
+            drop(session);
+            let _ = sender.send(());
+        });
+        assert!(receiver.recv_timeout(Duration::from_secs(10)).is_ok());
+        Ok(())
+    }
+}
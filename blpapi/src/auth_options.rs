@@ -0,0 +1,146 @@
+use crate::Error;
+use blpapi_sys::*;
+use std::ffi::CString;
+use std::ptr;
+
+/// Contains the information required to authorize an identity
+///
+/// Passed to 'Session::generate_authorized_identity' to authorize a user,
+/// an application or both.
+pub struct AuthOptions(pub(crate) *mut blpapi_AuthOptions_t);
+
+impl AuthOptions {
+    /// Create an 'AuthOptions' using the authentication configured in the
+    /// 'SessionOptions'
+    pub fn create_default() -> Result<Self, Error> {
+        let mut options = ptr::null_mut();
+        let res = unsafe { blpapi_AuthOptions_create_default(&mut options) };
+        Error::check(res)?;
+        Ok(AuthOptions(options))
+    }
+
+    /// Create an 'AuthOptions' authorizing the specified 'user'
+    pub fn for_user(user: &AuthUser) -> Result<Self, Error> {
+        let mut options = ptr::null_mut();
+        let res = unsafe { blpapi_AuthOptions_create_forUserMode(&mut options, user.0) };
+        Error::check(res)?;
+        Ok(AuthOptions(options))
+    }
+
+    /// Create an 'AuthOptions' authorizing the specified 'app'
+    pub fn for_app(app: &AuthApplication) -> Result<Self, Error> {
+        let mut options = ptr::null_mut();
+        let res = unsafe { blpapi_AuthOptions_create_forAppMode(&mut options, app.0) };
+        Error::check(res)?;
+        Ok(AuthOptions(options))
+    }
+
+    /// Create an 'AuthOptions' authorizing the specified 'user' on behalf
+    /// of the specified 'app'
+    pub fn for_user_and_app(user: &AuthUser, app: &AuthApplication) -> Result<Self, Error> {
+        let mut options = ptr::null_mut();
+        let res = unsafe { blpapi_AuthOptions_create_forUserAndAppMode(&mut options, user.0, app.0) };
+        Error::check(res)?;
+        Ok(AuthOptions(options))
+    }
+
+    /// Create an 'AuthOptions' for the specified 'token', e.g. as
+    /// generated by 'Session::generate_token' on a client side session
+    pub fn for_token(token: &str) -> Result<Self, Error> {
+        let token = CString::new(token).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let mut auth_token = ptr::null_mut();
+        let res = unsafe { blpapi_AuthToken_create(&mut auth_token, token.as_ptr()) };
+        Error::check(res)?;
+
+        let mut options = ptr::null_mut();
+        let res = unsafe { blpapi_AuthOptions_create_forToken(&mut options, auth_token) };
+        unsafe { blpapi_AuthToken_destroy(auth_token) };
+        Error::check(res)?;
+        Ok(AuthOptions(options))
+    }
+}
+
+impl Clone for AuthOptions {
+    fn clone(&self) -> Self {
+        let mut options = ptr::null_mut();
+        unsafe { blpapi_AuthOptions_duplicate(&mut options, self.0) };
+        AuthOptions(options)
+    }
+}
+
+impl Drop for AuthOptions {
+    fn drop(&mut self) {
+        unsafe { blpapi_AuthOptions_destroy(self.0) }
+    }
+}
+
+unsafe impl Send for AuthOptions {}
+unsafe impl Sync for AuthOptions {}
+
+/// The user to authorize with an 'AuthOptions'
+pub struct AuthUser(pub(crate) *mut blpapi_AuthUser_t);
+
+impl AuthUser {
+    /// The user logged on the machine (OS_LOGON)
+    pub fn with_logon_name() -> Result<Self, Error> {
+        let mut user = ptr::null_mut();
+        let res = unsafe { blpapi_AuthUser_createWithLogonName(&mut user) };
+        Error::check(res)?;
+        Ok(AuthUser(user))
+    }
+
+    /// The user identified by the specified active directory
+    /// 'property_name' of the user logged on the machine
+    /// (DIRECTORY_SERVICE)
+    pub fn with_active_directory_property(property_name: &str) -> Result<Self, Error> {
+        let property_name = CString::new(property_name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let mut user = ptr::null_mut();
+        let res = unsafe { blpapi_AuthUser_createWithActiveDirectoryProperty(&mut user, property_name.as_ptr()) };
+        Error::check(res)?;
+        Ok(AuthUser(user))
+    }
+
+    /// The user with the specified 'user_id', connecting from the
+    /// specified 'ip_address' (MANUAL_USER). Only valid together with an
+    /// application.
+    pub fn with_manual_options(user_id: &str, ip_address: &str) -> Result<Self, Error> {
+        let user_id = CString::new(user_id).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let ip_address = CString::new(ip_address).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let mut user = ptr::null_mut();
+        let res = unsafe { blpapi_AuthUser_createWithManualOptions(&mut user, user_id.as_ptr(), ip_address.as_ptr()) };
+        Error::check(res)?;
+        Ok(AuthUser(user))
+    }
+}
+
+impl Drop for AuthUser {
+    fn drop(&mut self) {
+        unsafe { blpapi_AuthUser_destroy(self.0) }
+    }
+}
+
+unsafe impl Send for AuthUser {}
+unsafe impl Sync for AuthUser {}
+
+/// The application to authorize with an 'AuthOptions' (APPLICATION)
+pub struct AuthApplication(pub(crate) *mut blpapi_AuthApplication_t);
+
+impl AuthApplication {
+    /// The application registered as the specified 'app_name'
+    pub fn new(app_name: &str) -> Result<Self, Error> {
+        let app_name = CString::new(app_name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let mut app = ptr::null_mut();
+        let res = unsafe { blpapi_AuthApplication_create(&mut app, app_name.as_ptr()) };
+        Error::check(res)?;
+        Ok(AuthApplication(app))
+    }
+}
+
+impl Drop for AuthApplication {
+    fn drop(&mut self) {
+        unsafe { blpapi_AuthApplication_destroy(self.0) }
+    }
+}
+
+unsafe impl Send for AuthApplication {}
+unsafe impl Sync for AuthApplication {}
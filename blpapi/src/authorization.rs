@@ -0,0 +1,80 @@
+//! Identity authorization (`//blp/apiauth` `AuthorizationRequest`)
+use crate::{
+    errors::ErrorInfo,
+    event::{EventQueue, EventType},
+    identity::Identity,
+    session::Session,
+    Error,
+};
+
+/// Authorization service name
+pub const APIAUTH_SERVICE: &str = "//blp/apiauth";
+
+/// How to authorize an identity with `Session::authorize`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Authorization {
+    /// A token generated by a client side session, e.g. with
+    /// `Session::generate_token_string`
+    Token(String),
+    /// A token generated by this session, using the authentication
+    /// options of its `SessionOptions`
+    SessionOptions,
+}
+
+impl From<&str> for Authorization {
+    fn from(token: &str) -> Self {
+        Authorization::Token(token.to_string())
+    }
+}
+
+impl From<String> for Authorization {
+    fn from(token: String) -> Self {
+        Authorization::Token(token)
+    }
+}
+
+impl<'a> Session<'a> {
+    /// Authorize a new identity and block until the authorization
+    /// completes.
+    ///
+    /// Generates the token first if needed, then sends an
+    /// `AuthorizationRequest` on `//blp/apiauth`. Returns the authorized
+    /// identity on `AuthorizationSuccess`, or `Error::AuthorizationFailure`
+    /// with the reason reported by bloomberg.
+    pub fn authorize<A: Into<Authorization>>(&mut self, authorization: A, timeout: Option<isize>) -> Result<Identity, Error> {
+        let token = match authorization.into() {
+            Authorization::Token(token) => token,
+            Authorization::SessionOptions => self.generate_token_string(timeout)?,
+        };
+
+        let service = self.get_or_open_service(APIAUTH_SERVICE)?;
+        let request = service.create_request("AuthorizationRequest")?;
+        request.element().set("token", token.as_str())?;
+
+        let identity = self.create_identity();
+        let token = self.cancellation_token.clone();
+        let mut queue = EventQueue::new();
+        self.send_authorization_request(&request, &identity, None, Some(&queue))?;
+
+        loop {
+            let event = queue.next_event_cancellable(timeout, token.as_ref())?;
+            match event.event_type() {
+                EventType::Response | EventType::PartialResponse | EventType::RequestStatus => {
+                    if let Some(message) = event.messages().next() {
+                        if message.message_type() == "AuthorizationSuccess" {
+                            return Ok(identity);
+                        }
+                        let reason = message
+                            .element()
+                            .get_element("reason")
+                            .map(|reason| ErrorInfo::from_element(&reason))
+                            .unwrap_or_default();
+                        return Err(Error::AuthorizationFailure(reason));
+                    }
+                }
+                EventType::Timeout => return Err(Error::TimeOut),
+                _ => (),
+            }
+        }
+    }
+}
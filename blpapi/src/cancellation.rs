@@ -0,0 +1,111 @@
+//! Cancellation of blocking operations
+//!
+//! A `CancellationToken` set on a session with
+//! `Session::set_cancellation_token` aborts the blocking helpers of the
+//! session (`ref_data`, `RequestBuilder::send_collect`, `authorize`,
+//! `open_services`, ...) with `Error::Cancelled` as soon as it is
+//! cancelled, e.g. from a shutdown signal handler, instead of waiting for
+//! the responses or the timeouts. The pending requests are cancelled when
+//! their dedicated queue is dropped.
+use crate::{
+    event::{Event, EventQueue, EventType},
+    service::Service,
+    session::Session,
+    Error,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Interval at which blocking operations check their token, in milliseconds
+const POLL_INTERVAL_MS: isize = 50;
+
+/// A flag shared between the threads waiting on blocking operations and
+/// the thread cancelling them
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Cancel all the operations using this token, or a clone of it
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Return `Error::Cancelled` if the token is cancelled
+    pub fn check(&self) -> Result<(), Error> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl EventQueue {
+    /// Same as `next_event`, returning `Error::Cancelled` once `token` is
+    /// cancelled. A TIMEOUT event is returned if no event is available
+    /// within `timeout` milliseconds.
+    pub fn next_event_cancellable(
+        &mut self,
+        timeout: Option<isize>,
+        token: Option<&CancellationToken>,
+    ) -> Result<Event, Error> {
+        let token = match token {
+            Some(token) => token,
+            None => return Ok(self.next_event(timeout)),
+        };
+        let deadline = timeout
+            .filter(|timeout| *timeout > 0)
+            .map(|timeout| Instant::now() + Duration::from_millis(timeout as u64));
+        loop {
+            token.check()?;
+            let wait = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now()).as_millis() as isize;
+                    remaining.clamp(1, POLL_INTERVAL_MS)
+                }
+                None => POLL_INTERVAL_MS,
+            };
+            let event = self.next_event(Some(wait));
+            if event.event_type() != EventType::Timeout {
+                return Ok(event);
+            }
+            if matches!(deadline, Some(deadline) if Instant::now() >= deadline) {
+                return Ok(event);
+            }
+        }
+    }
+}
+
+impl<'a> Session<'a> {
+    /// Abort the blocking operations of this session once `token` is
+    /// cancelled, or never if `None`
+    pub fn set_cancellation_token(&mut self, token: Option<CancellationToken>) {
+        self.cancellation_token = token;
+    }
+
+    pub fn cancellation_token(&self) -> Option<&CancellationToken> {
+        self.cancellation_token.as_ref()
+    }
+
+    /// Open all the `services`, in order, stopping at the first one which
+    /// cannot be opened or when the cancellation token is cancelled
+    pub fn open_services(&mut self, services: &[&str]) -> Result<Vec<Service>, Error> {
+        let mut opened = Vec::with_capacity(services.len());
+        for service in services {
+            if let Some(token) = &self.cancellation_token {
+                token.check()?;
+            }
+            opened.push(self.get_or_open_service(service)?);
+        }
+        Ok(opened)
+    }
+}
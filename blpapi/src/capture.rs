@@ -0,0 +1,232 @@
+//! Capture of inbound messages for archiving
+//!
+//! A `CaptureHook` hands every message received by a session, with its
+//! metadata, to an `Archiver` running on a dedicated thread. It is called
+//! from the session event handler before the events are processed, e.g.
+//! `let handler = hook.wrap(handler);`.
+//!
+//! Messages are queued to the archiver thread; the `Backpressure` policy
+//! decides what happens when the archiver cannot keep up.
+//!
+//! `Capture::captured_at` is when the hook got the event, i.e. when the
+//! session dispatched it: it lags the network receive time under load.
+//! The latter is `Capture::time_received`, for subscription data when
+//! enabled with `SessionOptions::with_record_subscription_data_receive_times`.
+use crate::correlation_id::CorrelationId;
+use crate::errors::Error;
+use crate::event::{Event, EventType};
+use crate::message::Message;
+use crate::timepoint::TimePoint;
+use blpapi_sys::*;
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::thread::{self, JoinHandle};
+use std::time::SystemTime;
+
+/// A captured message and its metadata
+#[derive(Debug, Clone)]
+pub struct Capture {
+    /// Type of the event the message was part of
+    pub event_type: EventType,
+    /// Name of the service the message belongs to, if any
+    pub service: Option<String>,
+    /// Correlation ids of the message
+    pub correlation_ids: Vec<CorrelationId>,
+    /// Time the event was handed to the hook, on dispatch
+    pub captured_at: SystemTime,
+    /// Time blpapi received the message, if recorded, see
+    /// `Message::time_received`
+    pub time_received: Option<TimePoint>,
+    pub message: Message,
+}
+
+impl Capture {
+    fn new(event_type: EventType, message: Message, captured_at: SystemTime) -> Self {
+        let service = unsafe {
+            let service = blpapi_Message_service(message.0);
+            if service.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(blpapi_Service_name(service)).to_string_lossy().into_owned())
+            }
+        };
+        let correlation_ids = message.correlation_ids().collect();
+        let time_received = message.time_received();
+        Capture {
+            event_type,
+            service,
+            correlation_ids,
+            captured_at,
+            time_received,
+            message,
+        }
+    }
+}
+
+/// Persists captured messages
+pub trait Archiver: Send {
+    /// Archive `capture`, called in the order the messages were received
+    fn archive(&mut self, capture: Capture);
+
+    /// Called when the hook is dropped, once all the queued captures are
+    /// archived
+    fn flush(&mut self) {}
+}
+
+impl<F: FnMut(Capture) + Send> Archiver for F {
+    fn archive(&mut self, capture: Capture) {
+        self(capture)
+    }
+}
+
+/// What to do when the archiver queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backpressure {
+    /// Block the session event thread until the archiver catches up, no
+    /// message is lost
+    Block,
+    /// Drop the new messages, counted by `CaptureHook::dropped`
+    Drop,
+}
+
+/// Hands every message of the events it is given to an `Archiver`
+pub struct CaptureHook {
+    sender: Option<SyncSender<Capture>>,
+    thread: Option<JoinHandle<()>>,
+    backpressure: Backpressure,
+    dropped: AtomicU64,
+}
+
+impl CaptureHook {
+    /// Start a thread archiving the captured messages with `archiver`,
+    /// holding at most `capacity` pending messages, failing with
+    /// `Error::Io` if the thread cannot be spawned
+    pub fn new<A: Archiver + 'static>(mut archiver: A, capacity: usize, backpressure: Backpressure) -> Result<Self, Error> {
+        let (sender, receiver) = sync_channel::<Capture>(capacity);
+        let thread = thread::Builder::new()
+            .name("blpapi-capture".to_string())
+            .spawn(move || {
+                for capture in receiver {
+                    archiver.archive(capture);
+                }
+                archiver.flush();
+            })
+            .map_err(Error::Io)?;
+        Ok(CaptureHook {
+            sender: Some(sender),
+            thread: Some(thread),
+            backpressure,
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    /// Capture all the messages of `event`
+    pub fn capture(&self, event: &Event) {
+        let sender = match &self.sender {
+            Some(sender) => sender,
+            None => return,
+        };
+        let event_type = event.event_type();
+        let captured_at = SystemTime::now();
+        for message in event.messages() {
+            let capture = Capture::new(event_type, message, captured_at);
+            match self.backpressure {
+                Backpressure::Block => {
+                    // only fails if the archiver thread is gone, e.g. it panicked
+                    let _ = sender.send(capture);
+                }
+                Backpressure::Drop => {
+                    if sender.try_send(capture).is_err() {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Wrap `handler` so the events are captured before being handled
+    pub fn wrap<F>(self, mut handler: F) -> impl FnMut(&Event) + Send
+    where
+        F: FnMut(&Event) + Send,
+    {
+        move |event: &Event| {
+            self.capture(event);
+            handler(event);
+        }
+    }
+
+    /// Number of messages dropped because the archiver queue was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for CaptureHook {
+    /// Wait for the archiver to process the queued messages
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::name::Name;
+    use crate::testutil::{EventBuilder, MessageProperties};
+    use std::sync::mpsc::channel;
+
+    fn build_event(messages: usize) -> Event {
+        let mut builder = EventBuilder::new(EventType::SubscriptionStatus).unwrap();
+        for id in 0..messages {
+            let properties = MessageProperties::new()
+                .unwrap()
+                .with_correlation_ids(&[CorrelationId::new_int(id as u64, None)])
+                .unwrap();
+            builder = builder
+                .append_message_from_json(Name::new("SubscriptionStarted"), Some(properties), "{}")
+                .unwrap();
+        }
+        builder.build()
+    }
+
+    #[test]
+    fn test_capture() {
+        let (sender, receiver) = channel();
+        let hook = CaptureHook::new(move |capture: Capture| sender.send(capture).unwrap(), 16, Backpressure::Block).unwrap();
+        let before = SystemTime::now();
+        hook.capture(&build_event(2));
+        drop(hook);
+
+        let captures: Vec<Capture> = receiver.iter().collect();
+        assert_eq!(captures.len(), 2);
+        for (id, capture) in captures.iter().enumerate() {
+            assert_eq!(capture.event_type, EventType::SubscriptionStatus);
+            assert_eq!(capture.correlation_ids, [CorrelationId::new_int(id as u64, None)]);
+            assert_eq!(capture.service, None);
+            assert!(capture.captured_at >= before);
+        }
+    }
+
+    #[test]
+    fn test_drop_backpressure() {
+        let (release, blocked) = channel::<()>();
+        let (sender, receiver) = channel();
+        let archiver = move |capture: Capture| {
+            // hold the first capture until released, filling the queue
+            let _ = blocked.recv();
+            sender.send(capture).unwrap();
+        };
+        let hook = CaptureHook::new(archiver, 1, Backpressure::Drop).unwrap();
+        hook.capture(&build_event(3));
+        let dropped = hook.dropped();
+        assert!(dropped >= 1);
+
+        drop(release);
+        drop(hook);
+        assert_eq!(receiver.iter().count() as u64 + dropped, 3);
+    }
+}
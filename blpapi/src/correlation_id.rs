@@ -1,7 +1,11 @@
 use blpapi_sys::*;
-use std::os::raw::c_uint;
-use std::fmt::{Debug, Formatter};
+use std::any::Any;
+use std::os::raw::{c_int, c_uint, c_void};
+use std::fmt::{Debug, Display, Formatter};
 use std::hash::{Hash, Hasher};
+use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 
 #[derive(Debug, PartialOrd, PartialEq)]
 pub enum CorrelationType {
@@ -23,6 +27,35 @@ impl From<u32> for CorrelationType {
     }
 }
 
+/// Class id of the correlation ids generated by `Session::next_correlation_id`,
+/// the last one available so it doesn't collide with application class ids
+pub const SESSION_CLASS_ID: usize = 0xffff;
+
+static NEXT_SESSION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// The payload of pointer correlation ids created by `CorrelationId::new_ptr`
+type Payload = Arc<dyn Any + Send + Sync>;
+
+/// Tags the pointers to a `Payload`, in the user data of the managed pointer
+static PAYLOAD_TAG: u8 = 0;
+
+/// Reference counts the payload of pointer correlation ids: blpapi calls it
+/// with `BLPAPI_MANAGEDPTR_COPY` when copying a correlation id, and with
+/// `BLPAPI_MANAGEDPTR_DESTROY` when releasing one.
+unsafe extern "C" fn manage_payload(
+    managed_ptr: *mut blpapi_ManagedPtr_t,
+    src_ptr: *const blpapi_ManagedPtr_t,
+    operation: c_int,
+) -> c_int {
+    if operation == BLPAPI_MANAGEDPTR_COPY as c_int {
+        *managed_ptr = *src_ptr;
+        Arc::increment_strong_count((*src_ptr).pointer as *const Payload);
+    } else if operation == BLPAPI_MANAGEDPTR_DESTROY {
+        Arc::decrement_strong_count((*managed_ptr).pointer as *const Payload);
+    }
+    0
+}
+
 /// A Correlation Id
 pub struct CorrelationId(pub(crate) blpapi_CorrelationId_t);
 
@@ -42,9 +75,113 @@ impl CorrelationId {
         CorrelationId(inner)
     }
 
+    /// Create a correlation id carrying `value`, e.g. the context of a
+    /// subscription, retrieved with `value_ptr`.
+    ///
+    /// The value is kept alive as long as a copy of the correlation id
+    /// is held, by the application or by blpapi. Correlation ids are
+    /// compared by pointer: two calls to `new_ptr` create different ids,
+    /// even for the same `value`.
+    pub fn new_ptr<T: Any + Send + Sync>(value: Arc<T>, class_id: Option<usize>) -> Self {
+        let payload: Arc<Payload> = Arc::new(value);
+        let mut inner = blpapi_CorrelationId_t_::default();
+        inner.set_size(std::mem::size_of::<blpapi_CorrelationId_t>() as c_uint);
+        inner.set_valueType(BLPAPI_CORRELATION_TYPE_POINTER);
+        inner.set_classId(class_id.unwrap_or(0) as c_uint);
+        let mut user_data = [blpapi_ManagedPtr_t_data_::default(); 4];
+        user_data[0].ptr = &PAYLOAD_TAG as *const u8 as *mut c_void;
+        inner.value.ptrValue = blpapi_ManagedPtr_t {
+            pointer: Arc::into_raw(payload) as *mut c_void,
+            userData: user_data,
+            manager: Some(manage_payload),
+        };
+
+        CorrelationId(inner)
+    }
+
+    /// Get the value of a correlation id created by `new_ptr`, or None if
+    /// it is not a pointer correlation id or if its value is not a `T`
+    pub fn value_ptr<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        let payload = self.payload()?;
+        payload.clone().downcast::<T>().ok()
+    }
+
+    fn payload(&self) -> Option<&Payload> {
+        if self.0.valueType() != BLPAPI_CORRELATION_TYPE_POINTER {
+            return None;
+        }
+        let ptr_value = unsafe { &self.0.value.ptrValue };
+        // only the pointers of `new_ptr` are known to point to a payload
+        let tag = unsafe { ptr_value.userData[0].ptr } as *const u8;
+        if !ptr::eq(tag, &PAYLOAD_TAG) || ptr_value.pointer.is_null() {
+            return None;
+        }
+        Some(unsafe { &*(ptr_value.pointer as *const Payload) })
+    }
+
+    /// Generate a unique correlation id of class `SESSION_CLASS_ID`
+    pub(crate) fn next_session_id() -> Self {
+        let value = NEXT_SESSION_ID.fetch_add(1, Ordering::Relaxed);
+        CorrelationId::new_int(value, Some(SESSION_CLASS_ID))
+    }
+
+    /// Take a reference on a correlation id returned by blpapi, which
+    /// still owns `raw`
+    pub(crate) unsafe fn from_raw(raw: blpapi_CorrelationId_t) -> Self {
+        let mut correlation_id = CorrelationId(raw);
+        if let Some(manager) = manager(&raw) {
+            correlation_id.0.value.ptrValue = blpapi_ManagedPtr_t::default();
+            manager(&mut correlation_id.0.value.ptrValue, &raw.value.ptrValue, BLPAPI_MANAGEDPTR_COPY as c_int);
+        }
+        correlation_id
+    }
+
+    /// Start building a correlation id of class `class_id`, e.g.
+    /// `CorrelationId::with_class(2).int(42)`
+    pub fn with_class(class_id: usize) -> CorrelationIdBuilder {
+        CorrelationIdBuilder { class_id }
+    }
+
     pub fn value_type(&self) -> CorrelationType {
         CorrelationType::from(self.0.valueType())
     }
+
+    /// Get the class id, 0 if none was given
+    pub fn class_id(&self) -> usize {
+        self.0.classId() as usize
+    }
+
+    /// Get the value of an integer or autogenerated correlation id
+    pub fn int_value(&self) -> Option<u64> {
+        match self.0.valueType() {
+            BLPAPI_CORRELATION_TYPE_INT | BLPAPI_CORRELATION_TYPE_AUTOGEN => Some(unsafe { self.0.value.intValue }),
+            _ => None,
+        }
+    }
+
+    /// Return true if no value was set, i.e. created by `new_empty`
+    pub fn is_unset(&self) -> bool {
+        self.0.valueType() == BLPAPI_CORRELATION_TYPE_UNSET
+    }
+}
+
+/// A correlation id of a given class id being built, see
+/// `CorrelationId::with_class`
+#[derive(Debug, Clone, Copy)]
+pub struct CorrelationIdBuilder {
+    class_id: usize,
+}
+
+impl CorrelationIdBuilder {
+    /// Build an integer correlation id
+    pub fn int(self, value: u64) -> CorrelationId {
+        CorrelationId::new_int(value, Some(self.class_id))
+    }
+
+    /// Build a pointer correlation id, see `CorrelationId::new_ptr`
+    pub fn ptr<T: Any + Send + Sync>(self, value: Arc<T>) -> CorrelationId {
+        CorrelationId::new_ptr(value, Some(self.class_id))
+    }
 }
 
 impl Debug for CorrelationId {
@@ -68,6 +205,22 @@ impl Debug for CorrelationId {
     }
 }
 
+/// Formats as `value`, prefixed with `class_id:` if there is a class id,
+/// e.g. `42` or `2:42`. Pointer values are formatted as addresses.
+impl Display for CorrelationId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.class_id() != 0 {
+            write!(f, "{}:", self.class_id())?;
+        }
+        match self.0.valueType() {
+            BLPAPI_CORRELATION_TYPE_UNSET => f.write_str("unset"),
+            BLPAPI_CORRELATION_TYPE_POINTER => write!(f, "{:p}", unsafe { self.0.value.ptrValue.pointer }),
+            BLPAPI_CORRELATION_TYPE_AUTOGEN => write!(f, "auto-{}", unsafe { self.0.value.intValue }),
+            _ => write!(f, "{}", unsafe { self.0.value.intValue }),
+        }
+    }
+}
+
 impl PartialEq for CorrelationId {
     fn eq(&self, other: &Self) -> bool {
         if self.0.valueType() != other.0.valueType() {
@@ -110,8 +263,24 @@ impl Hash for CorrelationId {
 
 impl Clone for CorrelationId {
     fn clone(&self) -> Self {
-        // TODO: if type is pointer, we should do some extra magic
-        Self(self.0)
+        unsafe { CorrelationId::from_raw(self.0) }
+    }
+}
+
+impl Drop for CorrelationId {
+    fn drop(&mut self) {
+        if let Some(manager) = manager(&self.0) {
+            unsafe { manager(&mut self.0.value.ptrValue, ptr::null(), BLPAPI_MANAGEDPTR_DESTROY) };
+        }
+    }
+}
+
+/// The manager of a pointer correlation id, if any
+fn manager(raw: &blpapi_CorrelationId_t) -> blpapi_ManagedPtr_ManagerFunction_t {
+    if raw.valueType() == BLPAPI_CORRELATION_TYPE_POINTER {
+        unsafe { raw.value.ptrValue.manager }
+    } else {
+        None
     }
 }
 
@@ -127,4 +296,53 @@ mod tests {
         let id = CorrelationId::new_int(1, None);
         assert_eq!(unsafe { id.0.value.intValue }, 1);
     }
+
+    #[test]
+    fn correlation_accessors() {
+        let id = CorrelationId::with_class(2).int(42);
+        assert_eq!(id.class_id(), 2);
+        assert_eq!(id.int_value(), Some(42));
+        assert_eq!(id.to_string(), "2:42");
+        assert_eq!(CorrelationId::new_int(7, None).to_string(), "7");
+
+        let generated = CorrelationId::next_session_id();
+        assert_eq!(generated.class_id(), SESSION_CLASS_ID);
+        assert_ne!(generated, CorrelationId::next_session_id());
+        assert_ne!(generated, CorrelationId::new_int(generated.int_value().unwrap(), None));
+
+        let empty = CorrelationId::new_empty();
+        assert!(empty.is_unset());
+        assert_eq!(empty.int_value(), None);
+    }
+
+    #[test]
+    fn correlation_ptr() {
+        let context = Arc::new("IBM US Equity".to_string());
+        let id = CorrelationId::new_ptr(context.clone(), None);
+        let copy = id.clone();
+        assert_eq!(id, copy);
+        assert_eq!(copy.value_ptr::<String>().as_deref(), Some(&*context));
+        assert!(copy.value_ptr::<u64>().is_none());
+        assert!(CorrelationId::new_int(1, None).value_ptr::<String>().is_none());
+
+        drop(id);
+        drop(copy);
+        assert_eq!(Arc::strong_count(&context), 1);
+    }
+
+    #[test]
+    fn correlation_ptr_from_raw() {
+        let context = Arc::new(42u64);
+        let id = CorrelationId::new_ptr(context.clone(), None);
+
+        // as returned by blpapi, which keeps its own reference
+        let returned = unsafe { CorrelationId::from_raw(id.0) };
+        assert_eq!(returned, id);
+        drop(returned);
+
+        assert_eq!(Arc::strong_count(&context), 2);
+        assert_eq!(id.value_ptr::<u64>().as_deref(), Some(&42));
+        drop(id);
+        assert_eq!(Arc::strong_count(&context), 1);
+    }
 }
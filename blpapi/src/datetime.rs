@@ -34,7 +34,8 @@ pub struct Datetime(pub(crate) blpapi_Datetime_t);
 
 macro_rules! impl_getter {
     ($rust_field:ident: $type:ty, $c_field:ident, $datetime:expr) => {
-        fn $rust_field(&self) -> Option<$type> {
+        #[doc = concat!("Get the ", stringify!($rust_field), " part, if set")]
+        pub fn $rust_field(&self) -> Option<$type> {
             if self.has_part($datetime) {
                 Some(self.0.$c_field as $type)
             } else {
@@ -58,6 +59,56 @@ impl Datetime {
     impl_getter!(year: u16, year, DatetimeParts::Year);
     impl_getter!(offset: i16, offset, DatetimeParts::Offset);
 
+    /// Format this datetime as ISO 8601, e.g. `2020-01-01`, `08:05:10.250`
+    /// or `2020-01-01T09:05:10+01:00`
+    ///
+    /// The date and time parts are taken as UTC, as by the chrono and time
+    /// conversions, so they are shifted to the local time of the offset.
+    /// Datetimes without an offset are formatted as they are.
+    pub fn to_iso8601(&self) -> String {
+        let local = match self.offset() {
+            Some(offset) => self.shifted(offset as i64),
+            None => self.clone(),
+        };
+        let mut result = String::new();
+        if let (Some(year), Some(month), Some(day)) = (local.year(), local.month(), local.day()) {
+            result.push_str(&format!("{:04}-{:02}-{:02}", year, month, day));
+        }
+        if let (Some(hours), Some(minutes), Some(seconds)) = (local.hours(), local.minutes(), local.seconds()) {
+            if !result.is_empty() {
+                result.push('T');
+            }
+            result.push_str(&format!("{:02}:{:02}:{:02}", hours, minutes, seconds));
+            if let Some(milli_seconds) = local.milli_seconds() {
+                result.push_str(&format!(".{:03}", milli_seconds));
+            }
+        }
+        if let Some(offset) = self.offset() {
+            let sign = if offset < 0 { '-' } else { '+' };
+            let offset = offset.unsigned_abs();
+            result.push_str(&format!("{}{:02}:{:02}", sign, offset / 60, offset % 60));
+        }
+        result
+    }
+
+    /// Shift the time by `minutes`, carrying over to the date if any
+    fn shifted(&self, minutes: i64) -> Datetime {
+        let mut result = self.clone();
+        if let (Some(hours), Some(current)) = (self.hours(), self.minutes()) {
+            let total = hours as i64 * 60 + current as i64 + minutes;
+            result.0.hours = total.rem_euclid(24 * 60).div_euclid(60) as _;
+            result.0.minutes = total.rem_euclid(60) as _;
+            if let (Some(year), Some(month), Some(day)) = (self.year(), self.month(), self.day()) {
+                let days = days_from_civil(year as i64, month as i64, day as i64) + total.div_euclid(24 * 60);
+                let (year, month, day) = civil_from_days(days);
+                result.0.year = year as _;
+                result.0.month = month as _;
+                result.0.day = day as _;
+            }
+        }
+        result
+    }
+
     /// Write the value of this object to the specified output 'stream' in
     /// a human-readable format.
     /// Optionally specify an initial indentation 'level', whose absolute
@@ -123,6 +174,30 @@ impl Display for Datetime {
     }
 }
 
+/// Days since 1970-01-01 of a proleptic gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The `(year, month, day)` of the days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
 unsafe impl Send for Datetime {}
 unsafe impl Sync for Datetime {}
 
@@ -170,6 +245,7 @@ mod tests {
         assert_eq!(datetime.year(), Some(2020));
         assert_eq!(datetime.offset(), None);
         assert_eq!(format!("{}", datetime), "2020-01-01");
+        assert_eq!(datetime.to_iso8601(), "2020-01-01");
         assert_eq!(
             format!("{:?}", datetime),
             "Datetime[year=Some(2020), month=Some(1), day=Some(1), hours=None, minutes=None, seconds=None, milliSeconds=None, offset=None]"
@@ -198,6 +274,7 @@ mod tests {
         assert_eq!(datetime.year(), Some(2020));
         assert_eq!(datetime.offset(), None);
         assert_eq!(format!("{}", datetime), "2020-01-01T08:05:10");
+        assert_eq!(datetime.to_iso8601(), "2020-01-01T08:05:10");
         assert_eq!(
             format!("{:?}", datetime),
             "Datetime[year=Some(2020), month=Some(1), day=Some(1), hours=Some(8), minutes=Some(5), seconds=Some(10), milliSeconds=None, offset=None]"
@@ -226,16 +303,67 @@ mod tests {
         assert_eq!(datetime.year(), Some(2020));
         assert_eq!(datetime.offset(), Some(60));
         assert_eq!(format!("{}", datetime), "2020-01-01T08:05:10+01:00");
+        assert_eq!(datetime.to_iso8601(), "2020-01-01T09:05:10+01:00");
         assert_eq!(
             format!("{:?}", datetime),
             "Datetime[year=Some(2020), month=Some(1), day=Some(1), hours=Some(8), minutes=Some(5), seconds=Some(10), milliSeconds=None, offset=Some(60)]"
         );
     }
+
+    #[test]
+    fn test_iso8601() {
+        let datetime = Datetime(blpapi_Datetime_t {
+            parts: BLPAPI_DATETIME_TIMEMILLI_PART as u8 | BLPAPI_DATETIME_OFFSET_PART as u8,
+            hours: 8,
+            minutes: 5,
+            seconds: 10,
+            milliSeconds: 250,
+            month: 0,
+            day: 0,
+            year: 0,
+            offset: -330,
+        });
+        assert_eq!(datetime.to_iso8601(), "02:35:10.250-05:30");
+
+        let datetime = Datetime(blpapi_Datetime_t {
+            parts: BLPAPI_DATETIME_DATE_PART as u8 | BLPAPI_DATETIME_TIME_PART as u8 | BLPAPI_DATETIME_OFFSET_PART as u8,
+            hours: 23,
+            minutes: 30,
+            seconds: 0,
+            milliSeconds: 0,
+            month: 2,
+            day: 28,
+            year: 2024,
+            offset: 60,
+        });
+        assert_eq!(datetime.to_iso8601(), "2024-02-29T00:30:00+01:00");
+
+        let datetime = Datetime(blpapi_Datetime_t {
+            parts: BLPAPI_DATETIME_DATE_PART as u8 | BLPAPI_DATETIME_TIME_PART as u8 | BLPAPI_DATETIME_OFFSET_PART as u8,
+            hours: 1,
+            minutes: 0,
+            seconds: 0,
+            milliSeconds: 0,
+            month: 1,
+            day: 1,
+            year: 2020,
+            offset: -120,
+        });
+        assert_eq!(datetime.to_iso8601(), "2019-12-31T23:00:00-02:00");
+    }
 }
 
+#[cfg(feature = "dates")]
+pub use self::chrono::ChronoConversionError;
+
+#[cfg(feature = "time")]
+pub use self::time_rs::TimeConversionError;
+
 #[cfg(feature = "dates")]
 mod chrono {
     use super::{Datetime, DatetimeParts};
+    use crate::{element::{Element, SetValue}, errors::Error, name::Name};
+    use blpapi_sys::{BLPAPI_DATETIME_DATE_PART, BLPAPI_DATETIME_OFFSET_PART, BLPAPI_DATETIME_TIMEMILLI_PART};
     use std::convert::TryInto;
     use chrono::prelude::*;
 
@@ -246,6 +374,14 @@ mod chrono {
         InvalidOffset,
     }
 
+    impl std::fmt::Display for ChronoConversionError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl std::error::Error for ChronoConversionError {}
+
     impl TryInto<NaiveDate> for Datetime {
         type Error = ChronoConversionError;
 
@@ -329,6 +465,72 @@ mod chrono {
         }
     }
 
+    fn with_date(mut datetime: Datetime, date: &NaiveDate) -> Datetime {
+        datetime.0.parts |= BLPAPI_DATETIME_DATE_PART as u8;
+        datetime.0.year = date.year() as _;
+        datetime.0.month = date.month() as _;
+        datetime.0.day = date.day() as _;
+        datetime
+    }
+
+    fn with_time(mut datetime: Datetime, time: &NaiveTime) -> Datetime {
+        datetime.0.parts |= BLPAPI_DATETIME_TIMEMILLI_PART as u8;
+        datetime.0.hours = time.hour() as _;
+        datetime.0.minutes = time.minute() as _;
+        datetime.0.seconds = time.second() as _;
+        // leap seconds are represented as 1000 ms or more by chrono
+        datetime.0.milliSeconds = (time.nanosecond() / 1_000_000).min(999) as _;
+        datetime
+    }
+
+    impl From<NaiveDate> for Datetime {
+        fn from(date: NaiveDate) -> Self {
+            with_date(Datetime::default(), &date)
+        }
+    }
+
+    impl From<NaiveTime> for Datetime {
+        fn from(time: NaiveTime) -> Self {
+            with_time(Datetime::default(), &time)
+        }
+    }
+
+    impl From<NaiveDateTime> for Datetime {
+        fn from(datetime: NaiveDateTime) -> Self {
+            with_time(with_date(Datetime::default(), &datetime.date()), &datetime.time())
+        }
+    }
+
+    impl From<DateTime<FixedOffset>> for Datetime {
+        fn from(datetime: DateTime<FixedOffset>) -> Self {
+            // the date and time parts are in UTC, see `TryInto<DateTime<FixedOffset>>`
+            let mut result = Datetime::from(datetime.naive_utc());
+            result.0.parts |= BLPAPI_DATETIME_OFFSET_PART as u8;
+            result.0.offset = (datetime.offset().local_minus_utc() / 60) as _;
+            result
+        }
+    }
+
+    macro_rules! impl_set_value {
+        ($($ty:ty),*) => {
+            $(
+                impl SetValue for $ty {
+                    fn set_at(self, element: &mut Element, index: usize) -> Result<(), Error> {
+                        (&Datetime::from(self)).set_at(element, index)
+                    }
+                    fn set(self, element: &mut Element, name: &str) -> Result<(), Error> {
+                        (&Datetime::from(self)).set(element, name)
+                    }
+                    fn set_named(self, element: &mut Element, name: &Name) -> Result<(), Error> {
+                        (&Datetime::from(self)).set_named(element, name)
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_set_value!(NaiveDate, NaiveTime, NaiveDateTime, DateTime<FixedOffset>);
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -466,5 +668,216 @@ mod chrono {
                 ))
             );
         }
+
+        #[test]
+        fn test_iso8601() {
+            let datetime = Datetime(blpapi_Datetime_t {
+                parts: BLPAPI_DATETIME_DATE_PART as u8 | BLPAPI_DATETIME_TIMEMILLI_PART as u8 | BLPAPI_DATETIME_OFFSET_PART as u8,
+                hours: 22,
+                minutes: 5,
+                seconds: 10,
+                milliSeconds: 250,
+                month: 12,
+                day: 31,
+                year: 2020,
+                offset: 330,
+            });
+            let expected: DateTime<FixedOffset> = datetime.clone().try_into().unwrap();
+            assert_eq!(DateTime::parse_from_rfc3339(&datetime.to_iso8601()), Ok(expected));
+
+            let datetime = Datetime::from(NaiveDateTime::new(NaiveDate::from_ymd(2020, 1, 1), NaiveTime::from_hms(8, 5, 10)));
+            let expected: NaiveDateTime = datetime.clone().try_into().unwrap();
+            assert_eq!(datetime.to_iso8601().parse(), Ok(expected));
+        }
+
+        #[test]
+        fn test_from_chrono() {
+            let datetime = Datetime::from(DateTime::<FixedOffset>::from_utc(
+                NaiveDateTime::new(NaiveDate::from_ymd(2020, 1, 1), NaiveTime::from_hms_milli(8, 5, 10, 250)),
+                FixedOffset::east(60 * 60)
+            ));
+            assert_eq!(datetime.year(), Some(2020));
+            assert_eq!(datetime.month(), Some(1));
+            assert_eq!(datetime.day(), Some(1));
+            assert_eq!(datetime.hours(), Some(8));
+            assert_eq!(datetime.minutes(), Some(5));
+            assert_eq!(datetime.seconds(), Some(10));
+            assert_eq!(datetime.milli_seconds(), Some(250));
+            assert_eq!(datetime.offset(), Some(60));
+
+            let date = Datetime::from(NaiveDate::from_ymd(2020, 1, 1));
+            assert_eq!(date.clone().try_into(), Ok(NaiveDate::from_ymd(2020, 1, 1)));
+            assert_eq!(TryInto::<NaiveTime>::try_into(date), Err(ChronoConversionError::MissingParts));
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_rs {
+    use super::{Datetime, DatetimeParts};
+    use crate::{element::{Element, SetValue}, errors::Error, name::Name};
+    use blpapi_sys::{BLPAPI_DATETIME_DATE_PART, BLPAPI_DATETIME_OFFSET_PART, BLPAPI_DATETIME_TIMEMILLI_PART};
+    use std::convert::{TryFrom, TryInto};
+    use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+    #[derive(Debug, Ord, PartialOrd, Eq, PartialEq)]
+    pub enum TimeConversionError {
+        MissingParts,
+        InvalidDateTime,
+        InvalidOffset,
+    }
+
+    impl std::fmt::Display for TimeConversionError {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl std::error::Error for TimeConversionError {}
+
+    impl TryInto<Date> for Datetime {
+        type Error = TimeConversionError;
+
+        fn try_into(self) -> Result<Date, Self::Error> {
+            if !self.has_part(DatetimeParts::Year) || !self.has_part(DatetimeParts::Month) || !self.has_part(DatetimeParts::Day) {
+                return Err(TimeConversionError::MissingParts);
+            }
+
+            let month = Month::try_from(self.0.month).map_err(|_| TimeConversionError::InvalidDateTime)?;
+            Date::from_calendar_date(self.0.year as i32, month, self.0.day)
+                .map_err(|_| TimeConversionError::InvalidDateTime)
+        }
+    }
+
+    impl TryInto<Time> for Datetime {
+        type Error = TimeConversionError;
+
+        fn try_into(self) -> Result<Time, Self::Error> {
+            if !self.has_part(DatetimeParts::Hour) ||
+                !self.has_part(DatetimeParts::Minute) ||
+                !self.has_part(DatetimeParts::Second) {
+                return Err(TimeConversionError::MissingParts);
+            }
+
+            let milli_seconds = if self.has_part(DatetimeParts::FractionalSecond) {
+                self.0.milliSeconds
+            } else {
+                0
+            };
+            Time::from_hms_milli(self.0.hours, self.0.minutes, self.0.seconds, milli_seconds)
+                .map_err(|_| TimeConversionError::InvalidDateTime)
+        }
+    }
+
+    impl TryInto<PrimitiveDateTime> for Datetime {
+        type Error = TimeConversionError;
+
+        fn try_into(self) -> Result<PrimitiveDateTime, Self::Error> {
+            Ok(PrimitiveDateTime::new(self.clone().try_into()?, self.try_into()?))
+        }
+    }
+
+    impl TryInto<OffsetDateTime> for Datetime {
+        type Error = TimeConversionError;
+
+        fn try_into(self) -> Result<OffsetDateTime, Self::Error> {
+            if !self.has_part(DatetimeParts::Offset) {
+                return Err(TimeConversionError::MissingParts);
+            }
+
+            let offset = UtcOffset::from_whole_seconds(self.0.offset as i32 * 60)
+                .map_err(|_| TimeConversionError::InvalidOffset)?;
+            let datetime: PrimitiveDateTime = self.try_into()?;
+            Ok(datetime.assume_utc().to_offset(offset))
+        }
+    }
+
+    fn with_date(mut datetime: Datetime, date: &Date) -> Datetime {
+        datetime.0.parts |= BLPAPI_DATETIME_DATE_PART as u8;
+        datetime.0.year = date.year() as _;
+        datetime.0.month = u8::from(date.month()) as _;
+        datetime.0.day = date.day() as _;
+        datetime
+    }
+
+    fn with_time(mut datetime: Datetime, time: &Time) -> Datetime {
+        datetime.0.parts |= BLPAPI_DATETIME_TIMEMILLI_PART as u8;
+        datetime.0.hours = time.hour() as _;
+        datetime.0.minutes = time.minute() as _;
+        datetime.0.seconds = time.second() as _;
+        datetime.0.milliSeconds = time.millisecond() as _;
+        datetime
+    }
+
+    impl From<Date> for Datetime {
+        fn from(date: Date) -> Self {
+            with_date(Datetime::default(), &date)
+        }
+    }
+
+    impl From<Time> for Datetime {
+        fn from(time: Time) -> Self {
+            with_time(Datetime::default(), &time)
+        }
     }
-}
\ No newline at end of file
+
+    impl From<PrimitiveDateTime> for Datetime {
+        fn from(datetime: PrimitiveDateTime) -> Self {
+            with_time(with_date(Datetime::default(), &datetime.date()), &datetime.time())
+        }
+    }
+
+    impl From<OffsetDateTime> for Datetime {
+        fn from(datetime: OffsetDateTime) -> Self {
+            // the date and time parts are in UTC, as for chrono
+            let utc = datetime.to_offset(UtcOffset::UTC);
+            let mut result = Datetime::from(PrimitiveDateTime::new(utc.date(), utc.time()));
+            result.0.parts |= BLPAPI_DATETIME_OFFSET_PART as u8;
+            result.0.offset = datetime.offset().whole_minutes() as _;
+            result
+        }
+    }
+
+    macro_rules! impl_set_value {
+        ($($ty:ty),*) => {
+            $(
+                impl SetValue for $ty {
+                    fn set_at(self, element: &mut Element, index: usize) -> Result<(), Error> {
+                        (&Datetime::from(self)).set_at(element, index)
+                    }
+                    fn set(self, element: &mut Element, name: &str) -> Result<(), Error> {
+                        (&Datetime::from(self)).set(element, name)
+                    }
+                    fn set_named(self, element: &mut Element, name: &Name) -> Result<(), Error> {
+                        (&Datetime::from(self)).set_named(element, name)
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_set_value!(Date, Time, PrimitiveDateTime, OffsetDateTime);
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_round_trip() {
+            let date = Date::from_calendar_date(2020, Month::January, 1).unwrap();
+            let time = Time::from_hms_milli(8, 5, 10, 250).unwrap();
+            let offset = UtcOffset::from_whole_seconds(60 * 60).unwrap();
+            let datetime = PrimitiveDateTime::new(date, time).assume_utc().to_offset(offset);
+
+            let converted = Datetime::from(datetime);
+            assert_eq!(converted.hours(), Some(8));
+            assert_eq!(converted.offset(), Some(60));
+            assert_eq!(converted.clone().try_into(), Ok(date));
+            assert_eq!(converted.clone().try_into(), Ok(time));
+            assert_eq!(converted.try_into(), Ok(datetime));
+
+            let date_only = Datetime::from(date);
+            assert_eq!(TryInto::<Time>::try_into(date_only), Err(TimeConversionError::MissingParts));
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Decimal values
+//!
+//! `DataType::Decimal` elements have no native getter: reading them as
+//! `f64` rounds the value. `Decimal` keeps the string representation sent
+//! by bloomberg instead, and converts to `rust_decimal::Decimal` with the
+//! `rust_decimal` feature.
+use crate::{
+    element::{Element, GetValue},
+    errors::BlpApiError,
+    Error,
+};
+use std::fmt::{Display, Formatter};
+
+/// A decimal value, as its exact string representation
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal(String);
+
+impl Decimal {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Convert to the closest `f64`
+    pub fn to_f64(&self) -> Result<f64, Error> {
+        self.0.parse().map_err(|_| {
+            let message = format!("{} is not a valid decimal", self.0);
+            Error::BlpApiError(BlpApiError::InvalidConversion(message))
+        })
+    }
+}
+
+impl<'e> GetValue<'e> for Decimal {
+    fn get_at(element: &'e Element, index: usize) -> Result<Self, Error> {
+        String::get_at(element, index).map(Decimal)
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl<'de> serde::Deserialize<'de> for Decimal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: serde::Deserializer<'de>
+    {
+        String::deserialize(deserializer).map(Decimal)
+    }
+}
+
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal_support {
+    use super::Decimal;
+    use crate::{
+        element::{Element, GetValue},
+        errors::BlpApiError,
+        Error,
+    };
+    use std::convert::TryFrom;
+    use std::str::FromStr;
+
+    impl TryFrom<Decimal> for rust_decimal::Decimal {
+        type Error = rust_decimal::Error;
+
+        fn try_from(value: Decimal) -> Result<Self, Self::Error> {
+            rust_decimal::Decimal::from_str(&value.0)
+                .or_else(|_| rust_decimal::Decimal::from_scientific(&value.0))
+        }
+    }
+
+    impl<'e> GetValue<'e> for rust_decimal::Decimal {
+        fn get_at(element: &'e Element, index: usize) -> Result<Self, Error> {
+            let value = Decimal::get_at(element, index)?;
+            rust_decimal::Decimal::try_from(value).map_err(|err| {
+                Error::BlpApiError(BlpApiError::InvalidConversion(err.to_string()))
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        let value = Decimal("123.4500000001".to_string());
+        assert_eq!(value.to_f64().unwrap(), 123.4500000001);
+        assert!(Decimal("abc".to_string()).to_f64().is_err());
+
+        #[cfg(feature = "rust_decimal")]
+        {
+            use std::convert::TryFrom;
+            assert_eq!(rust_decimal::Decimal::try_from(value).unwrap().to_string(), "123.4500000001");
+            let scientific = Decimal("1.5e-3".to_string());
+            assert_eq!(rust_decimal::Decimal::try_from(scientific).unwrap().to_string(), "0.0015");
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Per subscription delivery of messages
+//!
+//! A `SubscriptionDispatcher` is fed every event from the session event
+//! handler and forwards each message to the handler registered for its
+//! correlation id. Each subscription chooses where its handler runs:
+//!
+//! - on the session event thread, which is the cheapest but blocks the
+//!   delivery of all other events while the handler runs,
+//! - on a shared pool of worker threads,
+//! - on a thread dedicated to the subscription, for expensive handlers.
+//!
+//! Messages of a given subscription are always handled in order, by a
+//! single thread at a time. Queues are bounded: when a queue is full the
+//! session event thread blocks until the handler catches up.
+use crate::correlation_id::CorrelationId;
+use crate::event::Event;
+use crate::message::Message;
+use crate::Error;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+type Handler = Arc<Mutex<dyn FnMut(&Message) + Send>>;
+type Job = (Handler, Message);
+
+/// Where the handler of a subscription is called
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryPolicy {
+    /// Call the handler directly on the session event thread
+    EventThread,
+    /// Queue the messages to the shared worker pool of the dispatcher
+    WorkerPool,
+    /// Queue the messages to a new thread dedicated to the subscription,
+    /// holding at most `capacity` pending messages
+    Dedicated { capacity: usize },
+}
+
+struct Worker {
+    sender: Option<SyncSender<Job>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Worker {
+    fn spawn(name: String, capacity: usize) -> Result<Self, Error> {
+        let (sender, receiver) = sync_channel(capacity);
+        let thread = thread::Builder::new()
+            .name(name)
+            .spawn(move || run(receiver))
+            .map_err(Error::Io)?;
+        Ok(Worker { sender: Some(sender), thread: Some(thread) })
+    }
+
+    fn send(&self, job: Job) {
+        if let Some(sender) = &self.sender {
+            // only fails if the thread is gone, e.g. because a handler panicked
+            let _ = sender.send(job);
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run(receiver: Receiver<Job>) {
+    for (handler, message) in receiver {
+        let mut handler = handler.lock().unwrap_or_else(|e| e.into_inner());
+        (*handler)(&message);
+    }
+}
+
+#[derive(Clone)]
+enum Route {
+    EventThread(Handler),
+    Pool(Handler, usize),
+    Dedicated(Handler, Arc<Worker>),
+}
+
+/// Dispatch subscription messages to per subscription handlers
+///
+/// Usually shared through an `Arc` with the session event handler, which
+/// calls `dispatch` for every event.
+pub struct SubscriptionDispatcher {
+    routes: Mutex<HashMap<CorrelationId, Route>>,
+    pool: Vec<Worker>,
+    next_worker: AtomicUsize,
+}
+
+impl SubscriptionDispatcher {
+    /// Create a dispatcher with a pool of `pool_threads` workers, each
+    /// holding at most `pool_capacity` pending messages
+    ///
+    /// Fails if a worker thread cannot be spawned, in which case the
+    /// workers spawned so far are stopped.
+    pub fn new(pool_threads: usize, pool_capacity: usize) -> Result<Self, Error> {
+        let pool = (0..pool_threads)
+            .map(|index| Worker::spawn(format!("blpapi-delivery-{}", index), pool_capacity))
+            .collect::<Result<_, _>>()?;
+        Ok(SubscriptionDispatcher {
+            routes: Mutex::new(HashMap::new()),
+            pool,
+            next_worker: AtomicUsize::new(0),
+        })
+    }
+
+    /// Deliver the messages of the subscription identified by
+    /// `correlation_id` to `handler`, according to `policy`.
+    ///
+    /// Replaces any handler previously registered for `correlation_id`.
+    /// Subscriptions registered with `DeliveryPolicy::WorkerPool` are
+    /// handled on the session event thread if the pool is empty.
+    ///
+    /// Fails if the thread of a `DeliveryPolicy::Dedicated` subscription
+    /// cannot be spawned, leaving any previous handler registered.
+    pub fn register<F>(&self, correlation_id: CorrelationId, policy: DeliveryPolicy, handler: F) -> Result<(), Error>
+    where
+        F: FnMut(&Message) + Send + 'static,
+    {
+        let handler: Handler = Arc::new(Mutex::new(handler));
+        let route = match policy {
+            DeliveryPolicy::WorkerPool if !self.pool.is_empty() => {
+                // topics are pinned to a single worker to keep their messages ordered
+                let worker = self.next_worker.fetch_add(1, Ordering::Relaxed) % self.pool.len();
+                Route::Pool(handler, worker)
+            }
+            DeliveryPolicy::EventThread | DeliveryPolicy::WorkerPool => Route::EventThread(handler),
+            DeliveryPolicy::Dedicated { capacity } => {
+                let worker = Worker::spawn(format!("blpapi-delivery-{:?}", correlation_id), capacity)?;
+                Route::Dedicated(handler, Arc::new(worker))
+            }
+        };
+        self.routes().insert(correlation_id, route);
+        Ok(())
+    }
+
+    /// Stop delivering the messages of the subscription identified by
+    /// `correlation_id`.
+    ///
+    /// Messages already queued are still handled. A dedicated thread is
+    /// stopped once its queue is drained.
+    pub fn unregister(&self, correlation_id: &CorrelationId) {
+        self.routes().remove(correlation_id);
+    }
+
+    /// Forward every message of `event` to the handlers of its
+    /// correlation ids, ignoring unknown correlation ids
+    pub fn dispatch(&self, event: &Event) {
+        for message in event.messages() {
+            for correlation_id in message.correlation_ids() {
+                self.dispatch_message(&correlation_id, &message);
+            }
+        }
+    }
+
+    fn dispatch_message(&self, correlation_id: &CorrelationId, message: &Message) {
+        // do not hold the routes lock while blocking on a full queue
+        let route = match self.routes().get(correlation_id) {
+            Some(route) => route.clone(),
+            None => return,
+        };
+        match route {
+            Route::EventThread(handler) => {
+                let mut handler = handler.lock().unwrap_or_else(|e| e.into_inner());
+                (*handler)(message);
+            }
+            Route::Pool(handler, worker) => self.pool[worker].send((handler, message.clone())),
+            Route::Dedicated(handler, worker) => worker.send((handler, message.clone())),
+        }
+    }
+
+    fn routes(&self) -> std::sync::MutexGuard<'_, HashMap<CorrelationId, Route>> {
+        self.routes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{EventBuilder, MessageProperties};
+    use crate::event::EventType;
+    use crate::name::Name;
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    #[test]
+    fn test_dispatch() -> Result<(), Error> {
+        let dispatcher = SubscriptionDispatcher::new(1, 16)?;
+        let (sender, receiver) = channel();
+        for (id, policy) in [
+            (1, DeliveryPolicy::EventThread),
+            (2, DeliveryPolicy::WorkerPool),
+            (3, DeliveryPolicy::Dedicated { capacity: 1 }),
+        ] {
+            let sender = Mutex::new(sender.clone());
+            dispatcher.register(CorrelationId::new_int(id, None), policy, move |_| {
+                sender.lock().unwrap().send(id).unwrap();
+            })?;
+        }
+
+        let mut builder = EventBuilder::new(EventType::SubscriptionStatus)?;
+        for id in 1..=4 {
+            let properties = MessageProperties::new()?
+                .with_correlation_ids(&[CorrelationId::new_int(id, None)])?;
+            builder = builder.append_message_from_json(Name::new("SubscriptionStarted"), Some(properties), "{}")?;
+        }
+        dispatcher.dispatch(&builder.build());
+
+        let mut received: Vec<u64> = (0..3)
+            .map(|_| receiver.recv_timeout(Duration::from_secs(5)).unwrap())
+            .collect();
+        received.sort_unstable();
+        assert_eq!(received, vec![1, 2, 3]);
+        Ok(())
+    }
+}
@@ -0,0 +1,23 @@
+//! Internal diagnostics of blpapi
+//!
+//! Long running services can log `memory_info` periodically to monitor the
+//! memory used by the library itself.
+use crate::Error;
+use blpapi_sys::*;
+use std::os::raw::c_char;
+
+/// Describe the memory used by blpapi, in an unspecified format
+pub fn memory_info() -> Result<String, Error> {
+    let len = unsafe { blpapi_DiagnosticsUtil_memoryInfo(std::ptr::null_mut(), 0) };
+    if len < 0 {
+        Error::check(len)?;
+    }
+    let mut buffer = vec![0u8; len as usize + 1];
+    let res = unsafe { blpapi_DiagnosticsUtil_memoryInfo(buffer.as_mut_ptr() as *mut c_char, buffer.len()) };
+    if res < 0 {
+        Error::check(res)?;
+    }
+    let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    buffer.truncate(end);
+    Ok(String::from_utf8_lossy(&buffer).into_owned())
+}
@@ -1,14 +1,17 @@
-use crate::{datetime::Datetime, name::Name, Error};
+use crate::{datetime::Datetime, errors::BlpApiError, name::Name, schema::SchemaElementDefinition, Error};
 use blpapi_sys::*;
 use std::{
     ffi::{CStr, CString},
     marker::PhantomData,
-    os::raw::c_int,
+    os::raw::{c_char, c_int},
     ptr,
 };
 use std::fmt::{Display, Debug, Formatter};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::iter::FusedIterator;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum DataType {
     /// Bool
     Bool,
@@ -96,6 +99,11 @@ impl Element<'_> {
         DataType::from(data_type)
     }
 
+    /// Get the schema definition of this element
+    pub fn definition(&self) -> SchemaElementDefinition<'_> {
+        SchemaElementDefinition::new(unsafe { blpapi_Element_definition(self.ptr) })
+    }
+
     /// Has element
     pub fn has_element(&self, name: &str, exclude_null_elements: bool) -> bool {
         let name = CString::new(name).unwrap();
@@ -128,23 +136,32 @@ impl Element<'_> {
     }
 
     /// Get element from its name
+    ///
+    /// Fails with `BlpApiError::ItemNotFound` if there is no such
+    /// sub-element
     pub fn get_element(&self, name: &str) -> Result<Element, Error> {
         let mut element = ptr::null_mut();
-        let name = CString::new(name).unwrap();
+        let cname = CString::new(name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
         let res = unsafe {
             blpapi_Element_getElement(
                 self.ptr,
                 &mut element,
-                name.as_ptr(),
+                cname.as_ptr(),
                 ptr::null(),
             )
         };
+        if res != 0 && !self.has_element(name, false) {
+            return Err(self.not_found(name));
+        }
         Error::check(res)?;
 
         Ok(Element { ptr: element, _marker: PhantomData })
     }
 
     /// Get element from its name
+    ///
+    /// Fails with `BlpApiError::ItemNotFound` if there is no such
+    /// sub-element
     pub fn get_named_element(&self, named_element: &Name) -> Result<Element, Error> {
         let mut element = ptr::null_mut();
         let res = unsafe {
@@ -155,11 +172,37 @@ impl Element<'_> {
                 named_element.0,
             )
         };
+        if res != 0 && !self.has_named_element(named_element, false) {
+            return Err(self.not_found(&named_element.to_string_lossy()));
+        }
         Error::check(res)?;
 
         Ok(Element { ptr: element, _marker: PhantomData })
     }
 
+    /// Get element from its name, or None if there is no such sub-element
+    pub fn try_get_element(&self, name: &str) -> Option<Element> {
+        if self.has_element(name, false) {
+            self.get_element(name).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Get element from its name, or None if there is no such sub-element
+    pub fn try_get_named_element(&self, named_element: &Name) -> Option<Element> {
+        if self.has_named_element(named_element, false) {
+            self.get_named_element(named_element).ok()
+        } else {
+            None
+        }
+    }
+
+    fn not_found(&self, name: &str) -> Error {
+        let message = format!("no element {} in {}", name, self.string_name());
+        Error::BlpApiError(BlpApiError::ItemNotFound(message))
+    }
+
     /// Get element at index
     pub fn get_element_at(&self, index: usize) -> Result<Element, Error> {
         let mut element = ptr::null_mut();
@@ -178,6 +221,43 @@ impl Element<'_> {
         }
     }
 
+    /// Select the sub-element `name` of this choice element, returning it
+    pub fn set_choice(&mut self, name: &str) -> Result<Element<'_>, Error> {
+        let cname = CString::new(name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let mut ptr = ptr::null_mut();
+        let res = unsafe { blpapi_Element_setChoice(self.ptr, &mut ptr, cname.as_ptr(), ptr::null(), 0) };
+        Error::check(res)?;
+
+        Ok(Element { ptr, _marker: PhantomData })
+    }
+
+    /// Select the sub-element `name` of this choice element, returning it
+    pub fn set_named_choice(&mut self, name: &Name) -> Result<Element<'_>, Error> {
+        let mut ptr = ptr::null_mut();
+        let res = unsafe { blpapi_Element_setChoice(self.ptr, &mut ptr, ptr::null(), name.0, 0) };
+        Error::check(res)?;
+
+        Ok(Element { ptr, _marker: PhantomData })
+    }
+
+    /// Get the selected sub-element of this choice element
+    pub fn get_choice(&self) -> Result<Element<'_>, Error> {
+        let mut ptr = ptr::null_mut();
+        let res = unsafe { blpapi_Element_getChoice(self.ptr, &mut ptr) };
+        Error::check(res)?;
+
+        Ok(Element { ptr, _marker: PhantomData })
+    }
+
+    /// Fill this element with an `ElementBuilder`, e.g.
+    /// `element.build(|b| b.set("fieldId", "PX_LAST")?.sequence("value", |v| ..).map(drop))`
+    pub fn build<F>(&mut self, f: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut ElementBuilder<'_>) -> Result<(), Error>,
+    {
+        f(&mut ElementBuilder::new(self.ptr))
+    }
+
     /// Append a new element with `value`
     pub fn append<V: SetValue>(&mut self, value: V) -> Result<(), Error> {
         value.append_to(self)
@@ -210,6 +290,18 @@ impl Element<'_> {
         V::get_at(self, index)
     }
 
+    /// Read the string value at `index` into `buffer`, reusing its
+    /// allocation
+    pub fn get_string_into(&self, index: usize, buffer: &mut String) -> Result<(), Error> {
+        let mut tmp = ptr::null();
+        let res = unsafe { blpapi_Element_getValueAsString(self.ptr, &mut tmp, index) };
+        Error::check(res)?;
+
+        buffer.clear();
+        buffer.push_str(&unsafe { CStr::from_ptr(tmp) }.to_string_lossy());
+        Ok(())
+    }
+
     /// Set value at given index
     pub fn set_at<V: SetValue>(&mut self, index: usize, value: V) -> Result<(), Error> {
         value.set_at(self, index)
@@ -236,19 +328,45 @@ impl Element<'_> {
             len: self.num_values(),
             element: self,
             i: 0,
+            truncated: false,
             _phantom: PhantomData,
         }
     }
 
+    /// Get an iterator over the values, yielding an error for each value
+    /// which cannot be converted instead of ending the iteration
+    pub fn values_checked<'e, V: GetValue<'e>>(&'e self) -> ValuesChecked<'e, V> {
+        ValuesChecked {
+            len: self.num_values(),
+            element: self,
+            i: 0,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Iterate over the string values, borrowed from the element without
+    /// allocating
+    pub fn values_str(&self) -> Values<'_, &str> {
+        self.values()
+    }
+
     /// Get an iterator over the elements
     pub fn elements(&self) -> Elements {
         Elements {
             len: self.num_elements(),
             element: self,
             i: 0,
+            truncated: false,
         }
     }
 
+    /// Get the sub-elements with their names, in schema order
+    ///
+    /// Unlike collecting into a map, this keeps the original ordering.
+    pub fn to_ordered_pairs(&self) -> Vec<(Name, Element)> {
+        self.elements().map(|element| (element.name(), element)).collect()
+    }
+
     /// Return true if 'elementDefinition().maxValues() > 1' or
     /// 'elementDefinition().maxValues() == UNBOUNDED', and false otherwise.
     pub fn is_array(&self) -> bool {
@@ -263,6 +381,16 @@ impl Element<'_> {
         res != 0
     }
 
+    /// Compute the shape of this element tree: number of elements per data
+    /// type, maximum depth and total number of leaf values.
+    ///
+    /// This walks the whole tree but never converts any value.
+    pub fn summary(&self) -> ElementSummary {
+        let mut summary = ElementSummary::default();
+        summary.visit(self, 0);
+        summary
+    }
+
     /// Format this Element to the specified output 'stream' at the
     /// (absolute value of) the optionally specified indentation 'level' and
     /// return a reference to 'stream'. If 'level' is specified, optionally
@@ -301,6 +429,98 @@ impl Display for Element<'_> {
 unsafe impl Send for Element<'_> {}
 unsafe impl Sync for Element<'_> {}
 
+/// A helper to fill nested sequences, choices and arrays, each nested
+/// level being filled by a closure
+pub struct ElementBuilder<'a> {
+    element: Element<'a>,
+}
+
+impl<'a> ElementBuilder<'a> {
+    fn new(ptr: *mut blpapi_Element_t) -> Self {
+        ElementBuilder { element: Element { ptr, _marker: PhantomData } }
+    }
+
+    /// The element being filled
+    pub fn element(&mut self) -> &mut Element<'a> {
+        &mut self.element
+    }
+
+    /// Set the sub-element `name` to `value`
+    pub fn set<V: SetValue>(&mut self, name: &str, value: V) -> Result<&mut Self, Error> {
+        self.element.set(name, value)?;
+        Ok(self)
+    }
+
+    /// Append `value` to this array element
+    pub fn append<V: SetValue>(&mut self, value: V) -> Result<&mut Self, Error> {
+        self.element.append(value)?;
+        Ok(self)
+    }
+
+    /// Fill the sub-element `name`, a sequence, with `f`
+    pub fn sequence<F>(&mut self, name: &str, f: F) -> Result<&mut Self, Error>
+    where
+        F: FnOnce(&mut ElementBuilder<'_>) -> Result<(), Error>,
+    {
+        let ptr = self.element.get_element(name)?.ptr;
+        f(&mut ElementBuilder::new(ptr))?;
+        Ok(self)
+    }
+
+    /// Select the sub-element `name` of this choice element and fill it
+    /// with `f`
+    pub fn choice<F>(&mut self, name: &str, f: F) -> Result<&mut Self, Error>
+    where
+        F: FnOnce(&mut ElementBuilder<'_>) -> Result<(), Error>,
+    {
+        let ptr = self.element.set_choice(name)?.ptr;
+        f(&mut ElementBuilder::new(ptr))?;
+        Ok(self)
+    }
+
+    /// Append a new element to this array element and fill it with `f`
+    pub fn append_element<F>(&mut self, f: F) -> Result<&mut Self, Error>
+    where
+        F: FnOnce(&mut ElementBuilder<'_>) -> Result<(), Error>,
+    {
+        let ptr = self.element.append_element()?.ptr;
+        f(&mut ElementBuilder::new(ptr))?;
+        Ok(self)
+    }
+}
+
+/// The shape of an element tree, as returned by `Element::summary`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ElementSummary {
+    /// Number of elements per data type, including the root element
+    pub counts: HashMap<DataType, usize>,
+    /// Depth of the deepest element, the root element being at depth 0
+    pub max_depth: usize,
+    /// Total number of values held by non complex elements
+    pub total_values: usize,
+}
+
+impl ElementSummary {
+    fn visit(&mut self, element: &Element, depth: usize) {
+        let data_type = element.data_type();
+        *self.counts.entry(data_type).or_insert(0) += 1;
+        self.max_depth = self.max_depth.max(depth);
+
+        let is_complex = data_type == DataType::Sequence || data_type == DataType::Choice;
+        if element.is_array() && is_complex {
+            for value in element.values::<Element>() {
+                self.visit(&value, depth + 1);
+            }
+        } else if is_complex {
+            for child in element.elements() {
+                self.visit(&child, depth + 1);
+            }
+        } else {
+            self.total_values += element.num_values();
+        }
+    }
+}
+
 /// A trait to represent an Element value
 pub trait GetValue<'e>: Sized {
     /// Get value from elements by index
@@ -360,14 +580,14 @@ macro_rules! impl_value {
             }
         }
     };
-    ($ty:ty, $get_at:path, $set_at:path, $set:path, $from_bbg: expr, $to_bbg: expr) => {
+    ($ty:ty, $start:expr, $get_at:path, $set_at:path, $set:path, $from_bbg: expr, $to_bbg: expr) => {
         impl<'e> GetValue<'e> for $ty {
             fn get_at(element: &'e Element, index: usize) -> Result<Self, Error> {
-                let tmp = ptr::null_mut();
-                let res = unsafe { $get_at(element.ptr, tmp, index) };
+                let mut tmp = $start;
+                let res = unsafe { $get_at(element.ptr, &mut tmp as *mut _, index) };
                 Error::check(res)?;
 
-                Ok($from_bbg(unsafe { *tmp }))
+                Ok($from_bbg(tmp))
             }
         }
 
@@ -434,6 +654,7 @@ impl_value!(
 );
 impl_value!(
     bool,
+    0,
     blpapi_Element_getValueAsBool,
     blpapi_Element_setValueBool,
     blpapi_Element_setElementBool,
@@ -442,6 +663,7 @@ impl_value!(
 );
 impl_value!(
     Name,
+    ptr::null_mut(),
     blpapi_Element_getValueAsName,
     blpapi_Element_setValueFromName,
     blpapi_Element_setElementFromName,
@@ -449,6 +671,60 @@ impl_value!(
     |rust: Name| rust.0
 );
 
+/// The value of an enumeration element: its symbolic name and the
+/// matching string
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Enumeration {
+    name: Name,
+    value: String,
+}
+
+impl Enumeration {
+    /// The symbolic name of the value
+    pub fn name(&self) -> Name {
+        self.name
+    }
+
+    /// The value as a string
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+}
+
+impl Display for Enumeration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+impl<'e> GetValue<'e> for Enumeration {
+    fn get_at(element: &'e Element, index: usize) -> Result<Self, Error> {
+        let name = Name::get_at(element, index)?;
+        let value = name.to_string_lossy();
+        Ok(Enumeration { name, value })
+    }
+}
+
+/// Unsigned integers are read as i64 and checked to be in range, as blpapi
+/// has no unsigned data types
+macro_rules! impl_unsigned_value {
+    ($($ty:ty),*) => {
+        $(
+            impl<'e> GetValue<'e> for $ty {
+                fn get_at(element: &'e Element, index: usize) -> Result<Self, Error> {
+                    let value = i64::get_at(element, index)?;
+                    <$ty as TryFrom<i64>>::try_from(value).map_err(|_| {
+                        let message = format!("{} is out of range for {}", value, stringify!($ty));
+                        Error::BlpApiError(BlpApiError::InvalidConversion(message))
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned_value!(u8, u16, u32, u64, usize);
+
 impl<'e> GetValue<'e> for String {
     fn get_at(element: &'e Element, index: usize) -> Result<Self, Error> {
         let mut tmp = ptr::null();
@@ -471,6 +747,16 @@ impl<'e> GetValue<'e> for &'e CStr {
     }
 }
 
+impl<'e> GetValue<'e> for &'e str {
+    fn get_at(element: &'e Element, index: usize) -> Result<Self, Error> {
+        let str = <&CStr>::get_at(element, index)?;
+        str.to_str().map_err(|err| {
+            let message = format!("{} is not a valid utf-8 string: {}", element.string_name(), err);
+            Error::BlpApiError(BlpApiError::InvalidConversion(message))
+        })
+    }
+}
+
 impl<'a> SetValue for &'a str {
     fn set_at(self, element: &mut Element, index: usize) -> Result<(), Error> {
         let value = CString::new(self).unwrap();
@@ -504,6 +790,92 @@ impl<'a> SetValue for &'a str {
     }
 }
 
+impl<'e> GetValue<'e> for &'e [u8] {
+    fn get_at(element: &'e Element, index: usize) -> Result<Self, Error> {
+        let mut buffer: *const c_char = ptr::null();
+        let mut length = 0;
+        let res = unsafe { blpapi_Element_getValueAsBytes(element.ptr, &mut buffer, &mut length, index) };
+        Error::check(res)?;
+
+        if buffer.is_null() {
+            return Ok(&[]);
+        }
+        Ok(unsafe { std::slice::from_raw_parts(buffer as *const u8, length) })
+    }
+}
+
+impl<'a> SetValue for &'a [u8] {
+    fn set_at(self, element: &mut Element, index: usize) -> Result<(), Error> {
+        unsafe {
+            let res = blpapi_Element_setValueBytes(element.ptr, self.as_ptr() as *const c_char, self.len(), index);
+            Error::check(res)
+        }
+    }
+    fn set(self, element: &mut Element, name: &str) -> Result<(), Error> {
+        unsafe {
+            let named_element = ptr::null();
+            let name = CString::new(name).unwrap();
+            let res = blpapi_Element_setElementBytes(
+                element.ptr,
+                name.as_ptr(),
+                named_element,
+                self.as_ptr() as *const c_char,
+                self.len(),
+            );
+            Error::check(res)
+        }
+    }
+    fn set_named(self, element: &mut Element, named_element: &Name) -> Result<(), Error> {
+        unsafe {
+            let name = ptr::null();
+            let res = blpapi_Element_setElementBytes(
+                element.ptr,
+                name,
+                named_element.0,
+                self.as_ptr() as *const c_char,
+                self.len(),
+            );
+            Error::check(res)
+        }
+    }
+}
+
+/// An owned opaque binary value, for `DataType::ByteArray` elements
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ByteArray(pub Vec<u8>);
+
+impl<'e> GetValue<'e> for ByteArray {
+    fn get_at(element: &'e Element, index: usize) -> Result<Self, Error> {
+        <&[u8]>::get_at(element, index).map(|bytes| ByteArray(bytes.to_vec()))
+    }
+}
+
+impl<'a> SetValue for &'a ByteArray {
+    fn set_at(self, element: &mut Element, index: usize) -> Result<(), Error> {
+        self.0.as_slice().set_at(element, index)
+    }
+    fn set(self, element: &mut Element, name: &str) -> Result<(), Error> {
+        self.0.as_slice().set(element, name)
+    }
+    fn set_named(self, element: &mut Element, named_element: &Name) -> Result<(), Error> {
+        self.0.as_slice().set_named(element, named_element)
+    }
+}
+
+impl std::ops::Deref for ByteArray {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for ByteArray {
+    fn from(bytes: Vec<u8>) -> Self {
+        ByteArray(bytes)
+    }
+}
+
 impl<'e> GetValue<'e> for Datetime {
     fn get_at(element: &'e Element, index: usize) -> Result<Self, Error> {
         let mut tmp = Datetime::default();
@@ -522,7 +894,7 @@ impl<'e, T: GetValue<'e>> GetValue<'e> for Option<T> {
 
 impl<'e, T: GetValue<'e>> GetValue<'e> for Vec<T> {
     fn get_at(element: &'e Element, index: usize) -> Result<Self, Error> {
-        Ok(element.values::<T>().skip(index).collect())
+        element.values_checked::<T>().skip(index).collect()
     }
 }
 
@@ -538,7 +910,7 @@ impl<'e> GetValue<'e> for Element<'e> {
 
 impl<'e, T: GetValue<'e> + std::hash::Hash + Eq> GetValue<'e> for std::collections::HashSet<T> {
     fn get_at(element: &'e Element, index: usize) -> Result<Self, Error> {
-        Ok(element.values::<T>().skip(index).collect())
+        element.values_checked::<T>().skip(index).collect()
     }
 }
 
@@ -581,9 +953,26 @@ pub struct Values<'e, V> {
     element: &'e Element<'e>,
     i: usize,
     len: usize,
+    // whether `len` was cut at the first value which cannot be converted
+    truncated: bool,
     _phantom: PhantomData<V>,
 }
 
+impl<'e, V: GetValue<'e>> Values<'e, V> {
+    /// Get the value at `index` of the element, regardless of the
+    /// iteration progress
+    pub fn get(&self, index: usize) -> Option<V> {
+        self.element.get_at::<V>(index).ok()
+    }
+}
+
+/// Iteration ends at the first value which cannot be converted, use
+/// `Element::values_checked` to get the errors. The number of values
+/// yielded is therefore only bounded by `Element::num_values`.
+///
+/// Iterating from the back yields the same values: the first call to
+/// `next_back` converts the remaining values to find where the iteration
+/// ends.
 impl<'e, V: GetValue<'e>> Iterator for Values<'e, V> {
     type Item = V;
 
@@ -593,20 +982,92 @@ impl<'e, V: GetValue<'e>> Iterator for Values<'e, V> {
         }
         let v = self.element.get_at::<V>(self.i);
         self.i += 1;
+        if v.is_err() {
+            self.i = self.len;
+        }
         v.ok()
     }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.len - self.i))
+    }
+}
+
+impl<'e, V: GetValue<'e>> DoubleEndedIterator for Values<'e, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if !self.truncated {
+            self.truncated = true;
+            let element = self.element;
+            if let Some(end) = (self.i..self.len).find(|&i| element.get_at::<V>(i).is_err()) {
+                self.len = end;
+            }
+        }
+        if self.i == self.len {
+            return None;
+        }
+        self.len -= 1;
+        self.element.get_at::<V>(self.len).ok()
+    }
+}
+
+impl<'e, V: GetValue<'e>> FusedIterator for Values<'e, V> {}
+
+/// An iterator over values, see `Element::values_checked`
+pub struct ValuesChecked<'e, V> {
+    element: &'e Element<'e>,
+    i: usize,
+    len: usize,
+    _phantom: PhantomData<V>,
+}
+
+impl<'e, V: GetValue<'e>> Iterator for ValuesChecked<'e, V> {
+    type Item = Result<V, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.i == self.len {
+            return None;
+        }
+        let v = self.element.get_at::<V>(self.i);
+        self.i += 1;
+        Some(v)
+    }
     fn size_hint(&self) -> (usize, Option<usize>) {
         (self.len - self.i, Some(self.len - self.i))
     }
 }
 
+impl<'e, V: GetValue<'e>> DoubleEndedIterator for ValuesChecked<'e, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.i == self.len {
+            return None;
+        }
+        self.len -= 1;
+        Some(self.element.get_at::<V>(self.len))
+    }
+}
+
+impl<'e, V: GetValue<'e>> ExactSizeIterator for ValuesChecked<'e, V> {}
+
+impl<'e, V: GetValue<'e>> FusedIterator for ValuesChecked<'e, V> {}
+
 /// An iterator over elements
 pub struct Elements<'e> {
     element: &'e Element<'e>,
     i: usize,
     len: usize,
+    // whether `len` was cut at the first sub-element which cannot be accessed
+    truncated: bool,
+}
+
+impl<'e> Elements<'e> {
+    /// Get the sub-element at `index` of the element, regardless of the
+    /// iteration progress
+    pub fn get(&self, index: usize) -> Option<Element<'e>> {
+        self.element.get_element_at(index).ok()
+    }
 }
 
+/// Iteration ends at the first sub-element which cannot be accessed, from
+/// the front as from the back
 impl<'e> Iterator for Elements<'e> {
     type Item = Element<'e>;
 
@@ -616,9 +1077,105 @@ impl<'e> Iterator for Elements<'e> {
         }
         let v = self.element.get_element_at(self.i);
         self.i += 1;
+        if v.is_err() {
+            self.i = self.len;
+        }
         v.ok()
     }
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.len - self.i, Some(self.len - self.i))
+        (0, Some(self.len - self.i))
+    }
+}
+
+impl<'e> DoubleEndedIterator for Elements<'e> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if !self.truncated {
+            self.truncated = true;
+            let element = self.element;
+            if let Some(end) = (self.i..self.len).find(|&i| element.get_element_at(i).is_err()) {
+                self.len = end;
+            }
+        }
+        if self.i == self.len {
+            return None;
+        }
+        self.len -= 1;
+        self.element.get_element_at(self.len).ok()
+    }
+}
+
+impl FusedIterator for Elements<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{Event, EventType};
+    use crate::testutil::test_event;
+
+    fn build_event(json: &str) -> Result<Event, Error> {
+        test_event(EventType::SubscriptionData, "Status", None, json)
+    }
+
+    #[test]
+    fn test_bool_and_name_values() -> Result<(), Error> {
+        let event = build_event(r#"{ "active": true, "ticker": "IBM US Equity" }"#)?;
+        let message = event.messages().next().unwrap();
+        let element = message.element();
+        assert!(element.get_element("active")?.value::<bool>()?);
+        assert_eq!(element.get_element("ticker")?.value::<Name>()?, Name::new("IBM US Equity"));
+
+        // null elements have no value to read
+        let event = build_event("{}")?;
+        let message = event.messages().next().unwrap();
+        let element = message.element();
+        assert!(element.get_element("active").and_then(|active| active.value::<bool>()).is_err());
+        assert!(element.get_element("ticker").and_then(|ticker| ticker.value::<Name>()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_summary() -> Result<(), Error> {
+        let json = r#"{ "ticker": "IBM US Equity", "prices": [1.0, 2.0, 3.0], "trades": [{ "size": 1 }, { "size": 2 }] }"#;
+        let event = test_event(EventType::SubscriptionData, "Ticks", None, json)?;
+        let message = event.messages().next().unwrap();
+        let summary = message.element().summary();
+
+        // the root, the trades array and its two values
+        assert_eq!(summary.counts[&DataType::Sequence], 4);
+        assert_eq!(summary.counts[&DataType::String], 1);
+        assert_eq!(summary.counts[&DataType::Float64], 1);
+        assert_eq!(summary.counts[&DataType::Int64], 2);
+        assert_eq!(summary.max_depth, 3);
+        assert_eq!(summary.total_values, 6);
+        assert_eq!(message.summary(), summary);
+        Ok(())
+    }
+
+    #[test]
+    fn test_double_ended() -> Result<(), Error> {
+        let json = r#"{ "ticker": "IBM US Equity", "prices": [1.0, 2.0, 3.0], "trades": [{ "size": 1 }, { "size": 2 }] }"#;
+        let event = test_event(EventType::SubscriptionData, "Ticks", None, json)?;
+        let message = event.messages().next().unwrap();
+        let element = message.element();
+        let prices = element.get_element("prices")?;
+        assert_eq!(prices.values::<f64>().rev().collect::<Vec<_>>(), [3.0, 2.0, 1.0]);
+        assert_eq!(prices.values::<f64>().next_back(), Some(3.0));
+
+        let mut values = prices.values::<f64>();
+        assert_eq!(values.next(), Some(1.0));
+        assert_eq!(values.next_back(), Some(3.0));
+        assert_eq!(values.next_back(), Some(2.0));
+        assert_eq!(values.next(), None);
+        assert_eq!(values.next_back(), None);
+
+        let sizes: Vec<i64> = element
+            .get_element("trades")?
+            .values::<Element>()
+            .rev()
+            .map(|trade| trade.get_element("size")?.value::<i64>())
+            .collect::<Result<_, _>>()?;
+        assert_eq!(sizes, [2, 1]);
+        assert_eq!(element.elements().rev().count(), element.elements().count());
+        Ok(())
     }
 }
@@ -1,3 +1,5 @@
+use crate::element::Element;
+use crate::fields::InvalidField;
 use blpapi_sys::*;
 use std::ffi::CStr;
 
@@ -90,6 +92,42 @@ pub enum Error {
     TimeOut,
     StringConversionError(Box<dyn std::error::Error>),
     BlpApiError(BlpApiError),
+    /// The request failed, as reported by bloomberg
+    RequestFailure(ErrorInfo),
+    /// The identity could not be authorized, as reported by bloomberg
+    AuthorizationFailure(ErrorInfo),
+    /// The session could not be started, with the reason of its
+    /// `SessionStartupFailure` if any
+    SessionStartFailure(ErrorInfo),
+    /// A security identifier is malformed
+    InvalidSecurity(String),
+    /// A subscription string could not be built
+    InvalidSubscription(String),
+    /// TLS credentials could not be converted for blpapi
+    InvalidTlsMaterial(String),
+    /// A request parameter is out of the range accepted by bloomberg
+    InvalidRequestParameter(String),
+    /// An I/O error, e.g. a thread could not be spawned
+    Io(std::io::Error),
+    /// The blpapi library in use is older than required
+    UnsupportedVersion {
+        required: crate::version::Version,
+        actual: crate::version::Version,
+    },
+    /// The function is only bound with the given `sdk-*` feature
+    MissingSdkFeature(&'static str),
+    /// The operation was aborted by its cancellation token
+    Cancelled,
+    /// The session is quiescing and does not accept new requests
+    Quiescing,
+    /// Some requested fields are unknown, as reported by the field validator
+    InvalidFields(Vec<InvalidField>),
+    /// A value could not be (de)serialized from/to an element
+    #[cfg(feature = "serialization")]
+    Serialization(Box<crate::serde::deserialization::Error>),
+    /// A datetime could not be converted to a chrono type
+    #[cfg(feature = "dates")]
+    DateConversionError(crate::datetime::ChronoConversionError),
 }
 
 impl std::fmt::Display for Error {
@@ -114,3 +152,38 @@ impl Error {
         }
     }
 }
+
+/// Error details reported by bloomberg inside messages
+/// (`reason`, `responseError`, `securityError`, ...)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ErrorInfo {
+    pub source: String,
+    pub code: i32,
+    pub category: String,
+    pub subcategory: String,
+    pub message: String,
+}
+
+impl ErrorInfo {
+    /// Read error details from an `ErrorInfo` like element, missing
+    /// sub-elements are left empty
+    pub fn from_element(element: &Element) -> Self {
+        let string = |name| {
+            element
+                .get_element(name)
+                .and_then(|e| e.value::<String>())
+                .unwrap_or_default()
+        };
+        ErrorInfo {
+            source: string("source"),
+            code: element
+                .get_element("code")
+                .or_else(|_| element.get_element("errorCode"))
+                .and_then(|e| e.value::<i32>())
+                .unwrap_or_default(),
+            category: string("category"),
+            subcategory: string("subcategory"),
+            message: string("message"),
+        }
+    }
+}
@@ -1,10 +1,10 @@
-use crate::errors::Error;
+use crate::errors::{Error, ErrorInfo};
 use crate::message::Message;
 use blpapi_sys::*;
 use std::os::raw::c_int;
-use std::marker::PhantomData;
 use std::fmt::{Debug, Formatter};
 use std::ptr;
+use std::time::Duration;
 
 /// An event
 pub struct Event(pub(crate) *mut blpapi_Event_t);
@@ -16,16 +16,43 @@ impl Event {
     }
 
     /// Get an iterator over all messages of this event
+    ///
+    /// The iterator holds a reference on the event, so it can outlive
+    /// `self` and be moved to another thread.
     pub fn messages(&self) -> MessageIterator {
+        self.clone().into_messages()
+    }
+
+    /// Get an owning iterator over all messages of this event, without
+    /// taking an additional reference on the event
+    pub fn into_messages(self) -> MessageIterator {
         let ptr = unsafe { blpapi_MessageIterator_create(self.0) };
         MessageIterator {
             ptr,
             current_msg: ptr::null_mut(),
-            _phantom: PhantomData,
+            _event: self,
         }
     }
 }
 
+impl IntoIterator for Event {
+    type Item = Message;
+    type IntoIter = MessageIterator;
+
+    fn into_iter(self) -> MessageIterator {
+        self.into_messages()
+    }
+}
+
+impl IntoIterator for &Event {
+    type Item = Message;
+    type IntoIter = MessageIterator;
+
+    fn into_iter(self) -> MessageIterator {
+        self.messages()
+    }
+}
+
 impl Clone for Event {
     fn clone(&self) -> Self {
         unsafe { blpapi_Event_addRef(self.0) };
@@ -49,19 +76,25 @@ unsafe impl Send for Event {}
 unsafe impl Sync for Event {}
 
 /// A message iterator
-pub struct MessageIterator<'a> {
+///
+/// Both the iterator and the messages it yields are reference counted: the
+/// iterator keeps the event alive, and each `Message` holds its own
+/// reference, so messages stay valid after the iterator and the event are
+/// dropped.
+pub struct MessageIterator {
     pub(crate) ptr: *mut blpapi_MessageIterator_t,
     current_msg: *mut blpapi_Message_t,
-    _phantom: PhantomData<&'a Event>,
+    // keeps the event alive until the iterator is destroyed
+    _event: Event,
 }
 
-impl<'a> Drop for MessageIterator<'a> {
+impl Drop for MessageIterator {
     fn drop(&mut self) {
         unsafe { blpapi_MessageIterator_destroy(self.ptr) }
     }
 }
 
-impl<'a> Iterator for MessageIterator<'a> {
+impl Iterator for MessageIterator {
     type Item = Message;
 
     fn next(&mut self) -> Option<Message> {
@@ -77,10 +110,11 @@ impl<'a> Iterator for MessageIterator<'a> {
     }
 }
 
-unsafe impl Send for MessageIterator<'_> {}
-unsafe impl Sync for MessageIterator<'_> {}
+unsafe impl Send for MessageIterator {}
+unsafe impl Sync for MessageIterator {}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "recorder", derive(serde::Serialize, serde::Deserialize))]
 pub enum EventType {
     Admin,
     SessionStatus,
@@ -159,8 +193,24 @@ impl EventQueue {
     /// milliseconds an Event with a type() of TIMEOUT will be returned.
     pub fn next_event(&mut self, timeout: Option<isize>) -> Event {
         let timeout = timeout.unwrap_or(0) as c_int;
-        let event = unsafe { blpapi_EventQueue_nextEvent(self.0, timeout) };
-        Event(event)
+        let event = Event(unsafe { blpapi_EventQueue_nextEvent(self.0, timeout) });
+        #[cfg(feature = "tracing")]
+        crate::spans::record(&event);
+        event
+    }
+
+    /// Returns the next Event available from the EventQueue, or None if
+    /// no Event is available within the specified 'timeout'. Unlike
+    /// `next_event`, a zero 'timeout' doesn't wait forever but for at
+    /// least one millisecond.
+    pub fn next_event_timeout(&mut self, timeout: Duration) -> Result<Option<Event>, Error> {
+        let timeout = timeout.as_millis().clamp(1, c_int::MAX as u128) as isize;
+        let event = self.next_event(Some(timeout));
+        if event.event_type() == EventType::Timeout {
+            Ok(None)
+        } else {
+            Ok(Some(event))
+        }
     }
 
     /// If the EventQueue is non-empty, return the next Event available.
@@ -171,7 +221,10 @@ impl EventQueue {
         let ret = unsafe { blpapi_EventQueue_tryNextEvent(self.0, &mut event) };
         Error::check(ret)?;
 
-        Ok(Event(event))
+        let event = Event(event);
+        #[cfg(feature = "tracing")]
+        crate::spans::record(&event);
+        Ok(event)
     }
 
     /// Purges any Event objects in this EventQueue which have not
@@ -181,6 +234,81 @@ impl EventQueue {
     pub fn purge(&mut self) {
         unsafe { blpapi_EventQueue_purge(self.0); }
     }
+
+    /// Iterate over the events of a request sent to this queue, e.g. with
+    /// `Session::send_to_queue`, until its final RESPONSE event.
+    ///
+    /// A REQUEST_STATUS event is yielded as a `RequestFailure` error and
+    /// a TIMEOUT event, if no event is received within `timeout`
+    /// milliseconds, as a `TimeOut` error, both ending the iteration.
+    pub fn responses(&mut self, timeout: Option<isize>) -> ResponseEvents<'_> {
+        ResponseEvents {
+            queue: self,
+            timeout,
+            done: false,
+        }
+    }
+}
+
+/// A source of events polled by the application: an `EventQueue`, a
+/// synchronous `Session` or, for offline tests, a `testutil::MockSession`
+pub trait EventSource {
+    /// Returns the next event, waiting at most `timeout` milliseconds
+    /// (forever if None) before returning a TIMEOUT event
+    fn next_event(&mut self, timeout: Option<isize>) -> Result<Event, Error>;
+
+    /// Returns the next event if one is available, or an error without
+    /// waiting
+    fn try_next_event(&mut self) -> Result<Event, Error>;
+}
+
+impl EventSource for EventQueue {
+    fn next_event(&mut self, timeout: Option<isize>) -> Result<Event, Error> {
+        Ok(EventQueue::next_event(self, timeout))
+    }
+
+    fn try_next_event(&mut self) -> Result<Event, Error> {
+        EventQueue::try_next_event(self)
+    }
+}
+
+/// The events of a request, see `EventQueue::responses`
+pub struct ResponseEvents<'a> {
+    queue: &'a mut EventQueue,
+    timeout: Option<isize>,
+    done: bool,
+}
+
+impl<'a> Iterator for ResponseEvents<'a> {
+    type Item = Result<Event, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let event = self.queue.next_event(self.timeout);
+        match event.event_type() {
+            EventType::Response => self.done = true,
+            EventType::RequestStatus => {
+                self.done = true;
+                let reason = event
+                    .messages()
+                    .find_map(|message| {
+                        let element = message.element();
+                        let reason = element.get_element("reason").ok()?;
+                        Some(ErrorInfo::from_element(&reason))
+                    })
+                    .unwrap_or_default();
+                return Some(Err(Error::RequestFailure(reason)));
+            }
+            EventType::Timeout => {
+                self.done = true;
+                return Some(Err(Error::TimeOut));
+            }
+            _ => (),
+        }
+        Some(Ok(event))
+    }
 }
 
 impl Drop for EventQueue {
@@ -189,4 +317,23 @@ impl Drop for EventQueue {
     fn drop(&mut self) {
         unsafe { blpapi_EventQueue_destroy(self.0); }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::name::Name;
+    use crate::testutil::EventBuilder;
+
+    #[test]
+    fn test_messages_outlive_event() -> Result<(), Error> {
+        let event = EventBuilder::new(EventType::SubscriptionStatus)?
+            .append_message_from_json(Name::new("SubscriptionStarted"), None, "{}")?
+            .append_message_from_json(Name::new("SubscriptionTerminated"), None, "{}")?
+            .build();
+        let messages: Vec<Message> = event.into_messages().collect();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[1].message_type(), "SubscriptionTerminated");
+        Ok(())
+    }
+}
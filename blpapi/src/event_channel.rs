@@ -0,0 +1,67 @@
+//! Events delivered through a channel
+//!
+//! `Session::event_channel` creates an asynchronous session whose event
+//! handler forwards the events to a bounded `std::sync::mpsc` channel, so
+//! the event loop can run on any thread:
+//! `for event in receiver { ... }`.
+//!
+//! When the channel is full the blpapi dispatcher thread waits for the
+//! receiver to catch up. Dropping the `ChannelSession` stops the session and
+//! closes the channel, ending the receiver loop. As stopping waits for
+//! the pending events to be delivered, the receiver must either be dropped
+//! or keep being drained meanwhile.
+use crate::{
+    event::Event,
+    eventdispatcher::EventDispatcher,
+    session::Session,
+    session_options::SessionOptions,
+    Error,
+};
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::sync::mpsc::{sync_channel, Receiver};
+
+/// A session forwarding its events to a channel, see
+/// `Session::event_channel`
+pub struct ChannelSession {
+    // declared first so the session is destroyed before its dispatcher
+    session: Pin<Box<Session<'static>>>,
+    dispatcher: EventDispatcher,
+}
+
+impl Deref for ChannelSession {
+    type Target = Session<'static>;
+
+    fn deref(&self) -> &Session<'static> {
+        &self.session
+    }
+}
+
+impl DerefMut for ChannelSession {
+    fn deref_mut(&mut self) -> &mut Session<'static> {
+        &mut self.session
+    }
+}
+
+impl Drop for ChannelSession {
+    fn drop(&mut self) {
+        self.session.stop();
+        let _ = self.dispatcher.stop(false);
+    }
+}
+
+impl<'a> Session<'a> {
+    /// Create a session with `options` delivering its events to the
+    /// returned receiver, which holds at most `capacity` events
+    pub fn event_channel(options: SessionOptions, capacity: usize) -> Result<(ChannelSession, Receiver<Event>), Error> {
+        let (sender, receiver) = sync_channel(capacity);
+        let dispatcher = EventDispatcher::new(1);
+        let handler = move |event: &Event| {
+            // the receiver may have been dropped, the events are discarded then
+            let _ = sender.send(event.clone());
+        };
+        let session = Session::create(options, Some(handler), Some(&dispatcher));
+        dispatcher.start()?;
+        Ok((ChannelSession { session, dispatcher }, receiver))
+    }
+}
@@ -0,0 +1,176 @@
+//! Routing of events to handlers
+//!
+//! An `EventRouter` replaces the `match` over event and message types of
+//! a session event handler: handlers are registered per message type or
+//! per event type, and `EventRouter::dispatch` calls the right one for
+//! each message of an event.
+//!
+//! ```ignore
+//! let mut router = EventRouter::new()
+//!     .on_message_type(Name::new("SessionStarted"), |_| println!("started"))
+//!     .on_event_type(EventType::SubscriptionData, |message| println!("{:?}", message))
+//!     .fallback(|event_type, message| println!("unhandled {:?} {}", event_type, message.type_string()));
+//! while let Ok(event) = session.next_event() {
+//!     router.dispatch(&event);
+//! }
+//! ```
+use crate::{
+    event::{Event, EventType},
+    message::Message,
+    name::Name,
+};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+type MessageHandler = Box<dyn FnMut(&Message) + Send>;
+type EventHandler = Box<dyn FnMut(&Event) + Send>;
+type FallbackHandler = Box<dyn FnMut(EventType, &Message) + Send>;
+
+/// Dispatch the messages of events to the handlers registered for their
+/// message type or event type
+///
+/// A message is passed to the handler of its message type if any, else to
+/// the handler of its event type, else to the fallback handler.
+#[derive(Default)]
+pub struct EventRouter {
+    message_types: HashMap<Name, MessageHandler>,
+    event_types: HashMap<EventType, MessageHandler>,
+    events: HashMap<EventType, EventHandler>,
+    fallback: Option<FallbackHandler>,
+}
+
+impl EventRouter {
+    pub fn new() -> Self {
+        EventRouter::default()
+    }
+
+    /// Handle the messages of type `message_type`, e.g. `SessionStarted`,
+    /// whatever their event type
+    pub fn on_message_type<F>(mut self, message_type: Name, handler: F) -> Self
+    where
+        F: FnMut(&Message) + Send + 'static,
+    {
+        self.message_types.insert(message_type, Box::new(handler));
+        self
+    }
+
+    /// Handle the messages of `event_type` which have no message type
+    /// handler
+    pub fn on_event_type<F>(mut self, event_type: EventType, handler: F) -> Self
+    where
+        F: FnMut(&Message) + Send + 'static,
+    {
+        self.event_types.insert(event_type, Box::new(handler));
+        self
+    }
+
+    /// Handle the events of `event_type` as a whole, before their messages
+    /// are dispatched
+    pub fn on_event<F>(mut self, event_type: EventType, handler: F) -> Self
+    where
+        F: FnMut(&Event) + Send + 'static,
+    {
+        self.events.insert(event_type, Box::new(handler));
+        self
+    }
+
+    /// Handle the messages without handler
+    pub fn fallback<F>(mut self, handler: F) -> Self
+    where
+        F: FnMut(EventType, &Message) + Send + 'static,
+    {
+        self.fallback = Some(Box::new(handler));
+        self
+    }
+
+    /// Handle the messages of type `message_type` deserialized as `T`,
+    /// the deserialization error being passed to `handler` on failure
+    #[cfg(feature = "serialization")]
+    pub fn on_typed<T, F>(self, message_type: Name, mut handler: F) -> Self
+    where
+        T: serde::de::DeserializeOwned,
+        F: FnMut(Result<T, crate::Error>, &Message) + Send + 'static,
+    {
+        self.on_message_type(message_type, move |message| {
+            let value = crate::serde::deserialization::from_element(message.element())
+                .map_err(|err| crate::Error::Serialization(Box::new(err)));
+            handler(value, message)
+        })
+    }
+
+    /// Dispatch `event` and its messages, returning the number of messages
+    /// which had a handler
+    pub fn dispatch(&mut self, event: &Event) -> usize {
+        let event_type = event.event_type();
+        if let Some(handler) = self.events.get_mut(&event_type) {
+            handler(event);
+        }
+        let mut handled = 0;
+        for message in event.messages() {
+            if self.dispatch_message(event_type, &message) {
+                handled += 1;
+            }
+        }
+        handled
+    }
+
+    /// Dispatch a single `message` of an event of `event_type`, returning
+    /// true if it had a handler
+    pub fn dispatch_message(&mut self, event_type: EventType, message: &Message) -> bool {
+        if let Some(handler) = self.message_types.get_mut(&message.message_type()) {
+            handler(message);
+        } else if let Some(handler) = self.event_types.get_mut(&event_type) {
+            handler(message);
+        } else if let Some(handler) = self.fallback.as_mut() {
+            handler(event_type, message);
+        } else {
+            return false;
+        }
+        true
+    }
+}
+
+impl Debug for EventRouter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventRouter")
+            .field("message_types", &self.message_types.keys().collect::<Vec<_>>())
+            .field("event_types", &self.event_types.keys().collect::<Vec<_>>())
+            .field("events", &self.events.keys().collect::<Vec<_>>())
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::EventBuilder;
+    use crate::Error;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_dispatch() -> Result<(), Error> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let record = |label: &'static str| {
+            let calls = calls.clone();
+            move |_: &Message| calls.lock().unwrap().push(label)
+        };
+        let mut router = EventRouter::new()
+            .on_message_type(Name::new("SessionStarted"), record("started"))
+            .on_event_type(EventType::SessionStatus, record("status"));
+
+        let event = EventBuilder::new(EventType::SessionStatus)?
+            .append_message_from_json(Name::new("SessionStarted"), None, "{}")?
+            .append_message_from_json(Name::new("SessionTerminated"), None, "{}")?
+            .build();
+        assert_eq!(router.dispatch(&event), 2);
+
+        let event = EventBuilder::new(EventType::Admin)?
+            .append_message_from_json(Name::new("SlowConsumerWarning"), None, "{}")?
+            .build();
+        assert_eq!(router.dispatch(&event), 0);
+
+        assert_eq!(*calls.lock().unwrap(), vec!["started", "status"]);
+        Ok(())
+    }
+}
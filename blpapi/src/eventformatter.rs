@@ -0,0 +1,197 @@
+use crate::correlation_id::CorrelationId;
+use crate::datetime::Datetime;
+use crate::errors::Error;
+use crate::event::Event;
+use crate::name::Name;
+use crate::topic::Topic;
+use blpapi_sys::*;
+use std::ffi::CString;
+use std::marker::PhantomData;
+use std::ptr;
+
+/// A value which can be set on the current element of an `EventFormatter`
+pub trait FormatterValue {
+    /// Set the element `name` of the current element to this value
+    fn set(self, formatter: &mut EventFormatter, name: &str) -> Result<(), Error>;
+}
+
+macro_rules! impl_formatter_value {
+    ($ty:ty, $set:path) => {
+        impl_formatter_value!($ty, $set, |value| value);
+    };
+    ($ty:ty, $set:path, $to_bbg:expr) => {
+        impl FormatterValue for $ty {
+            fn set(self, formatter: &mut EventFormatter, name: &str) -> Result<(), Error> {
+                let name = CString::new(name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+                let res = unsafe { $set(formatter.ptr, name.as_ptr(), ptr::null(), $to_bbg(self)) };
+                Error::check(res)
+            }
+        }
+    };
+}
+
+impl_formatter_value!(bool, blpapi_EventFormatter_setValueBool, |value| if value { 1 } else { 0 });
+impl_formatter_value!(i32, blpapi_EventFormatter_setValueInt32);
+impl_formatter_value!(i64, blpapi_EventFormatter_setValueInt64);
+impl_formatter_value!(f32, blpapi_EventFormatter_setValueFloat32);
+impl_formatter_value!(f64, blpapi_EventFormatter_setValueFloat64);
+impl_formatter_value!(&Datetime, blpapi_EventFormatter_setValueDatetime, |value: &Datetime| &value.0 as *const _);
+impl_formatter_value!(&Name, blpapi_EventFormatter_setValueFromName, |value: &Name| value.0 as *const _);
+
+impl FormatterValue for &str {
+    fn set(self, formatter: &mut EventFormatter, name: &str) -> Result<(), Error> {
+        let name = CString::new(name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let value = CString::new(self).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let res = unsafe { blpapi_EventFormatter_setValueString(formatter.ptr, name.as_ptr(), ptr::null(), value.as_ptr()) };
+        Error::check(res)
+    }
+}
+
+/// Used to add messages to an Event for publishing
+///
+/// An EventFormatter is created from an Event obtained from
+/// 'createPublishEvent()' on Service. Once the Message or Messages have
+/// been appended to the Event using the EventFormatter the Event can be
+/// published using 'publish()' on the ProviderSession.
+///
+/// EventFormatter objects cannot be copied or assigned so as to
+/// ensure there is no ambiguity about what happens if two
+/// EventFormatters are both formatting the same Event.
+pub struct EventFormatter<'e> {
+    pub(crate) ptr: *mut blpapi_EventFormatter_t,
+    _phantom: PhantomData<&'e mut Event>,
+}
+
+impl<'e> EventFormatter<'e> {
+    /// Create an EventFormatter to create Messages in the specified
+    /// 'event'. An Event may only be reference by one EventFormatter at
+    /// any time.
+    pub fn new(event: &'e mut Event) -> Self {
+        let ptr = unsafe { blpapi_EventFormatter_create(event.0) };
+        EventFormatter { ptr, _phantom: PhantomData }
+    }
+
+    /// Append an (empty) message of the specified 'message_type' that
+    /// will be published under the specified 'topic' to the Event
+    /// referenced by this EventFormatter.
+    pub fn append_message(&mut self, message_type: &str, topic: &Topic) -> Result<(), Error> {
+        let message_type = CString::new(message_type).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let res = unsafe { blpapi_EventFormatter_appendMessage(self.ptr, message_type.as_ptr(), ptr::null_mut(), topic.0) };
+        Error::check(res)
+    }
+
+    /// Append an empty response message of the specified 'operation_name'
+    /// to the Event referenced by this EventFormatter.
+    pub fn append_response(&mut self, operation_name: &str) -> Result<(), Error> {
+        let operation_name = CString::new(operation_name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let res = unsafe { blpapi_EventFormatter_appendResponse(self.ptr, operation_name.as_ptr(), ptr::null_mut()) };
+        Error::check(res)
+    }
+
+    /// Append a (empty) recap message that will be published under the
+    /// specified 'topic', either solicited by the optionally specified
+    /// 'correlation_id' or unsolicited.
+    pub fn append_recap_message(&mut self, topic: &Topic, correlation_id: Option<&CorrelationId>) -> Result<(), Error> {
+        let correlation_id = correlation_id.map_or(ptr::null(), |correlation_id| &correlation_id.0 as *const _);
+        let res = unsafe { blpapi_EventFormatter_appendRecapMessage(self.ptr, topic.0, correlation_id) };
+        Error::check(res)
+    }
+
+    /// Set the element with the specified 'name' to the specified 'value'
+    /// in the current message.
+    pub fn set<V: FormatterValue>(&mut self, name: &str, value: V) -> Result<(), Error> {
+        value.set(self, name)
+    }
+
+    /// Set the element with the specified 'name' to null in the current
+    /// message.
+    pub fn set_null(&mut self, name: &str) -> Result<(), Error> {
+        let name = CString::new(name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let res = unsafe { blpapi_EventFormatter_setValueNull(self.ptr, name.as_ptr(), ptr::null()) };
+        Error::check(res)
+    }
+
+    /// Change the level at which this EventFormatter is operating to the
+    /// specified element 'name'. After this returns the context of the
+    /// EventFormatter is set to the element 'name' in the schema and any
+    /// calls to 'set', 'append_value' or 'push_element' are applied at
+    /// that level.
+    pub fn push_element(&mut self, name: &str) -> Result<(), Error> {
+        let name = CString::new(name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let res = unsafe { blpapi_EventFormatter_pushElement(self.ptr, name.as_ptr(), ptr::null()) };
+        Error::check(res)
+    }
+
+    /// Undo the most recent call to 'push_element' or 'append_element'.
+    pub fn pop_element(&mut self) -> Result<(), Error> {
+        let res = unsafe { blpapi_EventFormatter_popElement(self.ptr) };
+        Error::check(res)
+    }
+
+    /// Append the specified 'value' to the current (array) element.
+    pub fn append_value(&mut self, value: &str) -> Result<(), Error> {
+        let value = CString::new(value).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let res = unsafe { blpapi_EventFormatter_appendValueString(self.ptr, value.as_ptr()) };
+        Error::check(res)
+    }
+
+    /// Append a new element to the current (array of complex type)
+    /// element and make it the current element.
+    pub fn append_element(&mut self) -> Result<(), Error> {
+        let res = unsafe { blpapi_EventFormatter_appendElement(self.ptr) };
+        Error::check(res)
+    }
+}
+
+impl Drop for EventFormatter<'_> {
+    fn drop(&mut self) {
+        unsafe { blpapi_EventFormatter_destroy(self.ptr) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{create_topic, test_service};
+
+    #[test]
+    fn test_publish_event() -> Result<(), Error> {
+        let service = test_service()?;
+        let topic = create_topic(&service, true)?;
+        let mut event = service.create_publish_event()?;
+        {
+            let mut formatter = EventFormatter::new(&mut event);
+            formatter.append_message("MarketDataEvents", &topic)?;
+            formatter.set("LAST_PRICE", 101.5)?;
+            formatter.set("VOLUME", 1000i64)?;
+            formatter.set("ACTIVE", true)?;
+            formatter.set("TICKER", "IBM US Equity")?;
+        }
+
+        let message = event.messages().next().unwrap();
+        assert_eq!(message.message_type(), "MarketDataEvents");
+        let element = message.element();
+        assert_eq!(element.get_element("LAST_PRICE")?.value::<f64>()?, 101.5);
+        assert_eq!(element.get_element("VOLUME")?.value::<i64>()?, 1000);
+        assert!(element.get_element("ACTIVE")?.value::<bool>()?);
+        assert_eq!(element.get_element("TICKER")?.value::<String>()?, "IBM US Equity");
+        Ok(())
+    }
+
+    #[test]
+    fn test_response_event() -> Result<(), Error> {
+        let service = test_service()?;
+        let correlation_id = CorrelationId::new_int(1, None);
+        let mut event = service.create_response_event(&correlation_id)?;
+        {
+            let mut formatter = EventFormatter::new(&mut event);
+            formatter.append_response("PriceRequest")?;
+            formatter.set("price", 101.5)?;
+            assert!(formatter.set("unknown", 1.0).is_err());
+        }
+
+        let message = event.messages().next().unwrap();
+        assert_eq!(message.element().get_element("price")?.value::<f64>()?, 101.5);
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+//! Export of elements and messages as JSON or XML text
+//!
+//! Unlike `Display`, which uses the blpapi schema-like format, the printers
+//! below walk the element tree and write standard JSON or XML to any
+//! `io::Write`, so full responses can be archived verbatim and read back by
+//! other tools. Datetimes are written as ISO 8601 strings.
+use crate::{
+    element::{DataType, Element},
+    message::Message,
+    value::Value,
+    walk::{walk, Visitor},
+    Error,
+};
+use std::io::{self, Write};
+
+fn io_error(err: Error) -> io::Error {
+    match err {
+        Error::Io(err) => err,
+        err => io::Error::new(io::ErrorKind::InvalidData, err.to_string()),
+    }
+}
+
+fn is_complex(element: &Element) -> bool {
+    let data_type = element.data_type();
+    data_type == DataType::Sequence || data_type == DataType::Choice
+}
+
+fn write_json_string<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    writer.write_all(b"\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => writer.write_all(b"\\\"")?,
+            '\\' => writer.write_all(b"\\\\")?,
+            '\n' => writer.write_all(b"\\n")?,
+            '\r' => writer.write_all(b"\\r")?,
+            '\t' => writer.write_all(b"\\t")?,
+            c if (c as u32) < 0x20 => write!(writer, "\\u{:04x}", c as u32)?,
+            c => write!(writer, "{}", c)?,
+        }
+    }
+    writer.write_all(b"\"")
+}
+
+fn write_xml_text<W: Write>(writer: &mut W, value: &str) -> io::Result<()> {
+    for c in value.chars() {
+        match c {
+            '<' => writer.write_all(b"&lt;")?,
+            '>' => writer.write_all(b"&gt;")?,
+            '&' => writer.write_all(b"&amp;")?,
+            '"' => writer.write_all(b"&quot;")?,
+            '\'' => writer.write_all(b"&apos;")?,
+            c => write!(writer, "{}", c)?,
+        }
+    }
+    Ok(())
+}
+
+/// Writes the walk of an element as JSON text
+struct JsonPrinter<'w, W> {
+    writer: &'w mut W,
+    /// Whether the arrays and objects being written have no value yet
+    first: Vec<bool>,
+    /// Whether the next value is the value of an object field
+    in_field: bool,
+}
+
+impl<W: Write> JsonPrinter<'_, W> {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.writer.write_all(bytes).map_err(Error::Io)
+    }
+
+    /// Write the separator before a new array value or object field
+    fn separate(&mut self) -> Result<(), Error> {
+        match self.first.last_mut() {
+            Some(first) if *first => *first = false,
+            Some(_) => self.write(b",")?,
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Start writing a value, separated from the previous array value
+    fn start_value(&mut self) -> Result<(), Error> {
+        if self.in_field {
+            self.in_field = false;
+            Ok(())
+        } else {
+            self.separate()
+        }
+    }
+
+    fn write_value(&mut self, value: Value) -> io::Result<()> {
+        let writer = &mut *self.writer;
+        match value {
+            Value::Bool(value) => write!(writer, "{}", value),
+            Value::I32(value) => write!(writer, "{}", value),
+            Value::I64(value) => write!(writer, "{}", value),
+            Value::F32(value) if value.is_finite() => write!(writer, "{}", value),
+            Value::F64(value) if value.is_finite() => write!(writer, "{}", value),
+            Value::F32(_) | Value::F64(_) | Value::Null => writer.write_all(b"null"),
+            Value::String(value) => write_json_string(writer, &value),
+            Value::Datetime(value) => write_json_string(writer, &value.to_iso8601()),
+            Value::Name(value) => write_json_string(writer, &value.to_string_lossy()),
+            Value::Bytes(value) => {
+                writer.write_all(b"[")?;
+                for (i, byte) in value.iter().enumerate() {
+                    if i > 0 {
+                        writer.write_all(b",")?;
+                    }
+                    write!(writer, "{}", byte)?;
+                }
+                writer.write_all(b"]")
+            }
+        }
+    }
+}
+
+impl<W: Write> Visitor for JsonPrinter<'_, W> {
+    fn value(&mut self, value: Value) -> Result<(), Error> {
+        self.start_value()?;
+        self.write_value(value).map_err(Error::Io)
+    }
+
+    fn start_array(&mut self) -> Result<(), Error> {
+        self.start_value()?;
+        self.first.push(true);
+        self.write(b"[")
+    }
+
+    fn end_array(&mut self) -> Result<(), Error> {
+        self.first.pop();
+        self.write(b"]")
+    }
+
+    fn start_object(&mut self) -> Result<(), Error> {
+        self.start_value()?;
+        self.first.push(true);
+        self.write(b"{")
+    }
+
+    fn field(&mut self, name: &str) -> Result<(), Error> {
+        self.separate()?;
+        write_json_string(self.writer, name).map_err(Error::Io)?;
+        self.in_field = true;
+        self.write(b":")
+    }
+
+    fn end_object(&mut self) -> Result<(), Error> {
+        self.first.pop();
+        self.write(b"}")
+    }
+}
+
+fn write_xml_value<W: Write>(writer: &mut W, element: &Element, index: usize) -> io::Result<()> {
+    match element.get_value_dyn(index).map_err(io_error)? {
+        Value::Bool(value) => write!(writer, "{}", value),
+        Value::I32(value) => write!(writer, "{}", value),
+        Value::I64(value) => write!(writer, "{}", value),
+        Value::F32(value) => write!(writer, "{}", value),
+        Value::F64(value) => write!(writer, "{}", value),
+        Value::String(value) => write_xml_text(writer, &value),
+        Value::Datetime(value) => write_xml_text(writer, &value.to_iso8601()),
+        Value::Name(value) => write_xml_text(writer, &value.to_string_lossy()),
+        Value::Bytes(value) => value.iter().try_for_each(|byte| write!(writer, "{:02x}", byte)),
+        Value::Null => Ok(()),
+    }
+}
+
+impl Element<'_> {
+    /// Write this element, and all its sub-elements, as JSON: sequences
+    /// and choices become objects, arrays become arrays and null elements
+    /// `null`
+    pub fn print_json<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut printer = JsonPrinter { writer, first: Vec::new(), in_field: false };
+        walk(self, &mut printer).map_err(io_error)
+    }
+
+    /// Write this element, and all its sub-elements, as XML: each element
+    /// is a tag named after it, the values of arrays being repeated tags
+    pub fn print_xml<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let name = self.string_name();
+        if self.is_array() {
+            for index in 0..self.num_values() {
+                if is_complex(self) {
+                    let value = self.get_at::<Element>(index).map_err(io_error)?;
+                    write!(writer, "<{}>", name)?;
+                    value.print_xml_content(writer)?;
+                    write!(writer, "</{}>", name)?;
+                } else {
+                    write!(writer, "<{}>", name)?;
+                    write_xml_value(writer, self, index)?;
+                    write!(writer, "</{}>", name)?;
+                }
+            }
+            return Ok(());
+        }
+        if self.is_null().map_err(io_error)? || (!is_complex(self) && self.num_values() == 0) {
+            return write!(writer, "<{}/>", name);
+        }
+        write!(writer, "<{}>", name)?;
+        self.print_xml_content(writer)?;
+        write!(writer, "</{}>", name)
+    }
+
+    fn print_xml_content<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        match self.data_type() {
+            DataType::Sequence => self.elements().try_for_each(|child| child.print_xml(writer)),
+            DataType::Choice => self.get_choice().map_err(io_error)?.print_xml(writer),
+            _ => write_xml_value(writer, self, 0),
+        }
+    }
+}
+
+impl Message {
+    /// Write the content of this message as JSON, see `Element::print_json`
+    pub fn print_json<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.element().print_json(writer)
+    }
+
+    /// Write the content of this message as XML, see `Element::print_xml`
+    pub fn print_xml<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.element().print_xml(writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::Datetime;
+    use blpapi_sys::{blpapi_Datetime_t, BLPAPI_DATETIME_DATE_PART};
+
+    #[test]
+    fn escaping() {
+        let mut json = Vec::new();
+        write_json_string(&mut json, "a \"b\"\\\n\u{1}").unwrap();
+        assert_eq!(String::from_utf8(json).unwrap(), r#""a \"b\"\\\n\u0001""#);
+
+        let mut xml = Vec::new();
+        write_xml_text(&mut xml, "<a & 'b'>").unwrap();
+        assert_eq!(String::from_utf8(xml).unwrap(), "&lt;a &amp; &apos;b&apos;&gt;");
+    }
+
+    #[test]
+    fn json_printer() -> Result<(), Error> {
+        let mut json = Vec::new();
+        let mut printer = JsonPrinter { writer: &mut json, first: Vec::new(), in_field: false };
+        printer.start_object()?;
+        printer.field("prices")?;
+        printer.start_array()?;
+        printer.value(Value::F64(1.5))?;
+        printer.value(Value::F64(f64::NAN))?;
+        printer.end_array()?;
+        printer.field("security")?;
+        printer.start_object()?;
+        printer.field("ticker")?;
+        printer.value(Value::String("IBM US Equity".to_string()))?;
+        printer.end_object()?;
+        printer.field("active")?;
+        printer.value(Value::Null)?;
+        printer.field("updated")?;
+        printer.value(Value::Datetime(Datetime(blpapi_Datetime_t {
+            parts: BLPAPI_DATETIME_DATE_PART as u8,
+            year: 2024,
+            month: 2,
+            day: 29,
+            ..Datetime::default().0
+        })))?;
+        printer.end_object()?;
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            r#"{"prices":[1.5,null],"security":{"ticker":"IBM US Equity"},"active":null,"updated":"2024-02-29"}"#
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+//! Derive-free field mapping
+//!
+//! A `FieldMap` holds a table of `Name` -> setter closures, built once,
+//! and fills a value from each message by looking the fields up by `Name`.
+//! This skips the string comparisons and the visitors of the serde path,
+//! for latency sensitive subscriptions:
+//!
+//! ```ignore
+//! let map = FieldMap::new()
+//!     .value("LAST_PRICE", |tick: &mut Tick, price| tick.price = price)
+//!     .value("BID", |tick: &mut Tick, bid| tick.bid = bid);
+//! map.apply_message(&message, &mut tick)?;
+//! ```
+use crate::{
+    element::{Element, GetValue},
+    message::Message,
+    name::Name,
+    Error,
+};
+use blpapi_sys::*;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::ptr;
+
+type Setter<T> = Box<dyn Fn(&mut T, &Element) -> Result<(), Error> + Send + Sync>;
+
+/// A table of setters filling a `T` from the fields of an element
+pub struct FieldMap<T> {
+    fields: Vec<(Name, Setter<T>)>,
+}
+
+impl<T> FieldMap<T> {
+    /// Create an empty map
+    pub fn new() -> Self {
+        FieldMap { fields: Vec::new() }
+    }
+
+    /// Call `setter` with the sub-element `name` when present and not null
+    pub fn field<F>(mut self, name: &str, setter: F) -> Self
+    where
+        F: Fn(&mut T, &Element) -> Result<(), Error> + Send + Sync + 'static,
+    {
+        self.fields.push((Name::new(name), Box::new(setter)));
+        self
+    }
+
+    /// Call `setter` with the value of the sub-element `name` when present
+    /// and not null
+    pub fn value<V, F>(self, name: &str, setter: F) -> Self
+    where
+        V: for<'e> GetValue<'e>,
+        F: Fn(&mut T, V) + Send + Sync + 'static,
+    {
+        self.field(name, move |target, element| {
+            setter(target, element.get_at(0)?);
+            Ok(())
+        })
+    }
+
+    /// Number of registered fields
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    /// Whether no field is registered
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Apply the setters of the fields present in `element` to `target`,
+    /// returning the number of fields set
+    pub fn apply(&self, element: &Element, target: &mut T) -> Result<usize, Error> {
+        let mut count = 0;
+        for (name, setter) in &self.fields {
+            let mut field = ptr::null_mut();
+            let res = unsafe { blpapi_Element_getElement(element.ptr, &mut field, ptr::null(), name.0) };
+            if res != 0 {
+                continue;
+            }
+            let field = Element { ptr: field, _marker: PhantomData };
+            if field.is_null()? {
+                continue;
+            }
+            setter(target, &field)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Apply the setters to the content of `message`, see `apply`
+    pub fn apply_message(&self, message: &Message, target: &mut T) -> Result<usize, Error> {
+        self.apply(&message.element(), target)
+    }
+}
+
+impl<T> Default for FieldMap<T> {
+    fn default() -> Self {
+        FieldMap::new()
+    }
+}
+
+impl<T> Debug for FieldMap<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.fields.iter().map(|(name, _)| name))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventType;
+    use crate::testutil::EventBuilder;
+
+    #[derive(Default, Debug, PartialEq)]
+    struct Started {
+        resubscription_id: i32,
+        reason: String,
+        address: String,
+    }
+
+    const MESSAGE: &str = r#"
+        {
+            "resubscriptionId": 123,
+            "receivedFrom": { "address": "12.34.56.78:8194" },
+            "reason": "TestUtil"
+        }
+    "#;
+
+    fn field_map() -> FieldMap<Started> {
+        FieldMap::new()
+            .value("resubscriptionId", |started: &mut Started, id| started.resubscription_id = id)
+            .value("reason", |started: &mut Started, reason| started.reason = reason)
+            .field("receivedFrom", |started, element| {
+                started.address = element.get_element("address")?.get_at(0)?;
+                Ok(())
+            })
+            .value("notInSchema", |_: &mut Started, _: String| panic!("unknown field"))
+    }
+
+    #[test]
+    fn test_apply() -> Result<(), Error> {
+        let event = EventBuilder::new(EventType::SubscriptionStatus)?
+            .append_message_from_json(Name::new("SubscriptionStarted"), None, MESSAGE)?
+            .build();
+        let message = event.messages().next().unwrap();
+
+        let mut started = Started::default();
+        assert_eq!(field_map().apply_message(&message, &mut started)?, 3);
+        assert_eq!(
+            started,
+            Started {
+                resubscription_id: 123,
+                reason: "TestUtil".to_string(),
+                address: "12.34.56.78:8194".to_string(),
+            }
+        );
+        Ok(())
+    }
+}
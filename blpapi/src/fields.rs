@@ -0,0 +1,245 @@
+//! Field mnemonics validation (`//blp/apiflds`)
+//!
+//! Requesting a misspelled field only fails at response time, as a field
+//! exception of every security. A `FieldValidator` checks the mnemonics,
+//! or field ids such as `PR005`, against the fields service before the
+//! request is sent and suggests close mnemonics for the unknown ones.
+use crate::{element::Element, session::Session, Error};
+use std::collections::HashMap;
+
+/// Field information service name
+pub const APIFLDS_SERVICE: &str = "//blp/apiflds";
+
+/// Maximum number of suggestions returned per invalid field
+const MAX_SUGGESTIONS: usize = 5;
+
+/// A field mnemonic unknown to bloomberg
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidField {
+    pub field: String,
+    /// Closest known mnemonics, best match first
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FieldStatus {
+    Valid,
+    Invalid(Vec<String>),
+}
+
+/// Validates field mnemonics or ids, caching the results
+///
+/// Mnemonics and ids are case insensitive.
+#[derive(Debug, Clone, Default)]
+pub struct FieldValidator {
+    cache: HashMap<String, FieldStatus>,
+}
+
+impl FieldValidator {
+    /// Create a validator with an empty cache
+    pub fn new() -> Self {
+        FieldValidator::default()
+    }
+
+    /// Consider `fields` valid without querying the fields service, e.g.
+    /// to restore the mnemonics saved from `known_fields`
+    pub fn with_known_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for field in fields {
+            self.cache.insert(field.as_ref().to_uppercase(), FieldStatus::Valid);
+        }
+        self
+    }
+
+    /// Mnemonics and ids known to be valid
+    pub fn known_fields(&self) -> impl Iterator<Item = &str> {
+        self.cache
+            .iter()
+            .filter(|(_, status)| **status == FieldStatus::Valid)
+            .map(|(field, _)| field.as_str())
+    }
+
+    /// Check `fields` against the fields service, only querying the
+    /// mnemonics not cached yet.
+    ///
+    /// Returns the invalid fields, with suggestions, or an empty vector if
+    /// all fields are valid.
+    pub fn validate<I, S>(&mut self, session: &mut Session, fields: I) -> Result<Vec<InvalidField>, Error>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let fields: Vec<String> = fields.into_iter().map(|f| f.as_ref().to_uppercase()).collect();
+        let unknown: Vec<&String> = fields.iter().filter(|f| !self.cache.contains_key(*f)).collect();
+
+        if !unknown.is_empty() {
+            let valid = query_fields(session, &unknown)?;
+            for field in unknown {
+                let status = if valid.contains(field) {
+                    FieldStatus::Valid
+                } else {
+                    FieldStatus::Invalid(suggest(session, field)?)
+                };
+                self.cache.insert(field.clone(), status);
+            }
+        }
+
+        let invalid = fields
+            .into_iter()
+            .filter_map(|field| match self.cache.get(&field) {
+                Some(FieldStatus::Invalid(suggestions)) => Some(InvalidField {
+                    suggestions: suggestions.clone(),
+                    field,
+                }),
+                _ => None,
+            })
+            .collect();
+        Ok(invalid)
+    }
+}
+
+/// Return the (upper case) mnemonics and ids of `fields` known by the
+/// fields service
+fn query_fields(session: &mut Session, fields: &[&String]) -> Result<Vec<String>, Error> {
+    let service = session.get_or_open_service(APIFLDS_SERVICE)?;
+    let mut request = service.create_request("FieldInfoRequest")?;
+    for field in fields {
+        request.append("id", field.as_str())?;
+    }
+    request.element().set("returnFieldDocumentation", false)?;
+
+    let mut valid = Vec::new();
+    session.collect_responses(request, |response| {
+        valid.extend(identifiers(response)?.into_iter().map(|m| m.to_uppercase()));
+        Ok(())
+    })?;
+    Ok(valid)
+}
+
+/// Search the fields service for mnemonics close to `field`
+fn suggest(session: &mut Session, field: &str) -> Result<Vec<String>, Error> {
+    let service = session.get_or_open_service(APIFLDS_SERVICE)?;
+    let request = service.create_request("FieldSearchRequest")?;
+    request.element().set("searchSpec", field)?;
+    request.element().set("returnFieldDocumentation", false)?;
+
+    let mut candidates = Vec::new();
+    session.collect_responses(request, |response| {
+        candidates.extend(mnemonics(response)?);
+        Ok(())
+    })?;
+    Ok(closest(field, candidates))
+}
+
+/// Mnemonics of the `fieldData` entries with a `fieldInfo`
+fn mnemonics(response: &Element) -> Result<Vec<String>, Error> {
+    let mut result = Vec::new();
+    let field_data = response.get_element("fieldData")?;
+    for data in field_data.values::<Element>() {
+        if let Ok(info) = data.get_element("fieldInfo") {
+            result.push(info.get_element("mnemonic")?.value::<String>()?);
+        }
+    }
+    Ok(result)
+}
+
+/// Ids and mnemonics of the `fieldData` entries with a `fieldInfo`, as a
+/// field may be requested by either
+fn identifiers(response: &Element) -> Result<Vec<String>, Error> {
+    let mut result = Vec::new();
+    let field_data = response.get_element("fieldData")?;
+    for data in field_data.values::<Element>() {
+        if let Ok(info) = data.get_element("fieldInfo") {
+            result.push(data.get_element("id")?.value::<String>()?);
+            result.push(info.get_element("mnemonic")?.value::<String>()?);
+        }
+    }
+    Ok(result)
+}
+
+/// Keep the `MAX_SUGGESTIONS` candidates closest to `field`
+fn closest(field: &str, mut candidates: Vec<String>) -> Vec<String> {
+    let field = field.to_uppercase();
+    candidates.sort();
+    candidates.dedup();
+    candidates.sort_by_key(|candidate| edit_distance(&field, &candidate.to_uppercase()));
+    candidates.truncate(MAX_SUGGESTIONS);
+    candidates
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + if ca == *cb { 0 } else { 1 };
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
+}
+
+impl<'a> Session<'a> {
+    /// Validate the fields of the high level requests (`ref_data`,
+    /// `hist_data`) with `validator` before sending them, or stop
+    /// validating if None
+    pub fn set_field_validator(&mut self, validator: Option<FieldValidator>) {
+        self.field_validator = validator;
+    }
+
+    /// Check `fields` with the field validator, if any
+    pub(crate) fn validate_fields(&mut self, fields: &[&str]) -> Result<(), Error> {
+        let mut validator = match self.field_validator.take() {
+            Some(validator) => validator,
+            None => return Ok(()),
+        };
+        let result = validator.validate(self, fields);
+        self.field_validator = Some(validator);
+
+        let invalid = result?;
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidFields(invalid))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventType;
+    use crate::testutil::test_event;
+
+    #[test]
+    fn suggestions() {
+        assert_eq!(edit_distance("PX_LAST", "PX_LAST"), 0);
+        assert_eq!(edit_distance("PX_LSAT", "PX_LAST"), 2);
+        assert_eq!(edit_distance("", "ABC"), 3);
+
+        let candidates = vec!["PX_OPEN", "PX_LAST", "LAST_PRICE", "PX_LAST"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(closest("px_lst", candidates), vec!["PX_LAST", "PX_OPEN", "LAST_PRICE"]);
+    }
+
+    #[test]
+    fn test_identifiers() -> Result<(), Error> {
+        let json = r#"{ "fieldData": [
+            { "id": "PR005", "fieldInfo": { "mnemonic": "PX_LAST" } },
+            { "id": "PX_LSAT" }
+        ] }"#;
+        let event = test_event(EventType::Response, "fieldResponse", None, json)?;
+        let message = event.messages().next().unwrap();
+        assert_eq!(identifiers(&message.element())?, vec!["PR005", "PX_LAST"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,286 @@
+//! Historical data (`//blp/refdata` `HistoricalDataRequest`, a.k.a. BDH)
+use crate::{
+    element::Element,
+    errors::ErrorInfo,
+    refdata::{field_exceptions, FieldException, RefData, REFDATA_SERVICE},
+    request::Request,
+    session::Session,
+    Error,
+};
+use std::collections::HashMap;
+
+/// Periodicity of the returned data points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Periodicity {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+    SemiAnnually,
+    Yearly,
+}
+
+impl Periodicity {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Periodicity::Daily => "DAILY",
+            Periodicity::Weekly => "WEEKLY",
+            Periodicity::Monthly => "MONTHLY",
+            Periodicity::Quarterly => "QUARTERLY",
+            Periodicity::SemiAnnually => "SEMI_ANNUALLY",
+            Periodicity::Yearly => "YEARLY",
+        }
+    }
+}
+
+/// Determines the frequency and calendar type of the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodicityAdjustment {
+    Actual,
+    Calendar,
+    Fiscal,
+}
+
+impl PeriodicityAdjustment {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PeriodicityAdjustment::Actual => "ACTUAL",
+            PeriodicityAdjustment::Calendar => "CALENDAR",
+            PeriodicityAdjustment::Fiscal => "FISCAL",
+        }
+    }
+}
+
+/// Options of an historical data request
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistOptions {
+    start_date: String,
+    end_date: String,
+    periodicity: Option<Periodicity>,
+    periodicity_adjustment: Option<PeriodicityAdjustment>,
+    currency: Option<String>,
+    max_data_points: Option<i32>,
+    overrides: Vec<(String, String)>,
+}
+
+impl HistOptions {
+    /// Create new options for the `start_date` to `end_date` range, both
+    /// formatted as `YYYYMMDD`
+    pub fn new<S: Into<String>, E: Into<String>>(start_date: S, end_date: E) -> Self {
+        HistOptions {
+            start_date: start_date.into(),
+            end_date: end_date.into(),
+            periodicity: None,
+            periodicity_adjustment: None,
+            currency: None,
+            max_data_points: None,
+            overrides: Vec::new(),
+        }
+    }
+
+    /// Set the periodicity
+    pub fn with_periodicity(mut self, periodicity: Periodicity) -> Self {
+        self.periodicity = Some(periodicity);
+        self
+    }
+
+    /// Set the periodicity adjustment
+    pub fn with_periodicity_adjustment(mut self, adjustment: PeriodicityAdjustment) -> Self {
+        self.periodicity_adjustment = Some(adjustment);
+        self
+    }
+
+    /// Convert the values to the given ISO currency
+    pub fn with_currency<S: Into<String>>(mut self, currency: S) -> Self {
+        self.currency = Some(currency.into());
+        self
+    }
+
+    /// Limit the number of data points returned per security
+    pub fn with_max_data_points(mut self, max_data_points: i32) -> Self {
+        self.max_data_points = Some(max_data_points);
+        self
+    }
+
+    /// Override the value of field `field_id`
+    pub fn with_override<F: Into<String>, V: Into<String>>(mut self, field_id: F, value: V) -> Self {
+        self.overrides.push((field_id.into(), value.into()));
+        self
+    }
+
+    fn fill(&self, request: &mut Request) -> Result<(), Error> {
+        {
+            let mut element = request.element();
+            element.set("startDate", self.start_date.as_str())?;
+            element.set("endDate", self.end_date.as_str())?;
+            if let Some(periodicity) = self.periodicity {
+                element.set("periodicitySelection", periodicity.as_str())?;
+            }
+            if let Some(adjustment) = self.periodicity_adjustment {
+                element.set("periodicityAdjustment", adjustment.as_str())?;
+            }
+            if let Some(currency) = &self.currency {
+                element.set("currency", currency.as_str())?;
+            }
+            if let Some(max_data_points) = self.max_data_points {
+                element.set("maxDataPoints", max_data_points)?;
+            }
+        }
+        for (field_id, value) in &self.overrides {
+            request.add_override(field_id, value.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// The time series of a single security
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimeSeries<T> {
+    /// One row per date, each row containing the `date` and the requested fields
+    pub rows: Vec<T>,
+    /// Fields which could not be retrieved
+    pub field_exceptions: Vec<FieldException>,
+    /// Set if the whole security could not be retrieved
+    pub security_error: Option<ErrorInfo>,
+}
+
+impl<'a> Session<'a> {
+    /// Request the `T::FIELDS` time series of all `securities` and block
+    /// until the final response is received.
+    ///
+    /// Every row is built by calling `T::on_field` for the `date` and each
+    /// field value of the data point.
+    pub fn hist_data<T, I, S>(&mut self, securities: I, options: HistOptions) -> Result<HashMap<String, TimeSeries<T>>, Error>
+    where
+        T: RefData,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.validate_fields(T::FIELDS)?;
+        let service = self.get_or_open_service(REFDATA_SERVICE)?;
+        let mut request = service.create_request("HistoricalDataRequest")?;
+        for security in securities {
+            request.append("securities", security.as_ref())?;
+        }
+        for field in T::FIELDS {
+            request.append("fields", *field)?;
+        }
+        options.fill(&mut request)?;
+
+        let mut results = HashMap::new();
+        self.collect_responses(request, |response| process_response(response, &mut results))?;
+        Ok(results)
+    }
+}
+
+fn process_response<T: RefData>(
+    response: &Element,
+    results: &mut HashMap<String, TimeSeries<T>>,
+) -> Result<(), Error> {
+    let security_data = response.get_element("securityData")?;
+    let security = security_data.get_element("security")?.value::<String>()?;
+    let entry = results.entry(security).or_insert_with(TimeSeries::default);
+
+    if let Ok(error) = security_data.get_element("securityError") {
+        entry.security_error = Some(ErrorInfo::from_element(&error));
+    }
+
+    entry.field_exceptions.extend(field_exceptions(&security_data)?);
+
+    if let Ok(field_data) = security_data.get_element("fieldData") {
+        for point in field_data.values::<Element>() {
+            let mut row = T::default();
+            for field in point.elements() {
+                row.on_field(&field.string_name(), &field)?;
+            }
+            entry.rows.push(row);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{Event, EventType};
+    use crate::testutil::{test_event, test_service};
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Point {
+        date: String,
+        px_last: f64,
+    }
+
+    impl RefData for Point {
+        const FIELDS: &'static [&'static str] = &["PX_LAST"];
+
+        fn on_field(&mut self, field: &str, element: &Element) -> Result<(), Error> {
+            match field {
+                "date" => self.date = element.value()?,
+                "PX_LAST" => self.px_last = element.value()?,
+                _ => {}
+            }
+            Ok(())
+        }
+    }
+
+    fn build_event(json: &str) -> Result<Event, Error> {
+        test_event(EventType::PartialResponse, "HistoricalDataResponse", None, json)
+    }
+
+    #[test]
+    fn test_fill() -> Result<(), Error> {
+        let service = test_service()?;
+        let mut request = service.create_request("HistoricalDataRequest")?;
+        HistOptions::new("20200101", "20201231")
+            .with_periodicity(Periodicity::SemiAnnually)
+            .with_periodicity_adjustment(PeriodicityAdjustment::Calendar)
+            .with_currency("EUR")
+            .with_max_data_points(10)
+            .with_override("BEST_FPERIOD_OVERRIDE", "1BF")
+            .fill(&mut request)?;
+
+        let element = request.element();
+        let string = |name| element.get_element(name).and_then(|e| e.value::<String>());
+        assert_eq!(string("startDate")?, "20200101");
+        assert_eq!(string("endDate")?, "20201231");
+        assert_eq!(string("periodicitySelection")?, "SEMI_ANNUALLY");
+        assert_eq!(string("periodicityAdjustment")?, "CALENDAR");
+        assert_eq!(string("currency")?, "EUR");
+        assert_eq!(element.get_element("maxDataPoints")?.value::<i32>()?, 10);
+        let overrides = element.get_element("overrides")?;
+        assert_eq!(overrides.num_values(), 1);
+        let item = overrides.get_at::<Element>(0)?;
+        assert_eq!(item.get_element("fieldId")?.value::<String>()?, "BEST_FPERIOD_OVERRIDE");
+        assert_eq!(item.get_element("value")?.value::<String>()?, "1BF");
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_response() -> Result<(), Error> {
+        let mut results = HashMap::new();
+        for json in &[
+            r#"{ "securityData": { "security": "IBM US Equity", "fieldData": [
+                { "date": "2020-01-02", "PX_LAST": 135.4 },
+                { "date": "2020-01-03", "PX_LAST": 134.3 }
+            ] } }"#,
+            r#"{ "securityData": { "security": "UNKNOWN Equity", "securityError": { "category": "BAD_SEC" } } }"#,
+        ] {
+            let event = build_event(json)?;
+            for message in event.messages() {
+                process_response::<Point>(&message.element(), &mut results)?;
+            }
+        }
+
+        let ibm = &results["IBM US Equity"];
+        assert_eq!(ibm.rows, vec![
+            Point { date: "2020-01-02".to_string(), px_last: 135.4 },
+            Point { date: "2020-01-03".to_string(), px_last: 134.3 },
+        ]);
+        assert!(ibm.security_error.is_none());
+        let unknown = &results["UNKNOWN Equity"];
+        assert!(unknown.rows.is_empty());
+        assert_eq!(unknown.security_error.as_ref().unwrap().category, "BAD_SEC");
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
+use crate::element::Element;
 use crate::errors::Error;
 use crate::service::Service;
 use blpapi_sys::*;
 use std::os::raw::c_int;
+use std::ptr;
 
 pub enum SeatType {
     InvalidSeat,
@@ -22,7 +24,67 @@ impl From<i32> for SeatType {
 pub struct Identity(pub(crate) *mut blpapi_Identity_t);
 
 impl Identity {
-    // TODO: blpapi_Identity_hasEntitlements
+    /// Return true if this 'Identity' is entitled to all the specified
+    /// 'eids' on the specified 'service'
+    pub fn has_entitlements(&self, service: &Service, eids: &[i32]) -> bool {
+        self.failed_entitlements(service, eids).is_empty()
+    }
+
+    /// Return the subset of 'eids' this 'Identity' is not entitled to on
+    /// the specified 'service'
+    pub fn failed_entitlements(&self, service: &Service, eids: &[i32]) -> Vec<i32> {
+        if eids.is_empty() {
+            return Vec::new();
+        }
+        self.check_entitlements(service, ptr::null(), eids, eids.len())
+            .unwrap_or_else(|| eids.to_vec())
+    }
+
+    /// Same as `failed_entitlements`, for the eids of the specified
+    /// 'eids' element, e.g. the `eidData` of a security. Fails if the
+    /// eids must be read from the element and one of them is not an int.
+    pub fn failed_entitlements_element(&self, service: &Service, eids: &Element) -> Result<Vec<i32>, Error> {
+        let count = eids.num_values();
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        match self.check_entitlements(service, eids.ptr, &[], count) {
+            Some(failed) => Ok(failed),
+            None => eids.values_checked::<i32>().collect(),
+        }
+    }
+
+    /// Return the failed entitlements, or None if the entitlement check
+    /// failed without detail, e.g. the service is not authorized
+    fn check_entitlements(
+        &self,
+        service: &Service,
+        eid_element: *const blpapi_Element_t,
+        eids: &[i32],
+        count: usize,
+    ) -> Option<Vec<i32>> {
+        let mut failed = vec![0 as c_int; count];
+        let mut failed_count = failed.len() as c_int;
+        let entitled = unsafe {
+            blpapi_Identity_hasEntitlements(
+                self.0,
+                service.0,
+                eid_element,
+                if eids.is_empty() { ptr::null() } else { eids.as_ptr() },
+                eids.len(),
+                failed.as_mut_ptr(),
+                &mut failed_count,
+            )
+        };
+        if entitled != 0 {
+            return Some(Vec::new());
+        }
+        if failed_count <= 0 {
+            return None;
+        }
+        failed.truncate(failed_count as usize);
+        Some(failed)
+    }
 
     /// Return true if this 'Identity' is authorized to consume the
     /// specified 'service'; otherwise return false.
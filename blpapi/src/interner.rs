@@ -0,0 +1,156 @@
+//! String interning for repetitive values
+//!
+//! Market data repeats the same strings (tickers, condition codes,
+//! exchanges, ...) for every tick. Reading them through an `Interner`
+//! returns a shared `Arc<str>` and only allocates the first time a value
+//! is seen.
+//!
+//! An `Interner` is owned by its user, e.g. a `MarketDataCache` with
+//! interned fields, so reading through it takes no lock. Code without a
+//! place to keep one, such as serde deserialization, uses the interner of
+//! the current thread.
+use crate::{element::Element, errors::Error};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::ffi::CStr;
+use std::sync::Arc;
+
+/// Default maximum length of an interned string
+const DEFAULT_MAX_LEN: usize = 64;
+
+/// A set of shared strings
+///
+/// Strings longer than `max_len` or received once the interner holds
+/// `capacity` strings are returned as a fresh `Arc<str>`, so an unexpected
+/// stream of distinct values cannot grow the interner without bound.
+#[derive(Debug, Clone)]
+pub struct Interner {
+    strings: HashSet<Arc<str>>,
+    max_len: usize,
+    capacity: Option<usize>,
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Interner {
+            strings: HashSet::new(),
+            max_len: DEFAULT_MAX_LEN,
+            capacity: None,
+        }
+    }
+}
+
+impl Interner {
+    /// Create an empty interner, with no capacity limit
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    /// Only intern strings up to `max_len` bytes (64 by default)
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Stop interning new strings once `capacity` strings are held
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Return the shared string equal to `value`, interning it if needed
+    pub fn intern(&mut self, value: &str) -> Arc<str> {
+        if let Some(interned) = self.strings.get(value) {
+            return interned.clone();
+        }
+        let interned: Arc<str> = Arc::from(value);
+        let full = matches!(self.capacity, Some(capacity) if self.strings.len() >= capacity);
+        if value.len() <= self.max_len && !full {
+            self.strings.insert(interned.clone());
+        }
+        interned
+    }
+
+    /// Number of interned strings
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Return true if no string is interned
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Forget all the interned strings, strings already returned are not
+    /// affected
+    pub fn clear(&mut self) {
+        self.strings.clear();
+    }
+}
+
+thread_local! {
+    /// Interner of the current thread, used by `with_thread_interner`
+    static LOCAL: RefCell<Interner> = RefCell::new(Interner::default());
+}
+
+/// Run `f` with the interner of the current thread, e.g. to configure or
+/// clear it
+///
+/// Each thread interns its own strings, without contention between threads.
+/// The returned strings can still be sent to other threads.
+///
+/// # Panics
+///
+/// If called from `f`.
+pub fn with_thread_interner<F: FnOnce(&mut Interner) -> R, R>(f: F) -> R {
+    LOCAL.with(|interner| f(&mut interner.borrow_mut()))
+}
+
+impl<'a> Element<'a> {
+    /// Get the string value at `index` through `interner`
+    pub fn get_interned_at(&self, index: usize, interner: &mut Interner) -> Result<Arc<str>, Error> {
+        let value = self.get_at::<&CStr>(index)?;
+        Ok(interner.intern(&value.to_string_lossy()))
+    }
+
+    /// Get the first string value through `interner`
+    pub fn value_interned(&self, interner: &mut Interner) -> Result<Arc<str>, Error> {
+        self.get_interned_at(0, interner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intern() {
+        let mut interner = Interner::new().with_max_len(8).with_capacity(2);
+        let a = interner.intern("IBM US");
+        let b = interner.intern("IBM US");
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(interner.len(), 1);
+
+        // too long
+        let long = interner.intern("VOD LN Equity");
+        assert!(!Arc::ptr_eq(&long, &interner.intern("VOD LN Equity")));
+
+        interner.intern("AAPL US");
+        let full = interner.intern("MSFT US");
+        assert_eq!(&*full, "MSFT US");
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn thread_interner() {
+        let a = with_thread_interner(|interner| interner.intern("IBM US"));
+        let b = with_thread_interner(|interner| interner.intern("IBM US"));
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let other = std::thread::spawn(|| with_thread_interner(|interner| interner.intern("IBM US")))
+            .join()
+            .unwrap();
+        assert_eq!(a, other);
+        assert!(!Arc::ptr_eq(&a, &other));
+    }
+}
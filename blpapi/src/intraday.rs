@@ -0,0 +1,287 @@
+//! Intraday bars and ticks (`//blp/refdata` `IntradayBarRequest` and
+//! `IntradayTickRequest`)
+//!
+//! All times are expressed in UTC.
+use crate::{
+    datetime::Datetime,
+    element::Element,
+    refdata::REFDATA_SERVICE,
+    request::Request,
+    session::Session,
+    Error,
+};
+use chrono::NaiveDateTime;
+use std::convert::TryInto;
+
+/// The kind of events to aggregate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntradayEventType {
+    Trade,
+    Bid,
+    Ask,
+    BidBest,
+    AskBest,
+    MidPrice,
+    AtTrade,
+    BestBid,
+    BestAsk,
+    Settle,
+}
+
+impl IntradayEventType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            IntradayEventType::Trade => "TRADE",
+            IntradayEventType::Bid => "BID",
+            IntradayEventType::Ask => "ASK",
+            IntradayEventType::BidBest => "BID_BEST",
+            IntradayEventType::AskBest => "ASK_BEST",
+            IntradayEventType::MidPrice => "MID_PRICE",
+            IntradayEventType::AtTrade => "AT_TRADE",
+            IntradayEventType::BestBid => "BEST_BID",
+            IntradayEventType::BestAsk => "BEST_ASK",
+            IntradayEventType::Settle => "SETTLE",
+        }
+    }
+}
+
+fn format_datetime(datetime: &NaiveDateTime) -> String {
+    datetime.format("%Y-%m-%dT%H:%M:%S%.3f").to_string()
+}
+
+fn get_datetime(element: &Element, name: &str) -> Result<NaiveDateTime, Error> {
+    let datetime = element.get_element(name)?.value::<Datetime>()?;
+    datetime.try_into().map_err(Error::DateConversionError)
+}
+
+/// An `IntradayBarRequest` builder
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntradayBarRequest {
+    security: String,
+    event_type: IntradayEventType,
+    interval: u32,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    gap_fill_initial_bar: bool,
+}
+
+impl IntradayBarRequest {
+    /// Request 1 minute `TRADE` bars of `security` between `start` and `end`
+    pub fn new<S: Into<String>>(security: S, start: NaiveDateTime, end: NaiveDateTime) -> Self {
+        IntradayBarRequest {
+            security: security.into(),
+            event_type: IntradayEventType::Trade,
+            interval: 1,
+            start,
+            end,
+            gap_fill_initial_bar: false,
+        }
+    }
+
+    /// Set the type of events to aggregate
+    pub fn with_event_type(mut self, event_type: IntradayEventType) -> Self {
+        self.event_type = event_type;
+        self
+    }
+
+    /// Set the bar length in minutes, between 1 and 1440
+    pub fn with_interval(mut self, interval: u32) -> Result<Self, Error> {
+        if !(1..=1440).contains(&interval) {
+            return Err(Error::InvalidRequestParameter(format!(
+                "interval must be between 1 and 1440 minutes, got {}",
+                interval
+            )));
+        }
+        self.interval = interval;
+        Ok(self)
+    }
+
+    /// Fill the first bar with the last value before `start` if no event
+    /// happened in its interval
+    pub fn with_gap_fill_initial_bar(mut self, gap_fill_initial_bar: bool) -> Self {
+        self.gap_fill_initial_bar = gap_fill_initial_bar;
+        self
+    }
+
+    fn fill(&self, request: &mut Request) -> Result<(), Error> {
+        let mut element = request.element();
+        element.set("security", self.security.as_str())?;
+        element.set("eventType", self.event_type.as_str())?;
+        element.set("interval", self.interval as i32)?;
+        element.set("startDateTime", format_datetime(&self.start).as_str())?;
+        element.set("endDateTime", format_datetime(&self.end).as_str())?;
+        element.set("gapFillInitialBar", self.gap_fill_initial_bar)?;
+        Ok(())
+    }
+}
+
+/// A single bar
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bar {
+    /// Start of the bar
+    pub time: NaiveDateTime,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: i64,
+    pub num_events: i64,
+    pub value: f64,
+}
+
+impl Bar {
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        Ok(Bar {
+            time: get_datetime(element, "time")?,
+            open: element.get_element("open")?.value()?,
+            high: element.get_element("high")?.value()?,
+            low: element.get_element("low")?.value()?,
+            close: element.get_element("close")?.value()?,
+            volume: element.get_element("volume")?.value()?,
+            num_events: element.get_element("numEvents")?.value()?,
+            value: element.get_element("value")?.value()?,
+        })
+    }
+}
+
+/// An `IntradayTickRequest` builder
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntradayTickRequest {
+    security: String,
+    event_types: Vec<IntradayEventType>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    include_condition_codes: bool,
+    include_exchange_codes: bool,
+}
+
+impl IntradayTickRequest {
+    /// Request `TRADE` ticks of `security` between `start` and `end`
+    pub fn new<S: Into<String>>(security: S, start: NaiveDateTime, end: NaiveDateTime) -> Self {
+        IntradayTickRequest {
+            security: security.into(),
+            event_types: Vec::new(),
+            start,
+            end,
+            include_condition_codes: false,
+            include_exchange_codes: false,
+        }
+    }
+
+    /// Add a type of events to return, defaults to `TRADE` only
+    pub fn with_event_type(mut self, event_type: IntradayEventType) -> Self {
+        self.event_types.push(event_type);
+        self
+    }
+
+    /// Return the condition codes of each tick
+    pub fn with_condition_codes(mut self, include_condition_codes: bool) -> Self {
+        self.include_condition_codes = include_condition_codes;
+        self
+    }
+
+    /// Return the exchange code of each tick
+    pub fn with_exchange_codes(mut self, include_exchange_codes: bool) -> Self {
+        self.include_exchange_codes = include_exchange_codes;
+        self
+    }
+
+    fn fill(&self, request: &mut Request) -> Result<(), Error> {
+        if self.event_types.is_empty() {
+            request.append("eventTypes", IntradayEventType::Trade.as_str())?;
+        }
+        for event_type in &self.event_types {
+            request.append("eventTypes", event_type.as_str())?;
+        }
+        let mut element = request.element();
+        element.set("security", self.security.as_str())?;
+        element.set("startDateTime", format_datetime(&self.start).as_str())?;
+        element.set("endDateTime", format_datetime(&self.end).as_str())?;
+        element.set("includeConditionCodes", self.include_condition_codes)?;
+        element.set("includeExchangeCodes", self.include_exchange_codes)?;
+        Ok(())
+    }
+}
+
+/// A single tick
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tick {
+    pub time: NaiveDateTime,
+    /// Event type, e.g. `TRADE`
+    pub event_type: String,
+    pub value: f64,
+    pub size: i64,
+    /// Only set if requested
+    pub condition_codes: Option<String>,
+    /// Only set if requested
+    pub exchange_code: Option<String>,
+}
+
+impl Tick {
+    fn from_element(element: &Element) -> Result<Self, Error> {
+        let optional = |name| element.get_element(name).and_then(|e| e.value::<String>()).ok();
+        Ok(Tick {
+            time: get_datetime(element, "time")?,
+            event_type: element.get_element("type")?.value()?,
+            value: element.get_element("value")?.value()?,
+            size: element.get_element("size")?.value()?,
+            condition_codes: optional("conditionCodes"),
+            exchange_code: optional("exchangeCode"),
+        })
+    }
+}
+
+impl<'a> Session<'a> {
+    /// Send an intraday bar request and block until all bars are received
+    pub fn intraday_bars(&mut self, request: &IntradayBarRequest) -> Result<Vec<Bar>, Error> {
+        let service = self.get_or_open_service(REFDATA_SERVICE)?;
+        let mut blp_request = service.create_request("IntradayBarRequest")?;
+        request.fill(&mut blp_request)?;
+
+        let mut bars = Vec::new();
+        self.collect_responses(blp_request, |response| {
+            let bar_data = response.get_element("barData")?;
+            let data = bar_data.get_element("barTickData")?;
+            for bar in data.values::<Element>() {
+                bars.push(Bar::from_element(&bar)?);
+            }
+            Ok(())
+        })?;
+        Ok(bars)
+    }
+
+    /// Send an intraday tick request and block until all ticks are received
+    pub fn intraday_ticks(&mut self, request: &IntradayTickRequest) -> Result<Vec<Tick>, Error> {
+        let service = self.get_or_open_service(REFDATA_SERVICE)?;
+        let mut blp_request = service.create_request("IntradayTickRequest")?;
+        request.fill(&mut blp_request)?;
+
+        let mut ticks = Vec::new();
+        self.collect_responses(blp_request, |response| {
+            let tick_data = response.get_element("tickData")?;
+            let data = tick_data.get_element("tickData")?;
+            for tick in data.values::<Element>() {
+                ticks.push(Tick::from_element(&tick)?);
+            }
+            Ok(())
+        })?;
+        Ok(ticks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_with_interval() {
+        let start = NaiveDate::from_ymd(2020, 1, 1).and_hms(9, 0, 0);
+        let end = NaiveDate::from_ymd(2020, 1, 1).and_hms(17, 0, 0);
+        let request = IntradayBarRequest::new("IBM US Equity", start, end);
+        assert_eq!(request.clone().with_interval(1).unwrap().interval, 1);
+        assert_eq!(request.clone().with_interval(1440).unwrap().interval, 1440);
+        assert!(matches!(request.clone().with_interval(0), Err(Error::InvalidRequestParameter(_))));
+        assert!(matches!(request.with_interval(1441), Err(Error::InvalidRequestParameter(_))));
+    }
+}
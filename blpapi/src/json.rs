@@ -0,0 +1,142 @@
+//! Conversion of elements and messages to `serde_json::Value`
+//!
+//! Sequences become objects, choices objects with their selected element,
+//! arrays become arrays and null elements `null`. Datetimes are formatted
+//! as ISO 8601 strings and byte arrays as arrays of numbers.
+use crate::{
+    element::Element,
+    message::Message,
+    value::Value,
+    walk::{walk, Visitor},
+    Error,
+};
+use serde_json::{Map, Number, Value as Json};
+
+/// Builds a json value from the walk of an element
+#[derive(Default)]
+struct JsonBuilder {
+    /// The arrays and objects being built, with the name of the next field
+    /// of objects
+    stack: Vec<(Json, Option<String>)>,
+    result: Option<Json>,
+}
+
+impl JsonBuilder {
+    fn push(&mut self, json: Json) {
+        match self.stack.last_mut() {
+            Some((Json::Array(values), _)) => values.push(json),
+            Some((Json::Object(object), field)) => {
+                object.insert(field.take().unwrap_or_default(), json);
+            }
+            _ => self.result = Some(json),
+        }
+    }
+
+    fn end(&mut self) -> Result<(), Error> {
+        if let Some((json, _)) = self.stack.pop() {
+            self.push(json);
+        }
+        Ok(())
+    }
+}
+
+impl Visitor for JsonBuilder {
+    fn value(&mut self, value: Value) -> Result<(), Error> {
+        let json = match value {
+            Value::Bool(value) => Json::Bool(value),
+            Value::I32(value) => Json::from(value),
+            Value::I64(value) => Json::from(value),
+            Value::F32(value) => Number::from_f64(value.into()).map_or(Json::Null, Json::Number),
+            Value::F64(value) => Number::from_f64(value).map_or(Json::Null, Json::Number),
+            Value::String(value) => Json::String(value),
+            Value::Datetime(value) => Json::String(value.to_iso8601()),
+            Value::Bytes(value) => Json::from(value),
+            Value::Name(value) => Json::String(value.to_string_lossy()),
+            Value::Null => Json::Null,
+        };
+        self.push(json);
+        Ok(())
+    }
+
+    fn start_array(&mut self) -> Result<(), Error> {
+        self.stack.push((Json::Array(Vec::new()), None));
+        Ok(())
+    }
+
+    fn end_array(&mut self) -> Result<(), Error> {
+        self.end()
+    }
+
+    fn start_object(&mut self) -> Result<(), Error> {
+        self.stack.push((Json::Object(Map::new()), None));
+        Ok(())
+    }
+
+    fn field(&mut self, name: &str) -> Result<(), Error> {
+        if let Some((_, field)) = self.stack.last_mut() {
+            *field = Some(name.to_string());
+        }
+        Ok(())
+    }
+
+    fn end_object(&mut self) -> Result<(), Error> {
+        self.end()
+    }
+}
+
+impl Element<'_> {
+    /// Convert this element, and all its sub-elements, to a json value
+    pub fn to_json(&self) -> Result<Json, Error> {
+        let mut builder = JsonBuilder::default();
+        walk(self, &mut builder)?;
+        Ok(builder.result.unwrap_or(Json::Null))
+    }
+}
+
+impl Message {
+    /// Convert the content of this message to a json value
+    pub fn to_json(&self) -> Result<Json, Error> {
+        self.element().to_json()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::Datetime;
+    use blpapi_sys::{blpapi_Datetime_t, BLPAPI_DATETIME_DATE_PART};
+
+    #[test]
+    fn json_builder() -> Result<(), Error> {
+        let mut builder = JsonBuilder::default();
+        builder.start_object()?;
+        builder.field("prices")?;
+        builder.start_array()?;
+        builder.value(Value::F64(1.5))?;
+        builder.value(Value::F64(f64::NAN))?;
+        builder.end_array()?;
+        builder.field("security")?;
+        builder.start_object()?;
+        builder.field("ticker")?;
+        builder.value(Value::String("IBM US Equity".to_string()))?;
+        builder.end_object()?;
+        builder.field("updated")?;
+        builder.value(Value::Datetime(Datetime(blpapi_Datetime_t {
+            parts: BLPAPI_DATETIME_DATE_PART as u8,
+            year: 2024,
+            month: 2,
+            day: 29,
+            ..Datetime::default().0
+        })))?;
+        builder.end_object()?;
+        assert_eq!(
+            builder.result,
+            Some(serde_json::json!({
+                "prices": [1.5, null],
+                "security": { "ticker": "IBM US Equity" },
+                "updated": "2024-02-29",
+            }))
+        );
+        Ok(())
+    }
+}
@@ -1,25 +1,91 @@
+pub mod auth_options;
+pub mod authorization;
+pub mod cancellation;
+pub mod capture;
 pub mod correlation_id;
 pub mod datetime;
+pub mod decimal;
+pub mod delivery;
+pub mod diagnostics;
 pub mod element;
 pub mod errors;
 pub mod event;
+pub mod event_channel;
+pub mod event_router;
 pub mod eventdispatcher;
+pub mod eventformatter;
+pub mod export;
+pub mod field_map;
+pub mod fields;
+pub mod histdata;
 pub mod identity;
+pub mod interner;
+#[cfg(feature="dates")]
+pub mod intraday;
 pub mod logging;
 pub mod message;
 pub mod name;
+pub mod name_cache;
+pub mod pool;
+pub mod provider;
+pub mod quiesce;
+pub mod refdata;
 pub mod request;
+pub mod request_builder;
+pub mod request_template;
+pub mod resilient;
+pub mod resolutionlist;
+pub mod schema;
+pub mod security;
 pub mod service;
 pub mod session;
 pub mod session_options;
+pub mod subscription_builder;
+pub mod subscription_tracker;
 pub mod subscriptionlist;
+pub mod timepoint;
 pub mod tls_options;
+pub mod topic;
+pub mod topiclist;
+pub mod transaction;
 mod utils;
+pub mod value;
+pub mod version;
+mod walk;
+pub mod zfp;
 
 #[cfg(feature="serialization")]
 pub mod serde;
 
-#[cfg(test)]
-mod testutil;
+#[cfg(feature="serialization")]
+pub mod typed_service;
+
+#[cfg(feature="json")]
+pub mod json;
+
+#[cfg(feature="recorder")]
+pub mod recorder;
+
+#[cfg(feature="tracing")]
+mod spans;
+
+#[cfg(feature="user-agent")]
+pub mod user_agent;
 
+#[cfg(feature="mktdata-cache")]
+pub mod mktdata_cache;
+
+#[cfg(feature="tokio")]
+pub mod async_session;
+
+#[cfg(any(test, feature="test-util"))]
+pub mod testutil;
+
+pub use element::{GetValue, SetValue};
 pub use errors::Error;
+pub use refdata::RefData;
+pub use value::Value;
+
+/// Loading of blpapi at runtime
+#[cfg(feature="dlopen")]
+pub use blpapi_sys::dlopen;
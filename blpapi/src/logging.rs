@@ -1,9 +1,16 @@
+//! Logs of the underlying library
+//!
+//! The logs of blpapi can be received with `register_callback`, or
+//! forwarded to the `log` crate with `bridge_to_log` (`log` feature) or to
+//! `tracing` with `bridge_to_tracing` (`tracing` feature). The category
+//! of a log record is used as its target.
 use blpapi_sys::*;
 use crate::datetime::Datetime;
 use crate::errors::Error;
 use std::os::raw::{c_int, c_char};
 use std::ffi::CStr;
 use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, RwLock};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum LoggingSeverity {
@@ -46,16 +53,25 @@ impl From<blpapi_Logging_Severity_t> for LoggingSeverity {
     }
 }
 
-static mut RUST_CALLBACK: Option<Box<dyn Fn(u64, LoggingSeverity, Datetime, &str, &str) + 'static>> = None;
+type Callback = Arc<dyn Fn(u64, LoggingSeverity, Datetime, &str, &str) + Send + Sync + 'static>;
+
+/// The callback called by `c_callback`, from the threads of blpapi
+static RUST_CALLBACK: RwLock<Option<Callback>> = RwLock::new(None);
+
+fn set_rust_callback(callback: Option<Callback>) {
+    *RUST_CALLBACK.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = callback;
+}
 
 type LoggingFunc = unsafe extern "C" fn(thread_id: blpapi_UInt64_t, severity: c_int, timestamp: blpapi_Datetime_t, category: *const c_char, message: *const c_char);
 unsafe extern "C" fn c_callback(thread_id: blpapi_UInt64_t, severity: c_int, timestamp: blpapi_Datetime_t, category: *const c_char, message: *const c_char) {
-    match RUST_CALLBACK.as_ref() {
+    // not called under the lock, so that the callback can (de)register
+    let callback = RUST_CALLBACK.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+    match callback {
         Some(callback) => {
-            let category = CStr::from_ptr(category).to_str().unwrap();
-            let message = CStr::from_ptr(message).to_str().unwrap();
+            let category = CStr::from_ptr(category).to_string_lossy();
+            let message = CStr::from_ptr(message).to_string_lossy();
             if let Err(err) = catch_unwind(AssertUnwindSafe(|| {
-                callback(thread_id, LoggingSeverity::from(severity as blpapi_Logging_Severity_t), Datetime(timestamp), category, message);
+                callback(thread_id, LoggingSeverity::from(severity as blpapi_Logging_Severity_t), Datetime(timestamp), &category, &message);
             })) {
                 eprintln!("{:?}", err);
                 std::process::abort();
@@ -72,11 +88,92 @@ unsafe extern "C" fn c_callback(thread_id: blpapi_UInt64_t, severity: c_int, tim
 /// the last registered callback will take effect.  Registering with a
 /// 'None' callback will de-register the callback.
 /// '0' is returned if callback is registered and a non-zero otherwise.
-pub fn register_callback(callback: Option<impl Fn(u64, LoggingSeverity, Datetime, &str, &str) + 'static>, threshold_severity: LoggingSeverity) -> Result<(), Error> {
-    let res = unsafe {
-        let c_callback = callback.as_ref().and(Some(c_callback as LoggingFunc));
-        RUST_CALLBACK = callback.map(|cb| Box::new(cb) as _);
-        blpapi_Logging_registerCallback(c_callback, threshold_severity.into())
+pub fn register_callback(callback: Option<impl Fn(u64, LoggingSeverity, Datetime, &str, &str) + Send + Sync + 'static>, threshold_severity: LoggingSeverity) -> Result<(), Error> {
+    let callback = match callback {
+        Some(callback) => callback,
+        None => return unregister_callback(),
     };
+    // replaced before registering, the previous callback may still be called
+    // until blpapi switches to the new threshold
+    set_rust_callback(Some(Arc::new(callback)));
+    let res = unsafe { blpapi_Logging_registerCallback(Some(c_callback as LoggingFunc), threshold_severity.into()) };
+    Error::check(res)
+}
+
+/// De-register the callback registered with 'register_callback', if any.
+pub fn unregister_callback() -> Result<(), Error> {
+    let res = unsafe { blpapi_Logging_registerCallback(None, LoggingSeverity::OFF.into()) };
+    set_rust_callback(None);
     Error::check(res)
-}
\ No newline at end of file
+}
+
+/// Log a test message at the specified 'severity', e.g. to check that the
+/// registered callback receives the logs of blpapi.
+pub fn log_test_message(severity: LoggingSeverity) {
+    unsafe { blpapi_Logging_logTestMessage(severity.into()) }
+}
+
+/// Forward the logs of blpapi with severity greater than or equal to
+/// 'threshold_severity' to the `log` crate, with the category as target
+#[cfg(feature = "log")]
+pub fn bridge_to_log(threshold_severity: LoggingSeverity) -> Result<(), Error> {
+    register_callback(Some(|thread_id, severity, _timestamp, category: &str, message: &str| {
+        let level = match severity {
+            LoggingSeverity::OFF => return,
+            LoggingSeverity::FATAL | LoggingSeverity::ERROR => log::Level::Error,
+            LoggingSeverity::WARN => log::Level::Warn,
+            LoggingSeverity::INFO => log::Level::Info,
+            LoggingSeverity::DEBUG => log::Level::Debug,
+            LoggingSeverity::TRACE => log::Level::Trace,
+        };
+        log::log!(target: category, level, "[{}] {}", thread_id, message);
+    }), threshold_severity)
+}
+
+/// Forward the logs of blpapi with severity greater than or equal to
+/// 'threshold_severity' to `tracing` events, with the category as a field
+/// (tracing targets must be static)
+#[cfg(feature = "tracing")]
+pub fn bridge_to_tracing(threshold_severity: LoggingSeverity) -> Result<(), Error> {
+    register_callback(Some(|thread_id, severity, _timestamp, category: &str, message: &str| {
+        match severity {
+            LoggingSeverity::OFF => (),
+            LoggingSeverity::FATAL | LoggingSeverity::ERROR => tracing::error!(target: "blpapi", thread_id, category, "{}", message),
+            LoggingSeverity::WARN => tracing::warn!(target: "blpapi", thread_id, category, "{}", message),
+            LoggingSeverity::INFO => tracing::info!(target: "blpapi", thread_id, category, "{}", message),
+            LoggingSeverity::DEBUG => tracing::debug!(target: "blpapi", thread_id, category, "{}", message),
+            LoggingSeverity::TRACE => tracing::trace!(target: "blpapi", thread_id, category, "{}", message),
+        }
+    }), threshold_severity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_callback() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        set_rust_callback(Some(Arc::new({
+            let received = received.clone();
+            move |_thread_id, severity, _timestamp, category: &str, message: &str| {
+                received.lock().unwrap().push((severity, category.to_string(), message.to_string()));
+                // re-registering from the callback must not deadlock
+                set_rust_callback(None);
+            }
+        })));
+
+        let category = CString::new("blpapi.session").unwrap();
+        let message = CString::new("test message").unwrap();
+        let severity: blpapi_Logging_Severity_t = LoggingSeverity::WARN.into();
+        unsafe {
+            c_callback(1, severity as c_int, blpapi_Datetime_t::default(), category.as_ptr(), message.as_ptr());
+            c_callback(1, severity as c_int, blpapi_Datetime_t::default(), category.as_ptr(), message.as_ptr());
+        }
+
+        let received = received.lock().unwrap();
+        assert_eq!(*received, vec![(LoggingSeverity::WARN, "blpapi.session".to_string(), "test message".to_string())]);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{correlation_id::CorrelationId, errors::Error, element::Element, name::Name};
+use crate::{correlation_id::CorrelationId, errors::Error, element::{Element, ElementSummary}, identity::Identity, name::Name, service::Service, timepoint::TimePoint};
 use blpapi_sys::*;
 use std::ffi::CStr;
 use std::fmt::{Display, Debug, Formatter};
@@ -29,7 +29,30 @@ impl From<c_uint> for FragmentType {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecapType {
+    /// normal data tick
+    None,
+    /// generated on request by subscriber
+    Solicited,
+    /// generated by the service
+    Unsolicited,
+}
+
+impl From<c_int> for RecapType {
+    fn from(recap_type: c_int) -> Self {
+        match recap_type as u32 {
+            blpapi_sys::BLPAPI_MESSAGE_RECAPTYPE_SOLICITED => RecapType::Solicited,
+            blpapi_sys::BLPAPI_MESSAGE_RECAPTYPE_UNSOLICITED => RecapType::Unsolicited,
+            _ => RecapType::None,
+        }
+    }
+}
+
 /// A message
+///
+/// A `Message` holds a reference on the underlying message: it can outlive
+/// the `Event` it was received in, e.g. to be sent to another thread.
 pub struct Message(pub(crate) *mut blpapi_Message_t);
 
 impl Message {
@@ -64,14 +87,54 @@ impl Message {
 
     /// Get correlation id
     pub fn correlation_id(&self, index: usize) -> Option<CorrelationId> {
-        if index > self.num_correlation_ids() {
+        if index >= self.num_correlation_ids() {
             None
         } else {
             unsafe {
                 let ptr = blpapi_Message_correlationId(self.0, index);
-                Some(CorrelationId(ptr))
+                Some(CorrelationId::from_raw(ptr))
+            }
+        }
+    }
+
+    /// Iterate over all the correlation ids
+    pub fn correlation_ids(&self) -> impl Iterator<Item = CorrelationId> + '_ {
+        (0..self.num_correlation_ids()).filter_map(move |index| self.correlation_id(index))
+    }
+
+    /// Get the service this message originates from, if any
+    pub fn service(&self) -> Option<Service> {
+        let service = unsafe { blpapi_Message_service(self.0) };
+        if service.is_null() {
+            return None;
+        }
+        unsafe { blpapi_Service_addRef(service) };
+        Some(Service(service))
+    }
+
+    /// Return the entitlement ids of this message `identity` is not
+    /// entitled to on `service`, or an empty vector if the message may be
+    /// redistributed to `identity`.
+    ///
+    /// The eids are read from the `EID` field of subscription data and from
+    /// the `eidData` of every `securityData` of reference data responses,
+    /// requested with `returnEids`.
+    pub fn failed_entitlements(&self, identity: &Identity, service: &Service) -> Result<Vec<i32>, Error> {
+        let element = self.element();
+        let mut failed = Vec::new();
+        if let Some(eids) = element.try_get_element("EID") {
+            failed.extend(identity.failed_entitlements_element(service, &eids)?);
+        }
+        if let Some(securities) = element.try_get_element("securityData") {
+            for security_data in securities.values::<Element>() {
+                if let Some(eids) = security_data.try_get_element("eidData") {
+                    failed.extend(identity.failed_entitlements_element(service, &eids)?);
+                }
             }
         }
+        failed.sort_unstable();
+        failed.dedup();
+        Ok(failed)
     }
 
     /// Get corresponding element
@@ -80,11 +143,37 @@ impl Message {
         Element { ptr: elements, _marker: PhantomData }
     }
 
+    /// Get the shape of the message content, see `Element::summary`
+    pub fn summary(&self) -> ElementSummary {
+        self.element().summary()
+    }
+
+    /// Get the fragment type, large messages being split into several
+    /// fragments to be reassembled
     pub fn fragment_type(&self) -> FragmentType {
         let fragment_type = unsafe { blpapi_Message_fragmentType(self.0) as u32 };
         FragmentType::from(fragment_type)
     }
 
+    /// Get the recap type, a recap containing the full image of the
+    /// subscribed fields rather than an update
+    pub fn recap_type(&self) -> RecapType {
+        RecapType::from(unsafe { blpapi_Message_recapType(self.0) })
+    }
+
+    /// Get the time this message was received, only recorded for
+    /// subscription data when enabled with
+    /// `SessionOptions::with_record_subscription_data_receive_times`
+    pub fn time_received(&self) -> Option<TimePoint> {
+        let mut time_point = blpapi_TimePoint_t::default();
+        let res = unsafe { blpapi_Message_timeReceived(self.0, &mut time_point) };
+        if res == 0 {
+            Some(TimePoint(time_point.d_value))
+        } else {
+            None
+        }
+    }
+
     /// Format this Message to the specified formatter at the
     /// (absolute value of) the optionally specified indentation
     /// 'indent_level'. If 'indent_level' is specified, optionally
@@ -136,3 +225,27 @@ impl Display for Message {
 
 unsafe impl Send for Message {}
 unsafe impl Sync for Message {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventType;
+    use crate::testutil::{EventBuilder, MessageProperties};
+
+    #[test]
+    fn test_correlation_ids() -> Result<(), Error> {
+        let ids = [CorrelationId::new_int(1, None), CorrelationId::new_int(2, None)];
+        let properties = MessageProperties::new()?.with_correlation_ids(&ids)?;
+        let event = EventBuilder::new(EventType::SubscriptionStatus)?
+            .append_message_from_json(Name::new("SubscriptionStarted"), Some(properties), "{}")?
+            .build();
+        let message = event.messages().next().unwrap();
+
+        assert_eq!(message.num_correlation_ids(), 2);
+        assert_eq!(message.correlation_id(1), Some(ids[1].clone()));
+        // one past the last index is out of bounds
+        assert_eq!(message.correlation_id(2), None);
+        assert_eq!(message.correlation_ids().collect::<Vec<_>>(), ids);
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+//! Current value store for market data
+//!
+//! A `MarketDataCache` fed with the SUBSCRIPTION_DATA events of a session
+//! keeps the latest value of every (topic, field), e.g. to render pricing
+//! views or reconcile positions, without each consumer handling the ticks.
+//!
+//! Subscription data messages are identified by their correlation id: the
+//! topics are registered from the `SubscriptionList` used to subscribe.
+//!
+//! String fields repeating the same values on every tick, such as condition
+//! codes or exchanges, can be interned: their values are then stored as
+//! shared `Arc<str>` and only allocated the first time they are seen.
+use crate::{
+    correlation_id::CorrelationId,
+    element::DataType,
+    event::{Event, EventType},
+    interner::Interner,
+    message::Message,
+    name::Name,
+    subscriptionlist::SubscriptionList,
+    value::Value,
+    Error,
+};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// The latest values of a topic
+#[derive(Debug, Clone)]
+pub struct TopicValues {
+    /// Latest value per field
+    pub fields: HashMap<String, Value>,
+    /// Latest value per interned field, see
+    /// `MarketDataCache::with_interned_fields`
+    pub strings: HashMap<Arc<str>, Arc<str>>,
+    /// When the last update of the topic was received
+    pub updated_at: Instant,
+}
+
+/// Latest value per (topic, field)
+#[derive(Debug, Default)]
+pub struct MarketDataCache {
+    topics: HashMap<CorrelationId, String>,
+    values: HashMap<String, TopicValues>,
+    keep_nulls: bool,
+    interned_fields: HashSet<Name>,
+    interner: Interner,
+}
+
+impl MarketDataCache {
+    pub fn new() -> Self {
+        MarketDataCache::default()
+    }
+
+    /// Store null values as `Value::Null` instead of ignoring them, e.g.
+    /// when fields are reset at the start of the day
+    pub fn with_nulls(mut self, keep_nulls: bool) -> Self {
+        self.keep_nulls = keep_nulls;
+        self
+    }
+
+    /// Store the values of the string `fields` through `interner`, in
+    /// `TopicValues::strings` instead of `TopicValues::fields`. Null values
+    /// of these fields are ignored.
+    ///
+    /// Their values are read with `string`, or `value` on a `snapshot`.
+    pub fn with_interned_fields<I, S>(mut self, interner: Interner, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.interner = interner;
+        self.interned_fields.extend(fields.into_iter().map(|field| Name::new(field.as_ref())));
+        self
+    }
+
+    /// Cache the updates of the subscription `correlation_id` under `topic`
+    pub fn register(&mut self, correlation_id: CorrelationId, topic: &str) {
+        self.topics.insert(correlation_id, topic.to_string());
+    }
+
+    /// Register all the entries of `list`, by their topic string
+    pub fn register_list(&mut self, list: &SubscriptionList) {
+        for entry in list.entries() {
+            self.topics.insert(entry.correlation_id, entry.topic_string);
+        }
+    }
+
+    /// Update the cache from the messages of `event`, returning the number
+    /// of values updated. Events other than SUBSCRIPTION_DATA are ignored.
+    pub fn handle_event(&mut self, event: &Event) -> Result<usize, Error> {
+        if event.event_type() != EventType::SubscriptionData {
+            return Ok(0);
+        }
+        let mut count = 0;
+        for message in event.messages() {
+            count += self.handle_message(&message)?;
+        }
+        Ok(count)
+    }
+
+    /// Update the cache from a SUBSCRIPTION_DATA `message`, returning the
+    /// number of values updated
+    pub fn handle_message(&mut self, message: &Message) -> Result<usize, Error> {
+        let topics: Vec<String> = message
+            .correlation_ids()
+            .filter_map(|correlation_id| self.topics.get(&correlation_id).cloned())
+            .collect();
+        if topics.is_empty() {
+            return Ok(0);
+        }
+
+        let mut updates = Vec::new();
+        let mut strings = Vec::new();
+        for field in message.element().elements() {
+            let data_type = field.data_type();
+            if field.is_array() || data_type == DataType::Sequence || data_type == DataType::Choice {
+                continue;
+            }
+            if self.interned_fields.contains(&field.name()) {
+                if !field.is_null()? {
+                    let name = self.interner.intern(&field.name().to_cstr().to_string_lossy());
+                    strings.push((name, field.value_interned(&mut self.interner)?));
+                }
+                continue;
+            }
+            let value = field.get_value_dyn(0)?;
+            if value.is_null() && !self.keep_nulls {
+                continue;
+            }
+            updates.push((field.string_name(), value));
+        }
+
+        let now = Instant::now();
+        for topic in &topics {
+            let values = self.values.entry(topic.clone()).or_insert_with(|| TopicValues {
+                fields: HashMap::new(),
+                strings: HashMap::new(),
+                updated_at: now,
+            });
+            values.updated_at = now;
+            values.fields.extend(updates.iter().cloned());
+            values.strings.extend(strings.iter().cloned());
+        }
+        Ok((updates.len() + strings.len()) * topics.len())
+    }
+
+    /// Get the latest value of `field` for `topic`
+    pub fn value(&self, topic: &str, field: &str) -> Option<&Value> {
+        self.values.get(topic)?.fields.get(field)
+    }
+
+    /// Get the latest value of the string `field` for `topic`, interned or
+    /// not
+    pub fn string(&self, topic: &str, field: &str) -> Option<Arc<str>> {
+        let values = self.values.get(topic)?;
+        if let Some(value) = values.strings.get(field) {
+            return Some(value.clone());
+        }
+        match values.fields.get(field)? {
+            Value::String(value) => Some(Arc::from(value.as_str())),
+            _ => None,
+        }
+    }
+
+    /// Get the latest value of `field` for `topic`, converted to `T`
+    pub fn get<T: TryFrom<Value, Error = Error>>(&self, topic: &str, field: &str) -> Result<Option<T>, Error> {
+        self.value(topic, field).cloned().map(T::try_from).transpose()
+    }
+
+    /// Get the latest value of a numeric `field` for `topic`, as `f64`
+    pub fn f64(&self, topic: &str, field: &str) -> Option<f64> {
+        match self.value(topic, field)? {
+            Value::I32(value) => Some(f64::from(*value)),
+            Value::I64(value) => Some(*value as f64),
+            Value::F32(value) => Some(f64::from(*value)),
+            Value::F64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Get the latest values of `topic`
+    pub fn topic(&self, topic: &str) -> Option<&TopicValues> {
+        self.values.get(topic)
+    }
+
+    /// Iterate over the topics which received at least one update
+    pub fn topics(&self) -> impl Iterator<Item = &str> {
+        self.values.keys().map(String::as_str)
+    }
+
+    /// Copy the latest values of all the topics
+    pub fn snapshot(&self) -> HashMap<String, HashMap<String, Value>> {
+        self.values
+            .iter()
+            .map(|(topic, values)| {
+                let mut fields = values.fields.clone();
+                fields.extend(
+                    values.strings.iter().map(|(field, value)| (field.to_string(), Value::String(value.to_string()))),
+                );
+                (topic.clone(), fields)
+            })
+            .collect()
+    }
+
+    /// Forget the values of `topic`, e.g. once unsubscribed
+    pub fn remove(&mut self, topic: &str) -> Option<TopicValues> {
+        self.topics.retain(|_, t| t != topic);
+        self.values.remove(topic)
+    }
+
+    /// Forget all the values, keeping the registered topics
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{test_event, MessageProperties};
+
+    fn build_event(event_type: EventType, correlation_id: &CorrelationId, json: &str) -> Result<Event, Error> {
+        let properties = MessageProperties::new()?.with_correlation_ids(std::slice::from_ref(correlation_id))?;
+        test_event(event_type, "MarketDataEvents", Some(properties), json)
+    }
+
+    #[test]
+    fn test_handle_event() -> Result<(), Error> {
+        let ibm = CorrelationId::new_int(1, None);
+        let mut cache = MarketDataCache::new();
+        cache.register(ibm.clone(), "IBM US Equity");
+
+        let event = build_event(EventType::SubscriptionData, &ibm, r#"{ "LAST_PRICE": 101.5, "VOLUME": 1000 }"#)?;
+        assert_eq!(cache.handle_event(&event)?, 2);
+        assert_eq!(cache.f64("IBM US Equity", "LAST_PRICE"), Some(101.5));
+        assert_eq!(cache.get::<i64>("IBM US Equity", "VOLUME")?, Some(1000));
+
+        // only SUBSCRIPTION_DATA events update the cache
+        let event = build_event(EventType::SubscriptionStatus, &ibm, r#"{ "LAST_PRICE": 99.0 }"#)?;
+        assert_eq!(cache.handle_event(&event)?, 0);
+
+        // unknown subscriptions are ignored
+        let event = build_event(EventType::SubscriptionData, &CorrelationId::new_int(2, None), r#"{ "LAST_PRICE": 99.0 }"#)?;
+        assert_eq!(cache.handle_event(&event)?, 0);
+
+        // fields missing from an update keep their value
+        let event = build_event(EventType::SubscriptionData, &ibm, r#"{ "LAST_PRICE": 102.0 }"#)?;
+        let message = event.messages().next().unwrap();
+        assert_eq!(cache.handle_message(&message)?, 1);
+        assert_eq!(cache.f64("IBM US Equity", "LAST_PRICE"), Some(102.));
+        assert_eq!(cache.get::<i64>("IBM US Equity", "VOLUME")?, Some(1000));
+        assert_eq!(cache.topics().collect::<Vec<_>>(), ["IBM US Equity"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_interned_fields() -> Result<(), Error> {
+        let ibm = CorrelationId::new_int(1, None);
+        let mut cache = MarketDataCache::new().with_interned_fields(Interner::new(), ["EXCH_CODE_LAST"]);
+        cache.register(ibm.clone(), "IBM US Equity");
+
+        let event = build_event(EventType::SubscriptionData, &ibm, r#"{ "LAST_PRICE": 101.5, "EXCH_CODE_LAST": "N" }"#)?;
+        assert_eq!(cache.handle_event(&event)?, 2);
+        let first = cache.string("IBM US Equity", "EXCH_CODE_LAST").unwrap();
+        assert_eq!(&*first, "N");
+        assert!(cache.value("IBM US Equity", "EXCH_CODE_LAST").is_none());
+
+        let event = build_event(EventType::SubscriptionData, &ibm, r#"{ "EXCH_CODE_LAST": "N" }"#)?;
+        assert_eq!(cache.handle_event(&event)?, 1);
+        let second = cache.string("IBM US Equity", "EXCH_CODE_LAST").unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let snapshot = cache.snapshot();
+        assert!(matches!(&snapshot["IBM US Equity"]["EXCH_CODE_LAST"], Value::String(value) if value == "N"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_register_list() -> Result<(), Error> {
+        let ibm = CorrelationId::new_int(1, None);
+        let mut list = SubscriptionList::new();
+        list.add("IBM US Equity", None, None, Some(ibm.clone()))?;
+        let topic = list.entries().next().unwrap().topic_string;
+
+        let mut cache = MarketDataCache::new();
+        cache.register_list(&list);
+        let event = build_event(EventType::SubscriptionData, &ibm, r#"{ "LAST_PRICE": 101.5 }"#)?;
+        assert_eq!(cache.handle_event(&event)?, 1);
+        assert_eq!(cache.f64(&topic, "LAST_PRICE"), Some(101.5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_accessors() -> Result<(), Error> {
+        let ibm = CorrelationId::new_int(1, None);
+        let mut cache = MarketDataCache::new();
+        cache.register(ibm.clone(), "IBM US Equity");
+        let json = r#"{ "LAST_PRICE": 101.5, "VOLUME": 1000, "MARKET_STATUS": "OPEN" }"#;
+        cache.handle_event(&build_event(EventType::SubscriptionData, &ibm, json)?)?;
+
+        assert_eq!(cache.f64("IBM US Equity", "LAST_PRICE"), Some(101.5));
+        assert_eq!(cache.f64("IBM US Equity", "VOLUME"), Some(1000.));
+        assert_eq!(cache.get::<String>("IBM US Equity", "MARKET_STATUS")?, Some("OPEN".to_string()));
+        assert_eq!(cache.string("IBM US Equity", "MARKET_STATUS").as_deref(), Some("OPEN"));
+        assert!(cache.get::<i32>("IBM US Equity", "MARKET_STATUS").is_err());
+        assert_eq!(cache.get::<f64>("IBM US Equity", "BID")?, None);
+        assert_eq!(cache.topic("IBM US Equity").unwrap().fields.len(), 3);
+        assert_eq!(cache.snapshot()["IBM US Equity"].len(), 3);
+
+        cache.remove("IBM US Equity");
+        assert_eq!(cache.topics().count(), 0);
+        // the topic is no longer registered either
+        cache.handle_event(&build_event(EventType::SubscriptionData, &ibm, json)?)?;
+        assert!(cache.topic("IBM US Equity").is_none());
+        Ok(())
+    }
+}
@@ -63,9 +63,11 @@ impl<S: AsRef<str>> PartialEq<S> for Name {
     }
 }
 
+/// Names are compared by string: names created for the same string are
+/// usually the same pointer, which is checked first, but not always.
 impl PartialEq<Name> for Name {
     fn eq(&self, other: &Name) -> bool {
-        self.0 == other.0
+        self.0 == other.0 || self.to_cstr() == other.to_cstr()
     }
 }
 
@@ -74,20 +76,24 @@ impl Eq for Name {
 
 impl PartialOrd<Name> for Name {
     fn partial_cmp(&self, other: &Name) -> Option<Ordering> {
-        self.0.partial_cmp(&other.0)
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for Name {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.0.cmp(&other.0)
+        if self.0 == other.0 {
+            Ordering::Equal
+        } else {
+            self.to_cstr().cmp(other.to_cstr())
+        }
     }
 }
 
 impl Hash for Name {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        let addr = self.0 as usize;
-        state.write_usize(addr);
+        // consistent with `PartialEq`: equal names may be distinct pointers
+        self.to_cstr().hash(state);
     }
 }
 
@@ -0,0 +1,102 @@
+//! Cache of frequently used `Name`s
+//!
+//! Creating a `Name` looks up the string in the global name table of
+//! blpapi. Names used for every message, e.g. field names, are better
+//! created once: either through a `NameCache`, or as a function of the
+//! `names!` macro which creates its `Name` on first use.
+//!
+//! ```ignore
+//! blpapi::names! {
+//!     pub fn vwap = "VWAP";
+//! }
+//!
+//! let price = message.element().get_named_element(vwap())?;
+//! let bid = message.element().get_named_element(names::bid())?;
+//! ```
+use crate::name::Name;
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+/// A thread-safe cache of `Name`s, by string
+#[derive(Debug, Default)]
+pub struct NameCache {
+    names: RwLock<HashMap<String, Name>>,
+}
+
+impl NameCache {
+    pub fn new() -> Self {
+        NameCache::default()
+    }
+
+    /// Get the `Name` of `name_string`, creating it on first use
+    pub fn get(&self, name_string: &str) -> Name {
+        let names = self.names.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(name) = names.get(name_string) {
+            return *name;
+        }
+        drop(names);
+        let mut names = self.names.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *names
+            .entry(name_string.to_string())
+            .or_insert_with(|| Name::new(name_string))
+    }
+
+    /// Number of cached names
+    pub fn len(&self) -> usize {
+        self.names.read().unwrap_or_else(|poisoned| poisoned.into_inner()).len()
+    }
+
+    /// Return true if no name is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Process wide cache, used by `Name::cached`
+static GLOBAL: OnceLock<NameCache> = OnceLock::new();
+
+impl Name {
+    /// Get the `Name` of `name_string` through the process wide cache
+    pub fn cached(name_string: &str) -> Name {
+        GLOBAL.get_or_init(NameCache::new).get(name_string)
+    }
+}
+
+/// Declare functions returning a `Name`, each created on first use, e.g.
+/// `names! { pub fn vwap = "VWAP"; }`
+#[macro_export]
+macro_rules! names {
+    ($($(#[$attr:meta])* $vis:vis fn $fn:ident = $name:expr;)*) => {
+        $(
+            $(#[$attr])*
+            $vis fn $fn() -> $crate::name::Name {
+                static NAME: ::std::sync::OnceLock<$crate::name::Name> = ::std::sync::OnceLock::new();
+                *NAME.get_or_init(|| $crate::name::Name::new($name))
+            }
+        )*
+    };
+}
+
+/// Common names
+pub mod names {
+    names! {
+        /// `securityData`
+        pub fn security_data = "securityData";
+        /// `security`
+        pub fn security = "security";
+        /// `fieldData`
+        pub fn field_data = "fieldData";
+        /// `fieldExceptions`
+        pub fn field_exceptions = "fieldExceptions";
+        /// `securityError`
+        pub fn security_error = "securityError";
+        /// `responseError`
+        pub fn response_error = "responseError";
+        /// `BID`
+        pub fn bid = "BID";
+        /// `ASK`
+        pub fn ask = "ASK";
+        /// `LAST_PRICE`
+        pub fn last_price = "LAST_PRICE";
+    }
+}
@@ -0,0 +1,168 @@
+//! Object pooling for high-rate subscriptions
+//!
+//! Decoding every tick into a fresh struct allocates its strings and
+//! vectors again and again. A `Pool` keeps the values once they are
+//! dropped, so the next tick reuses their allocations:
+//! `let mut tick = pool.get(); tick.read(&message)?; sender.send(tick)?;`
+//!
+//! Pooling is opt-in: decoded types implement `Recycle` to be reset before
+//! being handed out again.
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+
+/// Default maximum number of idle values kept by a pool
+const DEFAULT_MAX_IDLE: usize = 1024;
+
+/// A value which can be reset and reused
+pub trait Recycle {
+    /// Reset the value, keeping its allocations
+    fn recycle(&mut self);
+}
+
+impl Recycle for String {
+    fn recycle(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T> Recycle for Vec<T> {
+    fn recycle(&mut self) {
+        self.clear();
+    }
+}
+
+impl<K: Eq + Hash, V> Recycle for HashMap<K, V> {
+    fn recycle(&mut self) {
+        self.clear();
+    }
+}
+
+struct Shared<T> {
+    idle: Mutex<Vec<T>>,
+    max_idle: usize,
+}
+
+/// A pool of reusable values, cheap to clone and shareable across threads
+pub struct Pool<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Self {
+        Pool { shared: self.shared.clone() }
+    }
+}
+
+impl<T: Recycle + Default> Default for Pool<T> {
+    fn default() -> Self {
+        Pool::new()
+    }
+}
+
+impl<T: Recycle + Default> Pool<T> {
+    /// Create a pool keeping at most 1024 idle values
+    pub fn new() -> Self {
+        Pool::with_max_idle(DEFAULT_MAX_IDLE)
+    }
+
+    /// Create a pool keeping at most `max_idle` idle values, the values
+    /// returned beyond are dropped
+    pub fn with_max_idle(max_idle: usize) -> Self {
+        Pool {
+            shared: Arc::new(Shared {
+                idle: Mutex::new(Vec::new()),
+                max_idle,
+            }),
+        }
+    }
+
+    /// Take an idle value, or create one if there is none. The value goes
+    /// back to the pool when the returned guard is dropped.
+    pub fn get(&self) -> Pooled<T> {
+        let value = self.lock().pop().unwrap_or_default();
+        Pooled {
+            value: Some(value),
+            pool: self.clone(),
+        }
+    }
+
+    /// Number of idle values
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<T>> {
+        self.shared.idle.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn give_back(&self, mut value: T) {
+        value.recycle();
+        let mut idle = self.lock();
+        if idle.len() < self.shared.max_idle {
+            idle.push(value);
+        }
+    }
+}
+
+/// A value borrowed from a `Pool`
+pub struct Pooled<T: Recycle + Default> {
+    value: Option<T>,
+    pool: Pool<T>,
+}
+
+impl<T: Recycle + Default> Pooled<T> {
+    /// Keep the value, it will not go back to the pool
+    pub fn detach(mut self) -> T {
+        self.value.take().unwrap()
+    }
+}
+
+impl<T: Recycle + Default> Deref for Pooled<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+impl<T: Recycle + Default> DerefMut for Pooled<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+impl<T: Recycle + Default> Drop for Pooled<T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            self.pool.give_back(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuse() {
+        let pool: Pool<String> = Pool::with_max_idle(1);
+        let capacity = {
+            let mut value = pool.get();
+            value.push_str("IBM US Equity");
+            value.capacity()
+        };
+        assert_eq!(pool.idle(), 1);
+
+        let value = pool.get();
+        assert!(value.is_empty());
+        assert_eq!(value.capacity(), capacity);
+        assert_eq!(pool.idle(), 0);
+
+        let other = pool.get();
+        drop(value);
+        drop(other);
+        assert_eq!(pool.idle(), 1);
+    }
+}
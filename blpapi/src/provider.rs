@@ -0,0 +1,501 @@
+//! Provider sessions, used to publish data and serve requests
+use crate::{
+    correlation_id::CorrelationId,
+    event::Event,
+    eventdispatcher::EventDispatcher,
+    identity::Identity,
+    message::Message,
+    resolutionlist::ResolutionList,
+    service::Service,
+    session_options::SessionOptions,
+    topic::Topic,
+    topiclist::TopicList,
+    Error,
+};
+use blpapi_sys::*;
+use std::ffi::CString;
+use std::ops::BitOr;
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::ptr;
+
+type EventHandlerFn<'a> = dyn FnMut(&Event) -> () + 'a + Send;
+type ProviderEventHandlerCallback = unsafe extern "C" fn(*mut blpapi_Event_t, *mut blpapi_ProviderSession_t, *mut c_void);
+
+unsafe extern "C" fn provider_event_handler_callback(event: *mut blpapi_Event_t, _: *mut blpapi_ProviderSession_t, user_data: *mut c_void) {
+    let event_handler: &mut Box<EventHandlerFn> = std::mem::transmute(user_data);
+    let event = Event(event);
+    #[cfg(feature = "tracing")]
+    crate::spans::record(&event);
+    if let Err(err) = catch_unwind(AssertUnwindSafe(move || (*event_handler)(&event))) {
+        eprintln!("{:?}", err);
+        std::process::abort();
+    }
+}
+
+/// Whether services needed to resolve or create topics are registered
+/// automatically
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolveMode {
+    /// Fail if the service of a topic is not registered yet
+    DontRegisterServices,
+    /// Register the service of a topic if needed
+    AutoRegisterServices,
+}
+
+impl ResolveMode {
+    fn as_c_int(&self) -> c_int {
+        match self {
+            ResolveMode::DontRegisterServices => BLPAPI_RESOLVEMODE_DONT_REGISTER_SERVICES as c_int,
+            ResolveMode::AutoRegisterServices => BLPAPI_RESOLVEMODE_AUTO_REGISTER_SERVICES as c_int,
+        }
+    }
+}
+
+/// The parts of a service to register, can be combined with `|`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegistrationParts(c_int);
+
+impl RegistrationParts {
+    pub const DEFAULT: RegistrationParts = RegistrationParts(BLPAPI_REGISTRATIONPARTS_DEFAULT as c_int);
+    pub const PUBLISHING: RegistrationParts = RegistrationParts(BLPAPI_REGISTRATIONPARTS_PUBLISHING as c_int);
+    pub const OPERATIONS: RegistrationParts = RegistrationParts(BLPAPI_REGISTRATIONPARTS_OPERATIONS as c_int);
+    pub const SUBSCRIBER_RESOLUTION: RegistrationParts = RegistrationParts(BLPAPI_REGISTRATIONPARTS_SUBSCRIBER_RESOLUTION as c_int);
+    pub const PUBLISHER_RESOLUTION: RegistrationParts = RegistrationParts(BLPAPI_REGISTRATIONPARTS_PUBLISHER_RESOLUTION as c_int);
+
+    /// Return true if all parts of `other` are set
+    pub fn contains(&self, other: RegistrationParts) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for RegistrationParts {
+    type Output = RegistrationParts;
+
+    fn bitor(self, rhs: RegistrationParts) -> RegistrationParts {
+        RegistrationParts(self.0 | rhs.0)
+    }
+}
+
+/// Options used when registering a service
+///
+/// Behaves like a builder
+pub struct ServiceRegistrationOptions(pub(crate) *mut blpapi_ServiceRegistrationOptions_t);
+
+impl ServiceRegistrationOptions {
+    /// Get the group id
+    pub fn group_id(&self) -> String {
+        let mut buffer = [0 as c_char; BLPAPI_MAX_GROUP_ID_SIZE as usize];
+        let mut len = buffer.len() as c_int;
+        unsafe { blpapi_ServiceRegistrationOptions_getGroupId(self.0, buffer.as_mut_ptr(), &mut len) };
+        let bytes: Vec<u8> = buffer[..len.max(0) as usize].iter().map(|c| *c as u8).collect();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// Set the group id, used to identify a group of publishers of the
+    /// same service
+    pub fn with_group_id(self, group_id: &str) -> Self {
+        unsafe {
+            blpapi_ServiceRegistrationOptions_setGroupId(
+                self.0,
+                group_id.as_ptr() as *const c_char,
+                group_id.len() as c_uint,
+            )
+        };
+        self
+    }
+
+    /// Get the service priority
+    pub fn service_priority(&self) -> i32 {
+        unsafe { blpapi_ServiceRegistrationOptions_getServicePriority(self.0) }
+    }
+
+    /// Set the service priority, a non-negative value where higher
+    /// priorities are preferred when routing requests
+    pub fn with_service_priority(self, priority: i32) -> Result<Self, Error> {
+        let res = unsafe { blpapi_ServiceRegistrationOptions_setServicePriority(self.0, priority) };
+        Error::check(res)?;
+        Ok(self)
+    }
+
+    /// Get the parts of the service to register
+    pub fn parts_to_register(&self) -> RegistrationParts {
+        RegistrationParts(unsafe { blpapi_ServiceRegistrationOptions_getPartsToRegister(self.0) })
+    }
+
+    /// Set the parts of the service to register
+    pub fn with_parts_to_register(self, parts: RegistrationParts) -> Self {
+        unsafe { blpapi_ServiceRegistrationOptions_setPartsToRegister(self.0, parts.0) };
+        self
+    }
+}
+
+impl Default for ServiceRegistrationOptions {
+    fn default() -> Self {
+        let ptr = unsafe { blpapi_ServiceRegistrationOptions_create() };
+        ServiceRegistrationOptions(ptr)
+    }
+}
+
+impl Clone for ServiceRegistrationOptions {
+    fn clone(&self) -> Self {
+        ServiceRegistrationOptions(unsafe { blpapi_ServiceRegistrationOptions_duplicate(self.0) })
+    }
+}
+
+impl Drop for ServiceRegistrationOptions {
+    fn drop(&mut self) {
+        unsafe { blpapi_ServiceRegistrationOptions_destroy(self.0) }
+    }
+}
+
+unsafe impl Send for ServiceRegistrationOptions {}
+unsafe impl Sync for ServiceRegistrationOptions {}
+
+/// This class provides a session that can be used for providing services.
+///
+/// It inherits from AbstractSession. In addition to the AbstractSession
+/// functionality a ProviderSession provides the following functions to
+/// applications.
+///
+/// A provider can register to provide services using
+/// 'ProviderSession::registerService*'. Before registering to provide a
+/// service the provider must have established its identity. Then the
+/// provider can create topics and publish events on topics. It also can
+/// get requests from the event queue and send back responses.
+///
+/// After users have registered a service they will start receiving
+/// subscription requests ('TopicSubscribed' message in 'TOPIC_STATUS')
+/// for topics which belong to the service. If the resolver has specified
+/// 'subServiceCode' for topics in 'PermissionResponse', then only
+/// providers who have activated the 'subServiceCode' will get the
+/// subscription request. Where multiple providers have registered the
+/// same service and sub-service code (if any), the provider that
+/// registered the highest priority for the sub-service code will
+/// receive subscription requests; if multiple providers have registered
+/// the same sub-service code with the same priority (or the resolver
+/// did not set a sub-service code for the subscription), the
+/// subscription request will be routed to one of the providers with the
+/// highest service priority.
+pub struct ProviderSession<'a> {
+    pub(crate) ptr: *mut blpapi_ProviderSession_t,
+    event_handler_fn: Option<Box<EventHandlerFn<'a>>>,
+}
+
+impl<'a> ProviderSession<'a> {
+    /// Construct a ProviderSession using the specified 'options', the
+    /// optionally specified 'event_handler' and the optionally specified
+    /// 'event_dispatcher'.
+    ///
+    /// If 'event_handler' is not None then this ProviderSession will
+    /// operate in asynchronous mode, otherwise it will operate in
+    /// synchronous mode and 'next_event' must be called to read incoming
+    /// events.
+    pub fn create(options: SessionOptions, event_handler: Option<impl FnMut(&Event) -> () + Send + 'a>, event_dispatcher: Option<&EventDispatcher>) -> Pin<Box<Self>> {
+        let mut session = Box::pin(ProviderSession {
+            ptr: ptr::null_mut(),
+            event_handler_fn: event_handler.map(|event_handler_fn| Box::new(event_handler_fn) as _)
+        });
+        session.ptr = unsafe {
+            match session.event_handler_fn.as_ref() {
+                Some(callback_user_data_ref) => {
+                    blpapi_ProviderSession_create(
+                        options.0,
+                        Some(provider_event_handler_callback as ProviderEventHandlerCallback),
+                        event_dispatcher.map_or(ptr::null_mut(), |event_dispatcher| event_dispatcher.0),
+                        std::mem::transmute(callback_user_data_ref)
+                    )
+                },
+                None => {
+                    blpapi_ProviderSession_create(
+                        options.0,
+                        None,
+                        ptr::null_mut(),
+                        ptr::null_mut(),
+                    )
+                }
+            }
+        };
+
+        session
+    }
+
+    /// Attempt to start this session and block until the session has
+    /// started or failed to start.
+    pub fn start(&mut self) -> Result<(), Error> {
+        let res = unsafe { blpapi_ProviderSession_start(self.ptr) };
+        Error::check(res)
+    }
+
+    /// Begin the process to start this session and return immediately.
+    /// The application must monitor events for a SESSION_STATUS Event
+    /// which will be generated once the session has started or failed to
+    /// start.
+    pub fn start_async(&mut self) -> Result<(), Error> {
+        let res = unsafe { blpapi_ProviderSession_startAsync(self.ptr) };
+        Error::check(res)
+    }
+
+    /// Stop operation of this session and block until all callbacks
+    /// relating to this session which are currently in progress have
+    /// completed.
+    pub fn stop(&mut self) -> Result<(), Error> {
+        let res = unsafe { blpapi_ProviderSession_stop(self.ptr) };
+        Error::check(res)
+    }
+
+    /// Begin the process to stop this session and return immediately.
+    pub fn stop_async(&mut self) -> Result<(), Error> {
+        let res = unsafe { blpapi_ProviderSession_stopAsync(self.ptr) };
+        Error::check(res)
+    }
+
+    /// Return the next available Event for this session, waiting at most
+    /// 'timeout' milliseconds (forever if None). Only valid for
+    /// synchronous sessions.
+    pub fn next_event(&mut self, timeout: Option<u32>) -> Result<Event, Error> {
+        let mut event = ptr::null_mut();
+        let res = unsafe { blpapi_ProviderSession_nextEvent(self.ptr, &mut event, timeout.unwrap_or(0)) };
+        Error::check(res)?;
+
+        let event = Event(event);
+        #[cfg(feature = "tracing")]
+        crate::spans::record(&event);
+        Ok(event)
+    }
+
+    /// Return the service identified by 'service_identifier' if it is
+    /// open or registered.
+    pub fn get_service(&self, service_identifier: &str) -> Result<Option<Service>, Error> {
+        let name = CString::new(service_identifier).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let mut service: *mut blpapi_Service_t = ptr::null_mut();
+        let res = unsafe {
+            let session = blpapi_ProviderSession_getAbstractSession(self.ptr);
+            blpapi_AbstractSession_getService(session, &mut service, name.as_ptr())
+        };
+        Error::check(res)?;
+
+        let result = if service.is_null() { None } else { Some(Service(service)) };
+        Ok(result)
+    }
+
+    /// Attempt to register the service identified by 'service_name' and
+    /// block until the service is either registered successfully or has
+    /// failed to be registered. The optionally specified 'identity' is
+    /// used to verify permissions to provide the service being
+    /// registered.
+    ///
+    /// The 'service_name' must be a full qualified service name. That is
+    /// it must be of the form '//<namespace>/<local-name>'.
+    pub fn register_service(
+        &mut self,
+        service_name: &str,
+        identity: Option<&Identity>,
+        options: Option<&ServiceRegistrationOptions>,
+    ) -> Result<(), Error> {
+        let service_name = CString::new(service_name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let identity = identity.map_or(ptr::null(), |identity| identity.0 as *const _);
+        let default_options;
+        let options = match options {
+            Some(options) => options,
+            None => {
+                default_options = ServiceRegistrationOptions::default();
+                &default_options
+            }
+        };
+        let res = unsafe { blpapi_ProviderSession_registerService(self.ptr, service_name.as_ptr(), identity, options.0) };
+        Error::check(res)
+    }
+
+    /// Begin the process of registering the service identified by
+    /// 'service_name' and return immediately. The application must
+    /// monitor events for a SERVICE_STATUS Event which will be generated
+    /// once the service has been successfully registered or registration
+    /// has failed.
+    pub fn register_service_async(
+        &mut self,
+        service_name: &str,
+        identity: Option<&Identity>,
+        correlation_id: Option<CorrelationId>,
+        options: Option<&ServiceRegistrationOptions>,
+    ) -> Result<CorrelationId, Error> {
+        let service_name = CString::new(service_name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let identity = identity.map_or(ptr::null(), |identity| identity.0 as *const _);
+        let mut correlation_id = correlation_id.unwrap_or_else(|| CorrelationId::new_empty());
+        let default_options;
+        let options = match options {
+            Some(options) => options,
+            None => {
+                default_options = ServiceRegistrationOptions::default();
+                &default_options
+            }
+        };
+        let res = unsafe {
+            blpapi_ProviderSession_registerServiceAsync(
+                self.ptr,
+                service_name.as_ptr(),
+                identity,
+                &mut correlation_id.0,
+                options.0,
+            )
+        };
+        Error::check(res)?;
+
+        #[cfg(feature = "tracing")]
+        crate::spans::open("register_service", &correlation_id, &service_name.to_string_lossy());
+
+        Ok(correlation_id)
+    }
+
+    /// Deregister the service identified by 'service_name'. All topics
+    /// of the service are deleted and the service stops receiving
+    /// subscriptions and requests.
+    pub fn deregister_service(&mut self, service_name: &str) -> Result<(), Error> {
+        let service_name = CString::new(service_name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let res = unsafe { blpapi_ProviderSession_deregisterService(self.ptr, service_name.as_ptr()) };
+        Error::check(res)
+    }
+
+    /// Resolve the topics in the specified 'resolution_list' and update
+    /// it with the results, blocking until all topics are resolved.
+    pub fn resolve(
+        &mut self,
+        resolution_list: &mut ResolutionList,
+        resolve_mode: ResolveMode,
+        identity: Option<&Identity>,
+    ) -> Result<(), Error> {
+        let identity = identity.map_or(ptr::null(), |identity| identity.0 as *const _);
+        let res = unsafe { blpapi_ProviderSession_resolve(self.ptr, resolution_list.0, resolve_mode.as_c_int(), identity) };
+        Error::check(res)
+    }
+
+    /// Begin the resolution of the topics in the specified
+    /// 'resolution_list' and return immediately. RESOLUTION_STATUS
+    /// events are generated for each topic.
+    pub fn resolve_async(
+        &mut self,
+        resolution_list: &ResolutionList,
+        resolve_mode: ResolveMode,
+        identity: Option<&Identity>,
+    ) -> Result<(), Error> {
+        let identity = identity.map_or(ptr::null(), |identity| identity.0 as *const _);
+        let res = unsafe { blpapi_ProviderSession_resolveAsync(self.ptr, resolution_list.0, resolve_mode.as_c_int(), identity) };
+        Error::check(res)
+    }
+
+    /// Create the topics in the specified 'topic_list' and update it
+    /// with the results, blocking until all topics are created.
+    pub fn create_topics(
+        &mut self,
+        topic_list: &mut TopicList,
+        resolve_mode: ResolveMode,
+        identity: Option<&Identity>,
+    ) -> Result<(), Error> {
+        let identity = identity.map_or(ptr::null(), |identity| identity.0 as *const _);
+        let res = unsafe { blpapi_ProviderSession_createTopics(self.ptr, topic_list.0, resolve_mode.as_c_int(), identity) };
+        Error::check(res)
+    }
+
+    /// Begin the creation of the topics in the specified 'topic_list' and
+    /// return immediately. TOPIC_STATUS events are generated for each
+    /// topic.
+    pub fn create_topics_async(
+        &mut self,
+        topic_list: &TopicList,
+        resolve_mode: ResolveMode,
+        identity: Option<&Identity>,
+    ) -> Result<(), Error> {
+        let identity = identity.map_or(ptr::null(), |identity| identity.0 as *const _);
+        let res = unsafe { blpapi_ProviderSession_createTopicsAsync(self.ptr, topic_list.0, resolve_mode.as_c_int(), identity) };
+        Error::check(res)
+    }
+
+    /// Return the topic object associated with the specified 'message',
+    /// which must be a TOPIC_STATUS, RESOLUTION_STATUS or request message.
+    pub fn get_topic(&self, message: &Message) -> Result<Topic, Error> {
+        let mut topic = ptr::null_mut();
+        let res = unsafe { blpapi_ProviderSession_getTopic(self.ptr, message.0, &mut topic) };
+        Error::check(res)?;
+        Ok(Topic(topic))
+    }
+
+    /// Create a topic used to publish the status of the specified
+    /// 'service'.
+    pub fn create_service_status_topic(&self, service: &Service) -> Result<Topic, Error> {
+        let mut topic = ptr::null_mut();
+        let res = unsafe { blpapi_ProviderSession_createServiceStatusTopic(self.ptr, service.0, &mut topic) };
+        Error::check(res)?;
+        Ok(Topic(topic))
+    }
+
+    /// Remove the specified 'topics' from the service they belong to,
+    /// terminating the subscriptions on them.
+    pub fn delete_topics(&mut self, topics: &[Topic]) -> Result<(), Error> {
+        let mut topics: Vec<*const blpapi_Topic_t> = topics.iter().map(|topic| topic.0 as *const _).collect();
+        let res = unsafe { blpapi_ProviderSession_deleteTopics(self.ptr, topics.as_mut_ptr(), topics.len()) };
+        Error::check(res)
+    }
+
+    /// Publish the specified 'event', created with
+    /// `Service::create_publish_event` or `Service::create_admin_event`.
+    pub fn publish(&mut self, event: &Event) -> Result<(), Error> {
+        let res = unsafe { blpapi_ProviderSession_publish(self.ptr, event.0) };
+        Error::check(res)
+    }
+
+    /// Send the response (or partial response if 'is_partial_response')
+    /// contained in the specified 'event', created with
+    /// `Service::create_response_event`.
+    pub fn send_response(&mut self, event: &Event, is_partial_response: bool) -> Result<(), Error> {
+        let res = unsafe { blpapi_ProviderSession_sendResponse(self.ptr, event.0, is_partial_response as c_int) };
+        Error::check(res)
+    }
+
+    /// Wait at most 'timeout' milliseconds for all published events to
+    /// be sent, returning true if they all were.
+    pub fn flush_published_events(&mut self, timeout: i32) -> Result<bool, Error> {
+        let mut all_flushed: c_int = 0;
+        let res = unsafe { blpapi_ProviderSession_flushPublishedEvents(self.ptr, &mut all_flushed, timeout) };
+        Error::check(res)?;
+        Ok(all_flushed != 0)
+    }
+}
+
+impl Drop for ProviderSession<'_> {
+    fn drop(&mut self) {
+        unsafe { blpapi_ProviderSession_destroy(self.ptr) }
+    }
+}
+
+unsafe impl Send for ProviderSession<'_> {}
+unsafe impl Sync for ProviderSession<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registration_parts() {
+        let parts = RegistrationParts::PUBLISHING | RegistrationParts::OPERATIONS;
+        assert!(parts.contains(RegistrationParts::PUBLISHING));
+        assert!(parts.contains(RegistrationParts::OPERATIONS));
+        assert!(!parts.contains(RegistrationParts::SUBSCRIBER_RESOLUTION));
+        assert!(!RegistrationParts::PUBLISHING.contains(parts));
+    }
+
+    #[test]
+    fn test_service_registration_options() -> Result<(), Error> {
+        let options = ServiceRegistrationOptions::default()
+            .with_group_id("group")
+            .with_service_priority(5)?
+            .with_parts_to_register(RegistrationParts::PUBLISHING);
+        assert!(options.clone().with_service_priority(-1).is_err());
+
+        let copy = options.clone();
+        assert_eq!(copy.group_id(), "group");
+        assert_eq!(copy.service_priority(), 5);
+        assert_eq!(copy.parts_to_register(), RegistrationParts::PUBLISHING);
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+//! Draining a session before shutting it down
+//!
+//! `Session::quiesce` is meant for rolling restarts: the session stops
+//! accepting new requests and subscriptions, waits for the responses of
+//! the outstanding requests, and unsubscribes everything so no
+//! subscription is left behind on the server side.
+//!
+//! Only the requests whose responses are delivered to the event handler of
+//! an asynchronous session are tracked; requests sent to a dedicated
+//! `EventQueue`, or through a synchronous session, are not waited for.
+use crate::{
+    correlation_id::CorrelationId,
+    event::{Event, EventType},
+    session::{Session, SubscriptionStatus},
+    subscriptionlist::SubscriptionList,
+    Error,
+};
+use std::collections::HashSet;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Instant;
+
+/// Correlation ids of the requests still waiting for their final response,
+/// with the condition notified once they all completed
+#[derive(Clone, Default)]
+pub(crate) struct Outstanding(Arc<(Mutex<HashSet<CorrelationId>>, Condvar)>);
+
+impl Outstanding {
+    pub(crate) fn lock(&self) -> MutexGuard<'_, HashSet<CorrelationId>> {
+        // a panicking handler aborts the process, the lock cannot be poisoned
+        (self.0).0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Forget the requests completed by `event`
+    pub(crate) fn complete(&self, event: &Event) {
+        match event.event_type() {
+            EventType::Response | EventType::RequestStatus => {}
+            _ => return,
+        }
+        let mut outstanding = self.lock();
+        if outstanding.is_empty() {
+            return;
+        }
+        for message in event.messages() {
+            if message.num_correlation_ids() > 0 {
+                if let Some(correlation_id) = message.correlation_id(0) {
+                    outstanding.remove(&correlation_id);
+                }
+            }
+        }
+        if outstanding.is_empty() {
+            (self.0).1.notify_all();
+        }
+    }
+
+    /// Block until all the requests completed or `deadline` is reached
+    fn wait(&self, deadline: Instant) {
+        let mut outstanding = self.lock();
+        while !outstanding.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                return;
+            }
+            outstanding = match (self.0).1.wait_timeout(outstanding, deadline - now) {
+                Ok((outstanding, _)) => outstanding,
+                Err(err) => err.into_inner().0,
+            };
+        }
+    }
+
+    fn pending(&self) -> Vec<CorrelationId> {
+        self.lock().iter().cloned().collect()
+    }
+}
+
+impl<'a> Session<'a> {
+    /// Unsubscribe every subscription of this session which is still
+    /// active or being established
+    pub fn unsubscribe_all(&mut self) -> Result<(), Error> {
+        let mut subscription_list = SubscriptionList::new();
+        let mut empty = true;
+        for (topic, correlation_id, status) in self.subscriptions() {
+            match status {
+                SubscriptionStatus::Subscribing | SubscriptionStatus::Subscribed => {
+                    subscription_list.add(&topic, None, None, Some(correlation_id))?;
+                    empty = false;
+                }
+                _ => {}
+            }
+        }
+        if empty {
+            return Ok(());
+        }
+        self.unsubscribe(&subscription_list)
+    }
+
+    /// Whether `quiesce` was called, new requests are rejected then
+    pub fn is_quiescing(&self) -> bool {
+        self.quiescing
+    }
+
+    /// Prepare this session to be stopped: reject any new request or
+    /// subscription with `Error::Quiescing`, wait until `deadline` for the
+    /// responses of the outstanding requests, then unsubscribe everything.
+    ///
+    /// The requests still outstanding at `deadline` are cancelled. Return
+    /// 'true' if all of them completed in time.
+    pub fn quiesce(&mut self, deadline: Instant) -> Result<bool, Error> {
+        self.quiescing = true;
+
+        self.outstanding.wait(deadline);
+        let pending = self.outstanding.pending();
+        if !pending.is_empty() {
+            self.cancel(&pending, Some("quiesce"))?;
+            self.outstanding.lock().clear();
+        }
+
+        self.unsubscribe_all()?;
+        Ok(pending.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_wait() {
+        let outstanding = Outstanding::default();
+        let start = Instant::now();
+        outstanding.wait(start + Duration::from_secs(10));
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        outstanding.lock().insert(CorrelationId::new_int(1, None));
+        outstanding.wait(Instant::now() + Duration::from_millis(10));
+        assert_eq!(outstanding.pending().len(), 1);
+
+        let completer = outstanding.clone();
+        let thread = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            let mut outstanding = completer.lock();
+            outstanding.clear();
+            (completer.0).1.notify_all();
+        });
+        let start = Instant::now();
+        outstanding.wait(start + Duration::from_secs(10));
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert!(outstanding.pending().is_empty());
+        thread.join().unwrap();
+    }
+}
@@ -0,0 +1,348 @@
+//! Recording of received events
+//!
+//! Messages are snapshotted into owned `Record`s which can be written to any
+//! `RecordSink`. Two storage formats are supported: JSON Lines (one record per
+//! line, meant for human inspection) and bincode (compact archives). Both
+//! sinks and readers work on plain `std::io` streams, so compression (e.g. a
+//! zstd encoder/decoder) can be layered on top by the caller.
+use crate::correlation_id::CorrelationType;
+use crate::event::{Event, EventType};
+use crate::message::Message;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Read, Write};
+use std::str::FromStr;
+
+/// An owned snapshot of a received message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    /// Type of the event the message was part of
+    pub event_type: EventType,
+    /// Message type
+    pub message_type: String,
+    /// Topic name (empty for non subscription messages)
+    pub topic_name: String,
+    /// Integer correlation ids of the message. Other kinds of correlation
+    /// ids cannot be meaningfully persisted and are skipped.
+    pub correlation_ids: Vec<u64>,
+    /// Message content, as printed by blpapi
+    pub payload: String,
+}
+
+impl Record {
+    /// Take a snapshot of the given message
+    pub fn from_message(event_type: EventType, message: &Message) -> Self {
+        let correlation_ids = message
+            .correlation_ids()
+            .filter(|id| id.value_type() == CorrelationType::Int)
+            .map(|id| unsafe { id.0.value.intValue })
+            .collect();
+        Record {
+            event_type,
+            message_type: message.type_string(),
+            topic_name: message.topic_name(),
+            correlation_ids,
+            payload: message.to_string(),
+        }
+    }
+}
+
+/// Error raised while writing or reading records
+#[derive(Debug)]
+pub enum RecordError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Bincode(bincode::Error),
+    /// Unknown storage format name
+    UnknownFormat(String),
+}
+
+impl std::fmt::Display for RecordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+impl From<std::io::Error> for RecordError {
+    fn from(e: std::io::Error) -> Self {
+        RecordError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for RecordError {
+    fn from(e: serde_json::Error) -> Self {
+        RecordError::Json(e)
+    }
+}
+
+impl From<bincode::Error> for RecordError {
+    fn from(e: bincode::Error) -> Self {
+        RecordError::Bincode(e)
+    }
+}
+
+/// A destination for records
+pub trait RecordSink {
+    /// Write a single record
+    fn write_record(&mut self, record: &Record) -> Result<(), RecordError>;
+
+    /// Flush any buffered record
+    fn flush(&mut self) -> Result<(), RecordError> {
+        Ok(())
+    }
+
+    /// Write a record for every message of the event
+    fn record_event(&mut self, event: &Event) -> Result<(), RecordError> {
+        let event_type = event.event_type();
+        for message in event.messages() {
+            self.write_record(&Record::from_message(event_type, &message))?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: RecordSink + ?Sized> RecordSink for Box<S> {
+    fn write_record(&mut self, record: &Record) -> Result<(), RecordError> {
+        (**self).write_record(record)
+    }
+
+    fn flush(&mut self) -> Result<(), RecordError> {
+        (**self).flush()
+    }
+}
+
+/// Writes records as JSON Lines
+pub struct JsonLinesSink<W: Write>(W);
+
+impl<W: Write> JsonLinesSink<W> {
+    pub fn new(writer: W) -> Self {
+        JsonLinesSink(writer)
+    }
+
+    /// Get back the underlying writer
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+impl<W: Write> RecordSink for JsonLinesSink<W> {
+    fn write_record(&mut self, record: &Record) -> Result<(), RecordError> {
+        serde_json::to_writer(&mut self.0, record)?;
+        self.0.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), RecordError> {
+        Ok(self.0.flush()?)
+    }
+}
+
+/// Writes records as a stream of bincode encoded values
+pub struct BincodeSink<W: Write>(W);
+
+impl<W: Write> BincodeSink<W> {
+    pub fn new(writer: W) -> Self {
+        BincodeSink(writer)
+    }
+
+    /// Get back the underlying writer
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+impl<W: Write> RecordSink for BincodeSink<W> {
+    fn write_record(&mut self, record: &Record) -> Result<(), RecordError> {
+        Ok(bincode::serialize_into(&mut self.0, record)?)
+    }
+
+    fn flush(&mut self) -> Result<(), RecordError> {
+        Ok(self.0.flush()?)
+    }
+}
+
+/// Reads records written by a `JsonLinesSink`
+pub struct JsonLinesReader<R: BufRead>(std::io::Lines<R>);
+
+impl<R: BufRead> JsonLinesReader<R> {
+    pub fn new(reader: R) -> Self {
+        JsonLinesReader(reader.lines())
+    }
+}
+
+impl<R: BufRead> Iterator for JsonLinesReader<R> {
+    type Item = Result<Record, RecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.0.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e.into())),
+            };
+            if !line.trim().is_empty() {
+                return Some(serde_json::from_str(&line).map_err(RecordError::from));
+            }
+        }
+    }
+}
+
+/// Reads records written by a `BincodeSink`
+pub struct BincodeReader<R: Read>(R);
+
+impl<R: Read> BincodeReader<R> {
+    pub fn new(reader: R) -> Self {
+        BincodeReader(reader)
+    }
+}
+
+impl<R: Read> Iterator for BincodeReader<R> {
+    type Item = Result<Record, RecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match bincode::deserialize_from(&mut self.0) {
+            Ok(record) => Some(Ok(record)),
+            Err(e) => match *e {
+                bincode::ErrorKind::Io(ref io) if io.kind() == std::io::ErrorKind::UnexpectedEof => None,
+                _ => Some(Err(e.into())),
+            },
+        }
+    }
+}
+
+/// Storage format of a record stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    JsonLines,
+    Bincode,
+}
+
+impl FromStr for Format {
+    type Err = RecordError;
+
+    /// Parse a format name, as typically given on a command line
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "jsonl" | "json-lines" | "ndjson" => Ok(Format::JsonLines),
+            "bincode" | "bin" => Ok(Format::Bincode),
+            _ => Err(RecordError::UnknownFormat(s.to_string())),
+        }
+    }
+}
+
+impl Format {
+    /// Create a sink writing records in this format
+    pub fn sink<'a, W: Write + 'a>(self, writer: W) -> Box<dyn RecordSink + 'a> {
+        match self {
+            Format::JsonLines => Box::new(JsonLinesSink::new(writer)),
+            Format::Bincode => Box::new(BincodeSink::new(writer)),
+        }
+    }
+
+    /// Create a reader of records stored in this format
+    pub fn reader<'a, R: BufRead + 'a>(self, reader: R) -> Box<dyn Iterator<Item = Result<Record, RecordError>> + 'a> {
+        match self {
+            Format::JsonLines => Box::new(JsonLinesReader::new(reader)),
+            Format::Bincode => Box::new(BincodeReader::new(reader)),
+        }
+    }
+}
+
+/// Copy all records from `records` to `sink`, returning the number of
+/// records written
+pub fn convert<I, S>(records: I, sink: &mut S) -> Result<usize, RecordError>
+where
+    I: IntoIterator<Item = Result<Record, RecordError>>,
+    S: RecordSink + ?Sized,
+{
+    let mut count = 0;
+    for record in records {
+        sink.write_record(&record?)?;
+        count += 1;
+    }
+    sink.flush()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correlation_id::CorrelationId;
+    use crate::name::Name;
+    use crate::testutil::{EventBuilder, MessageProperties};
+    use std::sync::Arc;
+
+    #[test]
+    fn convert_between_formats() {
+        let records = vec![
+            Record {
+                event_type: EventType::SubscriptionData,
+                message_type: "MarketDataEvents".to_string(),
+                topic_name: "IBM US Equity".to_string(),
+                correlation_ids: vec![1],
+                payload: "MarketDataEvents = {\n    LAST_PRICE = 1.0\n}\n".to_string(),
+            },
+            Record {
+                event_type: EventType::Response,
+                message_type: "ReferenceDataResponse".to_string(),
+                topic_name: String::new(),
+                correlation_ids: vec![],
+                payload: String::new(),
+            },
+        ];
+
+        let mut json = JsonLinesSink::new(Vec::new());
+        convert(records.clone().into_iter().map(Ok), &mut json).unwrap();
+        let json = json.into_inner();
+
+        let mut bin = BincodeSink::new(Vec::new());
+        assert_eq!(convert(Format::JsonLines.reader(&json[..]), &mut bin).unwrap(), 2);
+        let bin = bin.into_inner();
+
+        let read = Format::Bincode.reader(&bin[..]).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(read, records);
+    }
+
+    #[test]
+    fn test_record_event() -> Result<(), Box<dyn std::error::Error>> {
+        // pointer correlation ids cannot be persisted
+        let ids = [CorrelationId::new_int(1, None), CorrelationId::new_ptr(Arc::new(2), None)];
+        let properties = MessageProperties::new()?.with_correlation_ids(&ids)?;
+        let event = EventBuilder::new(EventType::SubscriptionStatus)?
+            .append_message_from_json(Name::new("SubscriptionStarted"), Some(properties), "{}")?
+            .append_message_from_json(Name::new("SubscriptionStarted"), None, "{}")?
+            .build();
+
+        let mut sink = JsonLinesSink::new(Vec::new());
+        sink.record_event(&event)?;
+        let json = sink.into_inner();
+        let records = JsonLinesReader::new(&json[..]).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].event_type, EventType::SubscriptionStatus);
+        assert_eq!(records[0].message_type, "SubscriptionStarted");
+        assert_eq!(records[0].correlation_ids, vec![1]);
+        assert!(records[1].correlation_ids.is_empty());
+
+        let message = event.messages().next().unwrap();
+        assert_eq!(records[0], Record::from_message(EventType::SubscriptionStatus, &message));
+        assert_eq!(records[0].payload, message.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn format_names() {
+        assert_eq!("jsonl".parse::<Format>().unwrap(), Format::JsonLines);
+        assert_eq!("ndjson".parse::<Format>().unwrap(), Format::JsonLines);
+        assert_eq!("bin".parse::<Format>().unwrap(), Format::Bincode);
+        assert!(matches!("csv".parse::<Format>(), Err(RecordError::UnknownFormat(name)) if name == "csv"));
+    }
+
+    #[test]
+    fn skip_blank_lines() {
+        let json = "\n{\"event_type\":\"Response\",\"message_type\":\"M\",\"topic_name\":\"\",\"correlation_ids\":[],\"payload\":\"\"}\n\n";
+        let records = JsonLinesReader::new(json.as_bytes()).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].message_type, "M");
+        assert!(JsonLinesReader::new("not json\n".as_bytes()).next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! Reference data (`//blp/refdata` `ReferenceDataRequest`, a.k.a. BDP)
+use crate::{
+    element::Element,
+    errors::ErrorInfo,
+    event::{Event, EventQueue, EventType},
+    identity::Identity,
+    message::Message,
+    request::Request,
+    service::Service,
+    session::Session,
+    Error,
+};
+use std::collections::HashMap;
+
+/// Reference data service name
+pub const REFDATA_SERVICE: &str = "//blp/refdata";
+
+/// A type which can be filled from the `fieldData` of a reference data response
+pub trait RefData: Default {
+    /// Bloomberg fields to request
+    const FIELDS: &'static [&'static str];
+
+    /// Called for every field returned for a security
+    fn on_field(&mut self, field: &str, element: &Element) -> Result<(), Error>;
+}
+
+/// A field which could not be retrieved for a security
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldException {
+    pub field_id: String,
+    pub error_info: ErrorInfo,
+}
+
+/// The reference data of a single security
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SecurityData<T> {
+    /// Field values
+    pub data: T,
+    /// Fields which could not be retrieved
+    pub field_exceptions: Vec<FieldException>,
+    /// Set if the whole security could not be retrieved
+    pub security_error: Option<ErrorInfo>,
+    /// Entitlement ids of the returned data, only filled by
+    /// `ref_data_with_eids`
+    pub eids: Vec<i32>,
+}
+
+impl<T> SecurityData<T> {
+    /// Return the eids of this security `identity` is not entitled to on
+    /// `service`, or an empty vector if the data may be redistributed to
+    /// `identity`
+    pub fn failed_entitlements(&self, identity: &Identity, service: &Service) -> Vec<i32> {
+        identity.failed_entitlements(service, &self.eids)
+    }
+
+    /// Return true if `identity` is entitled to all the eids of this
+    /// security on `service`
+    pub fn is_entitled(&self, identity: &Identity, service: &Service) -> bool {
+        self.failed_entitlements(identity, service).is_empty()
+    }
+}
+
+/// The securities of reference data responses, split by entitlement
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Entitlements {
+    /// Securities whose data may be redistributed
+    pub entitled: Vec<String>,
+    /// Securities whose data may not be redistributed, with the eids
+    /// missing for each of them
+    pub unentitled: HashMap<String, Vec<i32>>,
+}
+
+/// Split the securities of a (partial) reference data response `event`,
+/// requested with `returnEids`, by the entitlements of `identity` on
+/// `service`
+pub fn partition_entitlements(event: &Event, identity: &Identity, service: &Service) -> Result<Entitlements, Error> {
+    let mut entitlements = Entitlements::default();
+    for message in event.messages() {
+        let element = message.element();
+        let securities = match element.try_get_element("securityData") {
+            Some(securities) => securities,
+            None => continue,
+        };
+        for security_data in securities.values::<Element>() {
+            let security = match security_data.get_element("security").and_then(|e| e.value::<String>()) {
+                Ok(security) => security,
+                Err(_) => continue,
+            };
+            let failed = match security_data.try_get_element("eidData") {
+                Some(eids) => identity.failed_entitlements_element(service, &eids)?,
+                None => Vec::new(),
+            };
+            if failed.is_empty() {
+                entitlements.entitled.push(security);
+            } else {
+                entitlements.unentitled.insert(security, failed);
+            }
+        }
+    }
+    Ok(entitlements)
+}
+
+impl<'a> Session<'a> {
+    /// Request `T::FIELDS` for all `securities` and block until the final
+    /// response is received.
+    ///
+    /// Partial responses are merged, field exceptions and security errors
+    /// are reported per security rather than failing the whole request.
+    pub fn ref_data<T, I, S>(&mut self, securities: I) -> Result<HashMap<String, SecurityData<T>>, Error>
+    where
+        T: RefData,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.send_ref_data(securities, false)
+    }
+
+    /// Same as `ref_data` but also requests the entitlement ids (`eidData`)
+    /// of every security, e.g. to check them against the identity of the
+    /// final consumer of the data with `SecurityData::is_entitled`
+    pub fn ref_data_with_eids<T, I, S>(&mut self, securities: I) -> Result<HashMap<String, SecurityData<T>>, Error>
+    where
+        T: RefData,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.send_ref_data(securities, true)
+    }
+
+    fn send_ref_data<T, I, S>(&mut self, securities: I, return_eids: bool) -> Result<HashMap<String, SecurityData<T>>, Error>
+    where
+        T: RefData,
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.validate_fields(T::FIELDS)?;
+        let service = self.get_or_open_service(REFDATA_SERVICE)?;
+        let mut request = service.create_request("ReferenceDataRequest")?;
+        for security in securities {
+            request.append("securities", security.as_ref())?;
+        }
+        for field in T::FIELDS {
+            request.append("fields", *field)?;
+        }
+        if return_eids {
+            request.element().set("returnEids", true)?;
+        }
+
+        let mut results = HashMap::new();
+        self.collect_responses(request, |response| process_response(response, &mut results))?;
+        Ok(results)
+    }
+
+    /// Send `request` on a dedicated queue and feed every (partial) response
+    /// to `on_response` until the final response is received
+    pub(crate) fn collect_responses<F>(&mut self, request: Request, mut on_response: F) -> Result<(), Error>
+    where
+        F: FnMut(&Element) -> Result<(), Error>,
+    {
+        self.collect_messages(request, |message| {
+            let response = message.element();
+            if let Ok(error) = response.get_element("responseError") {
+                return Err(Error::RequestFailure(ErrorInfo::from_element(&error)));
+            }
+            on_response(&response)
+        })
+    }
+
+    /// Send `request` on a dedicated queue and feed every (partial) response
+    /// message to `on_message` until the final response is received
+    pub(crate) fn collect_messages<F>(&mut self, request: Request, mut on_message: F) -> Result<(), Error>
+    where
+        F: FnMut(&Message) -> Result<(), Error>,
+    {
+        let token = self.cancellation_token.clone();
+        let mut queue = EventQueue::new();
+        self.send_request(request, None, Some(&queue), None)?;
+
+        loop {
+            let event = queue.next_event_cancellable(None, token.as_ref())?;
+            let event_type = event.event_type();
+            match event_type {
+                EventType::PartialResponse | EventType::Response => {
+                    for message in event.messages() {
+                        on_message(&message)?;
+                    }
+                    if event_type == EventType::Response {
+                        return Ok(());
+                    }
+                }
+                EventType::RequestStatus => {
+                    for message in event.messages() {
+                        if let Ok(reason) = message.element().get_element("reason") {
+                            return Err(Error::RequestFailure(ErrorInfo::from_element(&reason)));
+                        }
+                    }
+                    return Err(Error::RequestFailure(ErrorInfo::default()));
+                }
+                EventType::Timeout => return Err(Error::TimeOut),
+                _ => (),
+            }
+        }
+    }
+}
+
+fn process_response<T: RefData>(
+    response: &Element,
+    results: &mut HashMap<String, SecurityData<T>>,
+) -> Result<(), Error> {
+    let securities = response.get_element("securityData")?;
+    for security_data in securities.values::<Element>() {
+        let security = security_data.get_element("security")?.value::<String>()?;
+        let entry = results.entry(security).or_insert_with(SecurityData::default);
+
+        if let Ok(error) = security_data.get_element("securityError") {
+            entry.security_error = Some(ErrorInfo::from_element(&error));
+        }
+
+        entry.field_exceptions.extend(field_exceptions(&security_data)?);
+
+        if let Ok(eids) = security_data.get_element("eidData") {
+            for eid in eids.values_checked::<i32>() {
+                entry.eids.push(eid?);
+            }
+        }
+
+        if let Ok(field_data) = security_data.get_element("fieldData") {
+            for field in field_data.elements() {
+                entry.data.on_field(&field.string_name(), &field)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Read the `fieldExceptions` of a `securityData` element
+pub(crate) fn field_exceptions(security_data: &Element) -> Result<Vec<FieldException>, Error> {
+    let mut result = Vec::new();
+    if let Ok(exceptions) = security_data.get_element("fieldExceptions") {
+        for exception in exceptions.values::<Element>() {
+            let field_id = exception.get_element("fieldId")?.value::<String>()?;
+            let error_info = exception
+                .get_element("errorInfo")
+                .map(|e| ErrorInfo::from_element(&e))
+                .unwrap_or_default();
+            result.push(FieldException { field_id, error_info });
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::test_event;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Price {
+        px_last: f64,
+        name: String,
+    }
+
+    impl RefData for Price {
+        const FIELDS: &'static [&'static str] = &["PX_LAST", "NAME"];
+
+        fn on_field(&mut self, field: &str, element: &Element) -> Result<(), Error> {
+            match field {
+                "PX_LAST" => self.px_last = element.value()?,
+                "NAME" => self.name = element.value()?,
+                _ => {}
+            }
+            Ok(())
+        }
+    }
+
+    fn build_event(json: &str) -> Result<Event, Error> {
+        test_event(EventType::PartialResponse, "ReferenceDataResponse", None, json)
+    }
+
+    #[test]
+    fn test_process_response() -> Result<(), Error> {
+        let mut results = HashMap::new();
+        let event = build_event(r#"{ "securityData": [
+            { "security": "IBM US Equity", "fieldData": { "PX_LAST": 101.5 } },
+            { "security": "UNKNOWN Equity", "securityError": { "category": "BAD_SEC", "message": "Unknown security" } }
+        ] }"#)?;
+        for message in event.messages() {
+            process_response::<Price>(&message.element(), &mut results)?;
+        }
+
+        // partial responses are merged per security
+        let event = build_event(r#"{ "securityData": [
+            {
+                "security": "IBM US Equity",
+                "fieldData": { "NAME": "IBM" },
+                "fieldExceptions": [ { "fieldId": "PX_LSAT", "errorInfo": { "code": 9, "category": "BAD_FLD" } } ]
+            }
+        ] }"#)?;
+        for message in event.messages() {
+            process_response::<Price>(&message.element(), &mut results)?;
+        }
+
+        let ibm = &results["IBM US Equity"];
+        assert_eq!(ibm.data, Price { px_last: 101.5, name: "IBM".to_string() });
+        assert!(ibm.security_error.is_none());
+        assert_eq!(ibm.field_exceptions.len(), 1);
+        assert_eq!(ibm.field_exceptions[0].field_id, "PX_LSAT");
+        assert_eq!(ibm.field_exceptions[0].error_info.code, 9);
+        assert_eq!(ibm.field_exceptions[0].error_info.category, "BAD_FLD");
+
+        let unknown = results["UNKNOWN Equity"].security_error.as_ref().unwrap();
+        assert_eq!(unknown.category, "BAD_SEC");
+        assert_eq!(unknown.message, "Unknown security");
+        assert!(unknown.source.is_empty());
+        Ok(())
+    }
+}
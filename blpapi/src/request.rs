@@ -9,10 +9,12 @@ use std::ptr;
 use std::os::raw::c_char;
 use std::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
 
 /// A `Request`
 ///
-/// A `Request` dereferences to an element
+/// The content of a `Request` is filled through its top level element,
+/// see `set`, `append` and `element_mut`
 pub struct Request {
     pub(crate) ptr: *mut blpapi_Request_t,
     elements: *mut blpapi_Element_t,
@@ -53,6 +55,48 @@ impl Request {
         Element { ptr: self.elements, _marker: PhantomData }
     }
 
+    /// Get a mutable access to the top level element, e.g. to fill nested
+    /// sequences with `Element::build`
+    pub fn element_mut(&mut self) -> ElementRef<'_> {
+        ElementRef { element: Element { ptr: self.elements, _marker: PhantomData } }
+    }
+
+    /// Get the inner Element defined by name
+    pub fn get_element(&self, name: &str) -> Result<Element<'_>, Error> {
+        // the inner elements live as long as the request
+        self.element()
+            .get_element(name)
+            .map(|element| Element { ptr: element.ptr, _marker: PhantomData })
+    }
+
+    /// Get the inner Element defined by name
+    pub fn get_named_element(&self, name: &Name) -> Result<Element<'_>, Error> {
+        // the inner elements live as long as the request
+        self.element()
+            .get_named_element(name)
+            .map(|element| Element { ptr: element.ptr, _marker: PhantomData })
+    }
+
+    /// Set the value of the inner Element defined by name
+    pub fn set<V: SetValue>(&mut self, name: &str, value: V) -> Result<(), Error> {
+        self.element_mut().set(name, value)
+    }
+
+    /// Set the value of the inner Element defined by name
+    pub fn set_named<V: SetValue>(&mut self, name: &Name, value: V) -> Result<(), Error> {
+        self.element_mut().set_named(name, value)
+    }
+
+    /// Override the value of field `field_id`, appending a
+    /// `{ fieldId, value }` entry to the `overrides` array of the request,
+    /// e.g. for `ReferenceDataRequest` and `HistoricalDataRequest`
+    pub fn add_override<V: SetValue>(&mut self, field_id: &str, value: V) -> Result<(), Error> {
+        let mut overrides = self.get_element("overrides")?;
+        let mut item = overrides.append_element()?;
+        item.set("fieldId", field_id)?;
+        item.set("value", value)
+    }
+
     /// Append a new value to the existing inner Element sequence defined by name
     pub fn append<V: SetValue>(&mut self, name: &str, value: V) -> Result<(), Error> {
         let element = self.element();
@@ -69,6 +113,25 @@ impl Request {
     }
 }
 
+/// The top level element of a `Request`, borrowed mutably from it
+pub struct ElementRef<'a> {
+    element: Element<'a>,
+}
+
+impl<'a> Deref for ElementRef<'a> {
+    type Target = Element<'a>;
+
+    fn deref(&self) -> &Element<'a> {
+        &self.element
+    }
+}
+
+impl<'a> DerefMut for ElementRef<'a> {
+    fn deref_mut(&mut self) -> &mut Element<'a> {
+        &mut self.element
+    }
+}
+
 impl Drop for Request {
     fn drop(&mut self) {
         unsafe { blpapi_Request_destroy(self.ptr) }
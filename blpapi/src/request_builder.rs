@@ -0,0 +1,66 @@
+//! One-shot requests
+//!
+//! `Session::request` collapses opening the service, creating the request
+//! and sending it, e.g.
+//! `session.request("//blp/refdata", "ReferenceDataRequest")?.append("securities", "IBM US Equity")?.send_collect()`
+use crate::{
+    correlation_id::CorrelationId,
+    element::SetValue,
+    event::EventQueue,
+    identity::Identity,
+    message::Message,
+    request::Request,
+    session::Session,
+    Error,
+};
+
+/// A request being built, see `Session::request`
+pub struct RequestBuilder<'s, 'a> {
+    session: &'s mut Session<'a>,
+    request: Request,
+}
+
+impl<'s, 'a> RequestBuilder<'s, 'a> {
+    /// Set the sub-element `name` of the request to `value`
+    pub fn set<V: SetValue>(mut self, name: &str, value: V) -> Result<Self, Error> {
+        self.request.element_mut().set(name, value)?;
+        Ok(self)
+    }
+
+    /// Append `value` to the array sub-element `name` of the request
+    pub fn append<V: SetValue>(mut self, name: &str, value: V) -> Result<Self, Error> {
+        self.request.append(name, value)?;
+        Ok(self)
+    }
+
+    /// The request, to fill parts not covered by `set` and `append`
+    pub fn request_mut(&mut self) -> &mut Request {
+        &mut self.request
+    }
+
+    /// Send the request, the responses are delivered to the session
+    /// event handler (or `event_queue` if any)
+    pub fn send(self, identity: Option<&Identity>, event_queue: Option<&EventQueue>) -> Result<CorrelationId, Error> {
+        self.session.send_request(self.request, identity, event_queue, None)
+    }
+
+    /// Send the request and block until the final response is received,
+    /// returning all the (partial) response messages
+    pub fn send_collect(self) -> Result<Vec<Message>, Error> {
+        let mut messages = Vec::new();
+        self.session.collect_messages(self.request, |message| {
+            messages.push(message.clone());
+            Ok(())
+        })?;
+        Ok(messages)
+    }
+}
+
+impl<'a> Session<'a> {
+    /// Start building an `operation` request of `service`, opening the
+    /// service if it is not open yet
+    pub fn request<'s>(&'s mut self, service: &str, operation: &str) -> Result<RequestBuilder<'s, 'a>, Error> {
+        let request = self.get_or_open_service(service)?.create_request(operation)?;
+        Ok(RequestBuilder { session: self, request })
+    }
+}
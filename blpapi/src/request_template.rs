@@ -0,0 +1,95 @@
+//! Snapshot request templates
+//!
+//! A `RequestTemplate` resolves a `//blp/mktdata` subscription string once;
+//! each `Session::send_request_template` then sends a snapshot request for
+//! it without the resolution overhead, receiving a single PARTIAL_RESPONSE
+//! or RESPONSE with the current values.
+//!
+//! A `RequestTemplateAvailable` admin message is delivered once the
+//! template can be used, and `RequestTemplateTerminated` once it can no
+//! longer be.
+use crate::{
+    correlation_id::CorrelationId,
+    identity::Identity,
+    session::Session,
+    Error,
+};
+use blpapi_sys::*;
+use std::ffi::CString;
+use std::fmt::{Debug, Formatter};
+use std::ptr;
+
+/// A snapshot request template, see `Session::create_snapshot_request_template`
+///
+/// Cloning a template is cheap: the underlying template is reference counted.
+pub struct RequestTemplate(pub(crate) *mut blpapi_RequestTemplate_t);
+
+impl Clone for RequestTemplate {
+    fn clone(&self) -> Self {
+        unsafe { blpapi_RequestTemplate_addRef(self.0) };
+        RequestTemplate(self.0)
+    }
+}
+
+impl Drop for RequestTemplate {
+    fn drop(&mut self) {
+        unsafe { blpapi_RequestTemplate_release(self.0) };
+    }
+}
+
+impl Debug for RequestTemplate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("RequestTemplate[{:p}]", self.0))
+    }
+}
+
+unsafe impl Send for RequestTemplate {}
+unsafe impl Sync for RequestTemplate {}
+
+impl<'a> Session<'a> {
+    /// Create a snapshot request template for `subscription_string`, e.g.
+    /// `//blp/mktdata/ticker/IBM US Equity?fields=BID,ASK`, authorized with
+    /// `identity` or the session identity.
+    ///
+    /// The status messages of the template, `RequestTemplateAvailable` and
+    /// `RequestTemplateTerminated`, are delivered with `correlation_id`.
+    pub fn create_snapshot_request_template(
+        &mut self,
+        subscription_string: &str,
+        identity: Option<&Identity>,
+        correlation_id: Option<CorrelationId>,
+    ) -> Result<RequestTemplate, Error> {
+        let subscription_string = CString::new(subscription_string)
+            .map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let mut correlation_id = correlation_id.unwrap_or_else(CorrelationId::new_empty);
+        let identity = identity.map_or(ptr::null(), |identity| identity.0 as *const _);
+        let mut template = ptr::null_mut();
+        let res = unsafe {
+            blpapi_Session_createSnapshotRequestTemplate(
+                &mut template,
+                self.ptr,
+                subscription_string.as_ptr(),
+                identity,
+                &mut correlation_id.0,
+            )
+        };
+        Error::check(res)?;
+        Ok(RequestTemplate(template))
+    }
+
+    /// Send a snapshot request for `template`, returning the correlation
+    /// id of its response
+    pub fn send_request_template(
+        &mut self,
+        template: &RequestTemplate,
+        correlation_id: Option<CorrelationId>,
+    ) -> Result<CorrelationId, Error> {
+        if self.quiescing {
+            return Err(Error::Quiescing);
+        }
+        let mut correlation_id = correlation_id.unwrap_or_else(CorrelationId::new_empty);
+        let res = unsafe { blpapi_Session_sendRequestTemplate(self.ptr, template.0, &mut correlation_id.0) };
+        Error::check(res)?;
+        Ok(correlation_id)
+    }
+}
@@ -0,0 +1,241 @@
+//! Automatic reconnection
+//!
+//! A `ResilientSession` is an opt-in wrapper over an asynchronous session
+//! whose events are read with `next_event`. When the session is terminated
+//! by the API, e.g. after the connection went down and could not be
+//! restored, a new session is created with the same options, the services
+//! opened through the wrapper are opened again and the active
+//! subscriptions are replayed with their original correlation ids.
+//!
+//! The `SessionConnectionDown` and `SessionTerminated` events are still
+//! handed to the application, which can use `reconnects` to tell a
+//! recovered session from the original one.
+use crate::{
+    correlation_id::CorrelationId,
+    errors::ErrorInfo,
+    event::{Event, EventType},
+    event_channel::ChannelSession,
+    service::Service,
+    session::Session,
+    session_options::SessionOptions,
+    subscriptionlist::SubscriptionList,
+    Error,
+};
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::Duration;
+
+/// Default number of events buffered before the dispatcher thread blocks
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// How long to wait for the `SessionStartupFailure` of a failed start
+const STARTUP_FAILURE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A subscription replayed after a reconnection
+struct Subscription {
+    topic: String,
+    fields: Vec<String>,
+    options: Vec<String>,
+    correlation_id: CorrelationId,
+}
+
+/// A session re-established whenever it is terminated
+pub struct ResilientSession {
+    options: SessionOptions,
+    capacity: usize,
+    max_retries: usize,
+    retry_delay: Duration,
+    services: Vec<String>,
+    subscriptions: Vec<Subscription>,
+    // declared first so the receiver is dropped before the session is
+    // stopped, which cannot block on a full channel then
+    receiver: Receiver<Event>,
+    session: ChannelSession,
+    reconnects: usize,
+}
+
+impl ResilientSession {
+    /// Create and start a session with `options`
+    pub fn start(options: SessionOptions) -> Result<Self, Error> {
+        let (session, receiver) = establish(&options, DEFAULT_CAPACITY)?;
+        Ok(ResilientSession {
+            options,
+            capacity: DEFAULT_CAPACITY,
+            max_retries: 10,
+            retry_delay: Duration::from_secs(1),
+            services: Vec::new(),
+            subscriptions: Vec::new(),
+            receiver,
+            session,
+            reconnects: 0,
+        })
+    }
+
+    /// Give up reconnecting after `max_retries` failed attempts, 10 by
+    /// default
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Wait `retry_delay` between two reconnection attempts, 1s by default
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// The current session, it is replaced on every reconnection
+    pub fn session(&mut self) -> &mut Session<'static> {
+        &mut self.session
+    }
+
+    /// Number of times the session was re-established
+    pub fn reconnects(&self) -> usize {
+        self.reconnects
+    }
+
+    /// Open `service`, which is opened again after a reconnection
+    pub fn open_service(&mut self, service: &str) -> Result<Service, Error> {
+        let opened = self.session.get_or_open_service(service)?;
+        if !self.services.iter().any(|name| name == service) {
+            self.services.push(service.to_string());
+        }
+        Ok(opened)
+    }
+
+    /// Subscribe to `topic`, the subscription is replayed with the same
+    /// `correlation_id` after a reconnection
+    pub fn subscribe(
+        &mut self,
+        topic: &str,
+        fields: &[&str],
+        options: &[&str],
+        correlation_id: CorrelationId,
+    ) -> Result<(), Error> {
+        let subscription = Subscription {
+            topic: topic.to_string(),
+            fields: fields.iter().map(|field| field.to_string()).collect(),
+            options: options.iter().map(|option| option.to_string()).collect(),
+            correlation_id,
+        };
+        let subscription_list = subscription_list(std::slice::from_ref(&subscription))?;
+        self.session.subscribe(&subscription_list, None)?;
+        self.subscriptions.push(subscription);
+        Ok(())
+    }
+
+    /// Cancel the subscription identified by `correlation_id`
+    pub fn unsubscribe(&mut self, correlation_id: &CorrelationId) -> Result<(), Error> {
+        let index = match self
+            .subscriptions
+            .iter()
+            .position(|subscription| &subscription.correlation_id == correlation_id)
+        {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+        let subscription = self.subscriptions.remove(index);
+        let subscription_list = subscription_list(std::slice::from_ref(&subscription))?;
+        self.session.unsubscribe(&subscription_list)
+    }
+
+    /// Wait for the next event of the session. When it reports the
+    /// termination of the session, the session is re-established before
+    /// the event is returned.
+    pub fn next_event(&mut self) -> Result<Event, Error> {
+        loop {
+            match self.receiver.recv() {
+                Ok(event) => {
+                    if is_terminated(&event) {
+                        self.reconnect()?;
+                    }
+                    return Ok(event);
+                }
+                // the session is gone without notice, start over
+                Err(_) => self.reconnect()?,
+            }
+        }
+    }
+
+    fn reconnect(&mut self) -> Result<(), Error> {
+        let mut attempt = 0;
+        loop {
+            match self.restore() {
+                Ok(()) => {
+                    self.reconnects += 1;
+                    return Ok(());
+                }
+                Err(err) if attempt >= self.max_retries => return Err(err),
+                Err(_) => {
+                    attempt += 1;
+                    thread::sleep(self.retry_delay);
+                }
+            }
+        }
+    }
+
+    /// Create a new session, then reopen the services and replay the
+    /// subscriptions
+    fn restore(&mut self) -> Result<(), Error> {
+        let (mut session, receiver) = establish(&self.options, self.capacity)?;
+        for service in &self.services {
+            session.get_or_open_service(service)?;
+        }
+        if !self.subscriptions.is_empty() {
+            let subscription_list = subscription_list(&self.subscriptions)?;
+            session.subscribe(&subscription_list, None)?;
+        }
+        self.receiver = receiver;
+        self.session = session;
+        Ok(())
+    }
+}
+
+fn establish(options: &SessionOptions, capacity: usize) -> Result<(ChannelSession, Receiver<Event>), Error> {
+    let (mut session, receiver) = Session::event_channel(options.clone(), capacity)?;
+    if !session.start() {
+        return Err(Error::SessionStartFailure(startup_failure(&receiver)));
+    }
+    Ok((session, receiver))
+}
+
+/// The reason of the `SessionStartupFailure` of a session which failed to
+/// start, the event is generated before `start` returns but handed to the
+/// channel by the dispatcher thread
+fn startup_failure(receiver: &Receiver<Event>) -> ErrorInfo {
+    while let Ok(event) = receiver.recv_timeout(STARTUP_FAILURE_TIMEOUT) {
+        for message in event.messages() {
+            if message.message_type() == "SessionStartupFailure" {
+                return message
+                    .element()
+                    .get_element("reason")
+                    .map(|reason| ErrorInfo::from_element(&reason))
+                    .unwrap_or_default();
+            }
+        }
+    }
+    ErrorInfo {
+        message: "cannot start session".to_string(),
+        ..ErrorInfo::default()
+    }
+}
+
+fn subscription_list(subscriptions: &[Subscription]) -> Result<SubscriptionList, Error> {
+    let mut subscription_list = SubscriptionList::new();
+    for subscription in subscriptions {
+        subscription_list.add(
+            &subscription.topic,
+            Some(&subscription.fields),
+            Some(&subscription.options),
+            Some(subscription.correlation_id.clone()),
+        )?;
+    }
+    Ok(subscription_list)
+}
+
+fn is_terminated(event: &Event) -> bool {
+    event.event_type() == EventType::SessionStatus
+        && event
+            .messages()
+            .any(|message| message.message_type() == "SessionTerminated")
+}
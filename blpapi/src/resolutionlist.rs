@@ -0,0 +1,233 @@
+use crate::correlation_id::CorrelationId;
+use crate::element::Element;
+use crate::errors::Error;
+use crate::message::Message;
+use crate::name::Name;
+use blpapi_sys::*;
+use std::ffi::{CString, CStr};
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+/// The resolution status of a topic in a `ResolutionList`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolutionStatus {
+    Unresolved,
+    Resolved,
+    ResolutionFailureBadService,
+    ResolutionFailureServiceAuthorizationFailed,
+    ResolutionFailureBadTopic,
+    ResolutionFailureTopicAuthorizationFailed,
+}
+
+impl From<c_int> for ResolutionStatus {
+    fn from(status: c_int) -> Self {
+        match status as u32 {
+            BLPAPI_RESOLUTIONLIST_RESOLVED => ResolutionStatus::Resolved,
+            BLPAPI_RESOLUTIONLIST_RESOLUTION_FAILURE_BAD_SERVICE => ResolutionStatus::ResolutionFailureBadService,
+            BLPAPI_RESOLUTIONLIST_RESOLUTION_FAILURE_SERVICE_AUTHORIZATION_FAILED => ResolutionStatus::ResolutionFailureServiceAuthorizationFailed,
+            BLPAPI_RESOLUTIONLIST_RESOLUTION_FAILURE_BAD_TOPIC => ResolutionStatus::ResolutionFailureBadTopic,
+            BLPAPI_RESOLUTIONLIST_RESOLUTION_FAILURE_TOPIC_AUTHORIZATION_FAILED => ResolutionStatus::ResolutionFailureTopicAuthorizationFailed,
+            _ => ResolutionStatus::Unresolved,
+        }
+    }
+}
+
+/// Contains a list of topics that require resolution.
+///
+/// Created from topic strings or from SUBSCRIPTION_STARTED
+/// messages. This is passed to a 'resolve()' call or
+/// 'resolveAsync()' call on a 'ProviderSession'. It is updated and
+/// returned by the 'resolve()' call.
+pub struct ResolutionList(pub(crate) *mut blpapi_ResolutionList_t);
+
+impl ResolutionList {
+    /// Create an empty 'ResolutionList'.
+    pub fn new() -> Self {
+        let ptr = unsafe { blpapi_ResolutionList_create(ptr::null_mut()) };
+        ResolutionList(ptr)
+    }
+
+    /// Add the specified 'topic' to this list, associating the specified
+    /// 'correlationId' with it. The status of the new entry is
+    /// UNRESOLVED.
+    pub fn add(&mut self, topic: &str, correlation_id: Option<CorrelationId>) -> Result<(), Error> {
+        let topic = CString::new(topic).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let correlation_id = correlation_id.unwrap_or_else(|| CorrelationId::new_empty());
+        let res = unsafe { blpapi_ResolutionList_add(self.0, topic.as_ptr(), &correlation_id.0) };
+        Error::check(res)
+    }
+
+    /// Add the topic contained in the specified
+    /// 'subscriptionStartedMessage' to this list, associating the
+    /// specified 'correlationId' with it. The status of the new entry is
+    /// UNRESOLVED.
+    pub fn add_from_message(&mut self, message: &Message, correlation_id: Option<CorrelationId>) -> Result<(), Error> {
+        let correlation_id = correlation_id.unwrap_or_else(|| CorrelationId::new_empty());
+        let res = unsafe { blpapi_ResolutionList_addFromMessage(self.0, message.0, &correlation_id.0) };
+        Error::check(res)
+    }
+
+    /// Add the specified 'attribute' to the list of attributes requested
+    /// during resolution for each topic in this list.
+    pub fn add_attribute(&mut self, attribute: &Name) -> Result<(), Error> {
+        let res = unsafe { blpapi_ResolutionList_addAttribute(self.0, attribute.0) };
+        Error::check(res)
+    }
+
+    /// Return the number of entries in this list.
+    pub fn size(&self) -> usize {
+        unsafe { blpapi_ResolutionList_size(self.0) as usize }
+    }
+
+    /// Return the CorrelationId of the specified 'index'th entry.
+    pub fn correlation_id_at(&self, index: usize) -> Result<CorrelationId, Error> {
+        let mut correlation_id = blpapi_CorrelationId_t::default();
+        let res = unsafe { blpapi_ResolutionList_correlationIdAt(self.0, &mut correlation_id, index) };
+        Error::check(res)?;
+        Ok(unsafe { CorrelationId::from_raw(correlation_id) })
+    }
+
+    /// Return the topic of the entry identified by 'correlation_id'.
+    pub fn topic_string(&self, correlation_id: &CorrelationId) -> Result<String, Error> {
+        let mut topic: *const c_char = ptr::null();
+        let res = unsafe { blpapi_ResolutionList_topicString(self.0, &mut topic, &correlation_id.0) };
+        Error::check(res)?;
+        Ok(unsafe { CStr::from_ptr(topic) }.to_string_lossy().into_owned())
+    }
+
+    /// Return the topic of the specified 'index'th entry.
+    pub fn topic_string_at(&self, index: usize) -> Result<String, Error> {
+        let mut topic: *const c_char = ptr::null();
+        let res = unsafe { blpapi_ResolutionList_topicStringAt(self.0, &mut topic, index) };
+        Error::check(res)?;
+        Ok(unsafe { CStr::from_ptr(topic) }.to_string_lossy().into_owned())
+    }
+
+    /// Return the resolution status of the entry identified by
+    /// 'correlation_id'.
+    pub fn status(&self, correlation_id: &CorrelationId) -> Result<ResolutionStatus, Error> {
+        let mut status: c_int = 0;
+        let res = unsafe { blpapi_ResolutionList_status(self.0, &mut status, &correlation_id.0) };
+        Error::check(res)?;
+        Ok(ResolutionStatus::from(status))
+    }
+
+    /// Return the resolution status of the specified 'index'th entry.
+    pub fn status_at(&self, index: usize) -> Result<ResolutionStatus, Error> {
+        let mut status: c_int = 0;
+        let res = unsafe { blpapi_ResolutionList_statusAt(self.0, &mut status, index) };
+        Error::check(res)?;
+        Ok(ResolutionStatus::from(status))
+    }
+
+    /// Return the value of the specified 'attribute' for the entry
+    /// identified by 'correlation_id'.
+    ///
+    /// Only valid once the entry is resolved and if 'attribute' was added
+    /// with 'add_attribute'.
+    pub fn attribute(&self, attribute: &Name, correlation_id: &CorrelationId) -> Result<Element, Error> {
+        let mut element = ptr::null_mut();
+        let res = unsafe { blpapi_ResolutionList_attribute(self.0, &mut element, attribute.0, &correlation_id.0) };
+        Error::check(res)?;
+        Ok(Element { ptr: element, _marker: PhantomData })
+    }
+
+    /// Return the value of the specified 'attribute' for the specified
+    /// 'index'th entry.
+    pub fn attribute_at(&self, attribute: &Name, index: usize) -> Result<Element, Error> {
+        let mut element = ptr::null_mut();
+        let res = unsafe { blpapi_ResolutionList_attributeAt(self.0, &mut element, attribute.0, index) };
+        Error::check(res)?;
+        Ok(Element { ptr: element, _marker: PhantomData })
+    }
+
+    /// Return the message received during resolution of the entry
+    /// identified by 'correlation_id'.
+    pub fn message(&self, correlation_id: &CorrelationId) -> Result<Message, Error> {
+        let mut message = ptr::null_mut();
+        let res = unsafe { blpapi_ResolutionList_message(self.0, &mut message, &correlation_id.0) };
+        Error::check(res)?;
+        unsafe { blpapi_Message_addRef(message) };
+        Ok(Message(message))
+    }
+
+    /// Return the message received during resolution of the specified
+    /// 'index'th entry.
+    pub fn message_at(&self, index: usize) -> Result<Message, Error> {
+        let mut message = ptr::null_mut();
+        let res = unsafe { blpapi_ResolutionList_messageAt(self.0, &mut message, index) };
+        Error::check(res)?;
+        unsafe { blpapi_Message_addRef(message) };
+        Ok(Message(message))
+    }
+
+    /// Return the value of the specified 'attribute' in the specified
+    /// 'RESOLUTION_SUCCESS' message, or None if it is not present.
+    pub fn extract_attribute_from_resolution_success<'m>(message: &'m Message, attribute: &Name) -> Option<Element<'m>> {
+        let element = unsafe { blpapi_ResolutionList_extractAttributeFromResolutionSuccess(message.0, attribute.0) };
+        if element.is_null() {
+            None
+        } else {
+            Some(Element { ptr: element, _marker: PhantomData })
+        }
+    }
+
+    /// Iterate over the entries of this list
+    pub fn entries(&self) -> ResolutionEntries {
+        ResolutionEntries { list: self, index: 0 }
+    }
+}
+
+/// An entry of a `ResolutionList`
+#[derive(Debug)]
+pub struct ResolutionEntry {
+    pub topic: String,
+    pub correlation_id: CorrelationId,
+    pub status: ResolutionStatus,
+}
+
+/// An iterator over the entries of a `ResolutionList`
+pub struct ResolutionEntries<'a> {
+    list: &'a ResolutionList,
+    index: usize,
+}
+
+impl<'a> Iterator for ResolutionEntries<'a> {
+    type Item = ResolutionEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.list.size() {
+            return None;
+        }
+        let index = self.index;
+        self.index += 1;
+        Some(ResolutionEntry {
+            topic: self.list.topic_string_at(index).ok()?,
+            correlation_id: self.list.correlation_id_at(index).ok()?,
+            status: self.list.status_at(index).ok()?,
+        })
+    }
+}
+
+impl Drop for ResolutionList {
+    fn drop(&mut self) {
+        unsafe { blpapi_ResolutionList_destroy(self.0) }
+    }
+}
+
+impl Clone for ResolutionList {
+    fn clone(&self) -> Self {
+        ResolutionList(unsafe { blpapi_ResolutionList_create(self.0) })
+    }
+}
+
+impl Debug for ResolutionList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("ResolutionList[size={}]", self.size()))
+    }
+}
+
+unsafe impl Send for ResolutionList {}
+unsafe impl Sync for ResolutionList {}
@@ -0,0 +1,300 @@
+//! Schema introspection
+//!
+//! The schema of a service describes its requests, responses and events as
+//! element definitions, each with a type definition listing the allowed
+//! sub-elements or values.
+use crate::{element::DataType, name::Name, Error};
+use blpapi_sys::*;
+use std::ffi::{CStr, CString};
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+/// The status of a schema item
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaStatus {
+    /// This item is current and may appear in Messages
+    Active,
+    /// This item is current and may appear in Messages but will be removed
+    /// in due course
+    Deprecated,
+    /// This item is not current and will not appear in Messages
+    Inactive,
+    /// This item is expected to be deprecated in the future
+    PendingDeprecation,
+}
+
+impl From<c_int> for SchemaStatus {
+    fn from(status: c_int) -> Self {
+        match status as u32 {
+            BLPAPI_STATUS_DEPRECATED => SchemaStatus::Deprecated,
+            BLPAPI_STATUS_INACTIVE => SchemaStatus::Inactive,
+            BLPAPI_STATUS_PENDING_DEPRECATION => SchemaStatus::PendingDeprecation,
+            _ => SchemaStatus::Active,
+        }
+    }
+}
+
+fn string(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        String::new()
+    } else {
+        unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned()
+    }
+}
+
+/// The definition of an element: its name, how many values it holds and
+/// its type
+#[derive(Clone, Copy)]
+pub struct SchemaElementDefinition<'a> {
+    pub(crate) ptr: *mut blpapi_SchemaElementDefinition_t,
+    pub(crate) _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> SchemaElementDefinition<'a> {
+    pub(crate) fn new(ptr: *mut blpapi_SchemaElementDefinition_t) -> Self {
+        SchemaElementDefinition { ptr, _marker: PhantomData }
+    }
+
+    /// Get the name of the element
+    pub fn name(&self) -> Name {
+        Name(unsafe { blpapi_SchemaElementDefinition_name(self.ptr) })
+    }
+
+    /// Get the description of the element
+    pub fn description(&self) -> String {
+        string(unsafe { blpapi_SchemaElementDefinition_description(self.ptr) })
+    }
+
+    /// Get the status of the element
+    pub fn status(&self) -> SchemaStatus {
+        SchemaStatus::from(unsafe { blpapi_SchemaElementDefinition_status(self.ptr) })
+    }
+
+    /// Get the type of the element
+    pub fn type_definition(&self) -> SchemaTypeDefinition<'a> {
+        let ptr = unsafe { blpapi_SchemaElementDefinition_type(self.ptr) };
+        SchemaTypeDefinition { ptr, _marker: PhantomData }
+    }
+
+    /// Get the minimum number of values of the element
+    pub fn min_values(&self) -> usize {
+        unsafe { blpapi_SchemaElementDefinition_minValues(self.ptr) }
+    }
+
+    /// Get the maximum number of values of the element, a very large
+    /// number for unbounded arrays
+    pub fn max_values(&self) -> usize {
+        unsafe { blpapi_SchemaElementDefinition_maxValues(self.ptr) }
+    }
+
+    /// Get the alternate names of the element
+    pub fn alternate_names(&self) -> Vec<Name> {
+        let count = unsafe { blpapi_SchemaElementDefinition_numAlternateNames(self.ptr) };
+        (0..count)
+            .map(|index| Name(unsafe { blpapi_SchemaElementDefinition_getAlternateName(self.ptr, index) }))
+            .collect()
+    }
+
+    /// Format this definition to the specified 'f', see `Element::print`
+    pub fn print(&self, f: &mut Formatter<'_>, indent_level: isize, spaces_per_level: isize) -> Result<(), Error> {
+        let res = unsafe {
+            let stream = std::mem::transmute(f);
+            blpapi_SchemaElementDefinition_print(
+                self.ptr,
+                Some(crate::utils::stream_writer),
+                stream,
+                indent_level as c_int,
+                spaces_per_level as c_int
+            )
+        };
+        Error::check(res)
+    }
+}
+
+impl Debug for SchemaElementDefinition<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("SchemaElementDefinition[name={}]", self.name().to_string_lossy()))
+    }
+}
+
+impl Display for SchemaElementDefinition<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.print(f, 0, 4).map_err(|_| std::fmt::Error)
+    }
+}
+
+/// The type of an element: a simple data type, an enumeration or a
+/// complex type made of sub-elements
+#[derive(Clone, Copy)]
+pub struct SchemaTypeDefinition<'a> {
+    pub(crate) ptr: *mut blpapi_SchemaTypeDefinition_t,
+    pub(crate) _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> SchemaTypeDefinition<'a> {
+    /// Get the name of the type
+    pub fn name(&self) -> Name {
+        Name(unsafe { blpapi_SchemaTypeDefinition_name(self.ptr) })
+    }
+
+    /// Get the description of the type
+    pub fn description(&self) -> String {
+        string(unsafe { blpapi_SchemaTypeDefinition_description(self.ptr) })
+    }
+
+    /// Get the status of the type
+    pub fn status(&self) -> SchemaStatus {
+        SchemaStatus::from(unsafe { blpapi_SchemaTypeDefinition_status(self.ptr) })
+    }
+
+    /// Get the data type of the values of this type
+    pub fn data_type(&self) -> DataType {
+        DataType::from(unsafe { blpapi_SchemaTypeDefinition_datatype(self.ptr) } as blpapi_DataType_t)
+    }
+
+    /// Return true if this type is a sequence or a choice
+    pub fn is_complex_type(&self) -> bool {
+        unsafe { blpapi_SchemaTypeDefinition_isComplexType(self.ptr) != 0 }
+    }
+
+    /// Return true if this type is neither a sequence nor a choice
+    pub fn is_simple_type(&self) -> bool {
+        unsafe { blpapi_SchemaTypeDefinition_isSimpleType(self.ptr) != 0 }
+    }
+
+    /// Return true if this type is an enumeration
+    pub fn is_enumeration_type(&self) -> bool {
+        unsafe { blpapi_SchemaTypeDefinition_isEnumerationType(self.ptr) != 0 }
+    }
+
+    /// Get the number of sub-element definitions of a complex type
+    pub fn num_element_definitions(&self) -> usize {
+        unsafe { blpapi_SchemaTypeDefinition_numElementDefinitions(self.ptr) }
+    }
+
+    /// Get the sub-element definition at `index`
+    pub fn element_definition_at(&self, index: usize) -> Option<SchemaElementDefinition<'a>> {
+        let ptr = unsafe { blpapi_SchemaTypeDefinition_getElementDefinitionAt(self.ptr, index) };
+        if ptr.is_null() {
+            None
+        } else {
+            Some(SchemaElementDefinition::new(ptr))
+        }
+    }
+
+    /// Get the sub-element definition named `name`
+    pub fn element_definition(&self, name: &str) -> Option<SchemaElementDefinition<'a>> {
+        let name = CString::new(name).ok()?;
+        let ptr = unsafe { blpapi_SchemaTypeDefinition_getElementDefinition(self.ptr, name.as_ptr(), ptr::null()) };
+        if ptr.is_null() {
+            None
+        } else {
+            Some(SchemaElementDefinition::new(ptr))
+        }
+    }
+
+    /// Get all the sub-element definitions of a complex type
+    pub fn element_definitions(&self) -> Vec<SchemaElementDefinition<'a>> {
+        (0..self.num_element_definitions())
+            .filter_map(|index| self.element_definition_at(index))
+            .collect()
+    }
+
+    /// Get the allowed values of an enumeration type
+    pub fn enumeration_values(&self) -> Vec<Name> {
+        let list = unsafe { blpapi_SchemaTypeDefinition_enumeration(self.ptr) };
+        if list.is_null() {
+            return Vec::new();
+        }
+        let count = unsafe { blpapi_ConstantList_numConstants(list) }.max(0) as usize;
+        (0..count)
+            .map(|index| unsafe { blpapi_ConstantList_getConstantAt(list, index) })
+            .filter(|constant| !constant.is_null())
+            .map(|constant| Name(unsafe { blpapi_Constant_name(constant) }))
+            .collect()
+    }
+
+    /// Format this definition to the specified 'f', see `Element::print`
+    pub fn print(&self, f: &mut Formatter<'_>, indent_level: isize, spaces_per_level: isize) -> Result<(), Error> {
+        let res = unsafe {
+            let stream = std::mem::transmute(f);
+            blpapi_SchemaTypeDefinition_print(
+                self.ptr,
+                Some(crate::utils::stream_writer),
+                stream,
+                indent_level as c_int,
+                spaces_per_level as c_int
+            )
+        };
+        Error::check(res)
+    }
+}
+
+impl Debug for SchemaTypeDefinition<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("SchemaTypeDefinition[name={}]", self.name().to_string_lossy()))
+    }
+}
+
+impl Display for SchemaTypeDefinition<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.print(f, 0, 4).map_err(|_| std::fmt::Error)
+    }
+}
+
+/// A request operation of a service, with the definitions of its request
+/// and its responses
+#[derive(Clone, Copy)]
+pub struct Operation<'a> {
+    pub(crate) ptr: *mut blpapi_Operation_t,
+    pub(crate) _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> Operation<'a> {
+    /// Get the name of the operation, to be used with
+    /// `Service::create_request`
+    pub fn name(&self) -> String {
+        string(unsafe { blpapi_Operation_name(self.ptr) })
+    }
+
+    /// Get the description of the operation
+    pub fn description(&self) -> String {
+        string(unsafe { blpapi_Operation_description(self.ptr) })
+    }
+
+    /// Get the definition of the request of this operation
+    pub fn request_definition(&self) -> Result<SchemaElementDefinition<'a>, Error> {
+        let mut ptr = ptr::null_mut();
+        let res = unsafe { blpapi_Operation_requestDefinition(self.ptr, &mut ptr) };
+        Error::check(res)?;
+        Ok(SchemaElementDefinition::new(ptr))
+    }
+
+    /// Get the number of possible responses to this operation
+    pub fn num_response_definitions(&self) -> usize {
+        unsafe { blpapi_Operation_numResponseDefinitions(self.ptr) }.max(0) as usize
+    }
+
+    /// Get the definition of the response at `index`
+    pub fn response_definition(&self, index: usize) -> Result<SchemaElementDefinition<'a>, Error> {
+        let mut ptr = ptr::null_mut();
+        let res = unsafe { blpapi_Operation_responseDefinition(self.ptr, &mut ptr, index) };
+        Error::check(res)?;
+        Ok(SchemaElementDefinition::new(ptr))
+    }
+}
+
+impl Debug for Operation<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("Operation[name={}]", self.name()))
+    }
+}
+
+unsafe impl Send for SchemaElementDefinition<'_> {}
+unsafe impl Sync for SchemaElementDefinition<'_> {}
+unsafe impl Send for SchemaTypeDefinition<'_> {}
+unsafe impl Sync for SchemaTypeDefinition<'_> {}
+unsafe impl Send for Operation<'_> {}
+unsafe impl Sync for Operation<'_> {}
@@ -0,0 +1,189 @@
+//! Security identifiers
+//!
+//! Bloomberg accepts securities as tickers (`IBM US Equity`) or as
+//! identifiers prefixed with their type (`/isin/US4592001014`). `Security`
+//! renders the right string for request `securities` elements and for
+//! subscription topics.
+use crate::{
+    element::{Element, SetValue},
+    name::Name,
+    Error,
+};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// A security identifier
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Security {
+    /// Bloomberg ticker with its yellow key, e.g. `IBM US Equity`
+    Ticker(String),
+    /// Financial Instrument Global Identifier, e.g. `BBG000BLNNH6`
+    Figi(String),
+    /// International Securities Identification Number, e.g. `US4592001014`
+    Isin(String),
+    /// Committee on Uniform Securities Identification Procedures number,
+    /// e.g. `459200101`
+    Cusip(String),
+}
+
+impl Security {
+    /// A Bloomberg ticker, e.g. `IBM US Equity`
+    pub fn ticker(ticker: &str) -> Result<Self, Error> {
+        if ticker.trim().is_empty() || ticker.starts_with('/') {
+            return Err(Error::InvalidSecurity(ticker.to_string()));
+        }
+        Ok(Security::Ticker(ticker.to_string()))
+    }
+
+    /// A FIGI: 12 upper case alphanumeric characters starting with `BBG`
+    pub fn figi(figi: &str) -> Result<Self, Error> {
+        if figi.len() != 12 || !figi.starts_with("BBG") || !is_upper_alphanumeric(figi) {
+            return Err(Error::InvalidSecurity(figi.to_string()));
+        }
+        Ok(Security::Figi(figi.to_string()))
+    }
+
+    /// An ISIN: 12 upper case alphanumeric characters with a valid check
+    /// digit
+    pub fn isin(isin: &str) -> Result<Self, Error> {
+        if isin.len() != 12 || !is_upper_alphanumeric(isin) || !isin_check_digit_is_valid(isin) {
+            return Err(Error::InvalidSecurity(isin.to_string()));
+        }
+        Ok(Security::Isin(isin.to_string()))
+    }
+
+    /// A CUSIP: 9 upper case alphanumeric characters
+    pub fn cusip(cusip: &str) -> Result<Self, Error> {
+        if cusip.len() != 9 || !is_upper_alphanumeric(cusip) {
+            return Err(Error::InvalidSecurity(cusip.to_string()));
+        }
+        Ok(Security::Cusip(cusip.to_string()))
+    }
+
+    /// The topic prefix of the identifier type, None for tickers
+    pub fn prefix(&self) -> Option<&'static str> {
+        match self {
+            Security::Ticker(_) => None,
+            Security::Figi(_) => Some("/bbgid/"),
+            Security::Isin(_) => Some("/isin/"),
+            Security::Cusip(_) => Some("/cusip/"),
+        }
+    }
+
+    /// The identifier, without prefix
+    pub fn id(&self) -> &str {
+        match self {
+            Security::Ticker(id) | Security::Figi(id) | Security::Isin(id) | Security::Cusip(id) => id,
+        }
+    }
+
+    /// The subscription topic of this security on `service`, e.g.
+    /// `//blp/mktdata/isin/US4592001014`, or the topic relative to the
+    /// default subscription service if None
+    pub fn topic(&self, service: Option<&str>) -> String {
+        match (service, self.prefix()) {
+            (None, _) => self.to_string(),
+            (Some(service), None) => format!("{}/ticker/{}", service.trim_end_matches('/'), self.id()),
+            (Some(service), Some(prefix)) => format!("{}{}{}", service.trim_end_matches('/'), prefix, self.id()),
+        }
+    }
+}
+
+impl Display for Security {
+    /// The string expected by request `securities` elements
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.prefix() {
+            Some(prefix) => write!(f, "{}{}", prefix, self.id()),
+            None => f.write_str(self.id()),
+        }
+    }
+}
+
+impl FromStr for Security {
+    type Err = Error;
+
+    /// Parse a ticker or a prefixed identifier, as rendered by `Display`
+    fn from_str(s: &str) -> Result<Self, Error> {
+        if let Some(id) = s.strip_prefix("/bbgid/") {
+            Security::figi(id)
+        } else if let Some(id) = s.strip_prefix("/isin/") {
+            Security::isin(id)
+        } else if let Some(id) = s.strip_prefix("/cusip/") {
+            Security::cusip(id)
+        } else {
+            Security::ticker(s)
+        }
+    }
+}
+
+impl<'a> SetValue for &'a Security {
+    fn set_at(self, element: &mut Element, index: usize) -> Result<(), Error> {
+        self.to_string().as_str().set_at(element, index)
+    }
+
+    fn set(self, element: &mut Element, name: &str) -> Result<(), Error> {
+        self.to_string().as_str().set(element, name)
+    }
+
+    fn set_named(self, element: &mut Element, name: &Name) -> Result<(), Error> {
+        self.to_string().as_str().set_named(element, name)
+    }
+}
+
+fn is_upper_alphanumeric(s: &str) -> bool {
+    s.chars().all(|c| c.is_ascii_digit() || c.is_ascii_uppercase())
+}
+
+/// Luhn check of the ISIN, letters being expanded to two digits (A=10)
+fn isin_check_digit_is_valid(isin: &str) -> bool {
+    let digits: Vec<u32> = isin
+        .chars()
+        .flat_map(|c| {
+            let value = c.to_digit(36).unwrap_or(0);
+            if value < 10 { vec![value] } else { vec![value / 10, value % 10] }
+        })
+        .collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, digit)| {
+            if i % 2 == 1 {
+                let doubled = digit * 2;
+                doubled / 10 + doubled % 10
+            } else {
+                *digit
+            }
+        })
+        .sum();
+    sum % 10 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let isin = Security::isin("US4592001014").unwrap();
+        assert_eq!(isin.to_string(), "/isin/US4592001014");
+        assert_eq!(isin.topic(Some("//blp/mktdata")), "//blp/mktdata/isin/US4592001014");
+
+        let ticker = Security::ticker("IBM US Equity").unwrap();
+        assert_eq!(ticker.to_string(), "IBM US Equity");
+        assert_eq!(ticker.topic(Some("//blp/mktdata/")), "//blp/mktdata/ticker/IBM US Equity");
+        assert_eq!(ticker.topic(None), "IBM US Equity");
+
+        assert_eq!("/bbgid/BBG000BLNNH6".parse::<Security>().unwrap(), Security::Figi("BBG000BLNNH6".into()));
+        assert_eq!("/cusip/459200101".parse::<Security>().unwrap(), Security::Cusip("459200101".into()));
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Security::isin("US4592001015").is_err());
+        assert!(Security::isin("us4592001014").is_err());
+        assert!(Security::figi("XYZ000BLNNH6").is_err());
+        assert!(Security::cusip("45920010").is_err());
+        assert!(Security::ticker("/isin/US4592001014").is_err());
+    }
+}
@@ -1,7 +1,10 @@
 use serde::Deserialize;
-use crate::element::{Element, DataType, Elements};
+use crate::datetime::Datetime;
+use crate::element::{ByteArray, Element, DataType};
 use crate::name::Name;
-use serde::de::{Visitor, SeqAccess, DeserializeSeed, MapAccess};
+use serde::de::{Visitor, SeqAccess, DeserializeSeed, MapAccess, EnumAccess, VariantAccess, IntoDeserializer};
+use std::cell::Cell;
+use std::ffi::CStr;
 use std::fmt::{self, Display};
 use std::str::Utf8Error;
 
@@ -22,15 +25,27 @@ pub enum Error {
     ExpectedNull,
     ExpectedValue,
     ExpectedValidString(Utf8Error),
+    MissingField(&'static str),
     BlpApiError(crate::errors::Error),
 }
 
+thread_local! {
+    /// Whether the last error was created by `de::Error::missing_field`, which
+    /// `FieldValue` can't read from the error type of a generic deserializer
+    static MISSING_FIELD: Cell<bool> = const { Cell::new(false) };
+}
+
 impl std::error::Error for Error {}
 
 impl serde::de::Error for Error {
     fn custom<T: Display>(msg: T) -> Self {
         Error::Message(msg.to_string())
     }
+
+    fn missing_field(field: &'static str) -> Self {
+        MISSING_FIELD.with(|missing| missing.set(true));
+        Error::MissingField(field)
+    }
 }
 
 impl Display for Error {
@@ -44,6 +59,7 @@ impl Display for Error {
             Error::ExpectedValue => formatter.write_str("expected value in map"),
             Error::ExpectedArrayOrComplexType => formatter.write_str("expected array or complex type"),
             Error::ExpectedValidString(err) => formatter.write_fmt(format_args!("expected valid string: {}", err)),
+            Error::MissingField(field) => formatter.write_fmt(format_args!("missing field `{}`", field)),
             Error::BlpApiError(err) => formatter.write_fmt(format_args!("blpapi error: {}", err)),
         }
     }
@@ -54,7 +70,9 @@ pub enum FieldValue<T>
 {
     /// Field is present, containing value `T`
     Present(T),
-    /// Field is missing
+    /// Field is part of the element but has a null value
+    Null,
+    /// Field is missing from the element
     Missing,
 }
 
@@ -68,6 +86,7 @@ impl<T: Clone> Clone for FieldValue<T> {
     fn clone(&self) -> Self {
         match self {
             FieldValue::Present(x) => FieldValue::Present(x.clone()),
+            FieldValue::Null => FieldValue::Null,
             FieldValue::Missing => FieldValue::Missing,
         }
     }
@@ -84,7 +103,7 @@ impl<T> Into<Option<T>> for FieldValue<T> {
     fn into(self) -> Option<T> {
         match self {
             FieldValue::Present(inner) => Some(inner),
-            FieldValue::Missing => None,
+            FieldValue::Null | FieldValue::Missing => None,
         }
     }
 }
@@ -100,28 +119,255 @@ impl<'de, T: Deserialize<'de>> serde::Deserialize<'de> for FieldValue<T> {
     fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
         where D: serde::Deserializer<'de>
     {
-        match T::deserialize(deserializer) {
-            Ok(value) => Ok(FieldValue::Present(value)),
-            //Err(Error::ElementNotFoundAtField(_, _)) => Ok(FieldValue::Missing),
-            Err(error) => {
-                // we have to resort to this hack until specialization lands in stable
-                let formatted_error = format!("{}", error);
-                if formatted_error.starts_with("no element found in ") && formatted_error.contains(" with field ") {
-                    Ok(FieldValue::Missing)
-                } else {
-                    Err(error)
+        // missing elements are reported with `visit_none` and null ones with
+        // `visit_unit`, see `FieldBased` and `ElementDeserializer`
+        let visited = Cell::new(false);
+        let visitor = FieldValueVisitor { visited: &visited, _marker: std::marker::PhantomData };
+        MISSING_FIELD.with(|missing| missing.set(false));
+        match deserializer.deserialize_newtype_struct(FIELD_VALUE, visitor) {
+            Ok(value) => Ok(value),
+            // serde reports the fields absent from a map, e.g. for structs with
+            // flattened fields, with a deserializer failing with `missing_field`
+            // on any request: every other error is propagated
+            Err(_) if !visited.get() && MISSING_FIELD.with(Cell::take) => Ok(FieldValue::Missing),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Name of the newtype struct requested by `FieldValue`, so the deserializer
+/// of a missing or null element can report it rather than failing
+const FIELD_VALUE: &str = "$blpapi::FieldValue";
+
+struct FieldValueVisitor<'v, T> {
+    visited: &'v Cell<bool>,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<'de, 'v, T: Deserialize<'de>> Visitor<'de> for FieldValueVisitor<'v, T> {
+    type Value = FieldValue<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a field value")
+    }
+
+    fn visit_none<E: serde::de::Error>(self) -> std::result::Result<Self::Value, E> {
+        self.visited.set(true);
+        Ok(FieldValue::Missing)
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> std::result::Result<Self::Value, E> {
+        self.visited.set(true);
+        Ok(FieldValue::Null)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+        where D: serde::Deserializer<'de>
+    {
+        self.visited.set(true);
+        T::deserialize(deserializer).map(FieldValue::Present)
+    }
+}
+
+/// The sub-elements of a complex element as `(name, value)` pairs, in the
+/// order they appear in the element (a `HashMap` target loses this order)
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct OrderedPairs<T>(pub Vec<(Name, T)>);
+
+impl<T> OrderedPairs<T> {
+    pub fn into_inner(self) -> Vec<(Name, T)> {
+        self.0
+    }
+}
+
+impl<'de, T: Deserialize<'de>> serde::Deserialize<'de> for OrderedPairs<T> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+        where D: serde::Deserializer<'de>
+    {
+        struct PairsVisitor<T>(std::marker::PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for PairsVisitor<T> {
+            type Value = OrderedPairs<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a complex element")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+                where A: MapAccess<'de>
+            {
+                let mut pairs = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(pair) = map.next_entry()? {
+                    pairs.push(pair);
                 }
-            },
+                Ok(OrderedPairs(pairs))
+            }
+        }
+
+        deserializer.deserialize_map(PairsVisitor(std::marker::PhantomData))
+    }
+}
+
+/// Name of the newtype struct requested by `Name`: the deserializers of
+/// element names and values hand over their `Name` directly instead of a
+/// string, so maps keyed by `Name` don't allocate per field
+const NAME: &str = "$blpapi::Name";
+
+thread_local! {
+    /// The `Name` handed over to the `NameVisitor` of the current thread
+    static CURRENT_NAME: std::cell::Cell<Option<Name>> = const { std::cell::Cell::new(None) };
+}
+
+/// Hand `name` over to a visitor requested by `Name::deserialize`
+fn visit_name<'de, V: Visitor<'de>>(name: Name, visitor: V) -> Result<V::Value> {
+    CURRENT_NAME.with(|current| current.set(Some(name)));
+    let result = visitor.visit_unit();
+    CURRENT_NAME.with(|current| current.set(None));
+    result
+}
+
+struct NameVisitor;
+
+impl<'de> Visitor<'de> for NameVisitor {
+    type Value = Name;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a name")
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> std::result::Result<Name, E> {
+        CURRENT_NAME
+            .with(|current| current.take())
+            .ok_or_else(|| E::invalid_type(serde::de::Unexpected::Unit, &self))
+    }
+
+    fn visit_str<E: serde::de::Error>(self, value: &str) -> std::result::Result<Name, E> {
+        if value.contains('\0') {
+            return Err(E::invalid_value(serde::de::Unexpected::Str(value), &self));
+        }
+        Ok(Name::new(value))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> std::result::Result<Name, D::Error>
+        where D: serde::Deserializer<'de>
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+/// Names can be used as map keys, e.g. `HashMap<Name, T>`, to avoid
+/// allocating a `String` per field when deserializing hot paths such as
+/// tick data. Use `Interned` keys to share the strings instead.
+impl<'de> serde::Deserialize<'de> for Name {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+        where D: serde::Deserializer<'de>
+    {
+        deserializer.deserialize_newtype_struct(NAME, NameVisitor)
+    }
+}
+
+/// A datetime element value as blpapi holds it, keeping the parts which
+/// are set and its offset, where chrono types need one representation
+#[derive(Clone, Debug, Default)]
+pub struct BbgDatetime(pub Datetime);
+
+impl BbgDatetime {
+    pub fn into_inner(self) -> Datetime {
+        self.0
+    }
+}
+
+/// Name of the newtype struct requested by `BbgDatetime`, so the
+/// deserializer of a datetime element hands over its `Datetime` directly
+const DATETIME: &str = "$blpapi::Datetime";
+
+thread_local! {
+    /// The `Datetime` handed over to the `DatetimeVisitor` of the current thread
+    static CURRENT_DATETIME: std::cell::Cell<Option<Datetime>> = const { std::cell::Cell::new(None) };
+}
+
+/// Hand `datetime` over to a visitor requested by `BbgDatetime::deserialize`
+fn visit_datetime<'de, V: Visitor<'de>>(datetime: Datetime, visitor: V) -> Result<V::Value> {
+    CURRENT_DATETIME.with(|current| current.set(Some(datetime)));
+    let result = visitor.visit_unit();
+    CURRENT_DATETIME.with(|current| current.set(None));
+    result
+}
+
+struct DatetimeVisitor;
+
+impl<'de> Visitor<'de> for DatetimeVisitor {
+    type Value = BbgDatetime;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a datetime element")
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> std::result::Result<BbgDatetime, E> {
+        CURRENT_DATETIME
+            .with(|current| current.take())
+            .map(BbgDatetime)
+            .ok_or_else(|| E::invalid_type(serde::de::Unexpected::Unit, &self))
+    }
+}
+
+/// Only datetime elements can be deserialized, use chrono types for
+/// other sources such as JSON
+impl<'de> serde::Deserialize<'de> for BbgDatetime {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+        where D: serde::Deserializer<'de>
+    {
+        deserializer.deserialize_newtype_struct(DATETIME, DatetimeVisitor)
+    }
+}
+
+/// A string value shared through the interner of the current thread
+/// (`crate::interner::with_thread_interner`), for fields repeating the same
+/// values such as tickers or condition codes
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+pub struct Interned(pub std::sync::Arc<str>);
+
+impl std::ops::Deref for Interned {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Interned {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+        where D: serde::Deserializer<'de>
+    {
+        struct InternedVisitor;
+
+        impl<'de> Visitor<'de> for InternedVisitor {
+            type Value = Interned;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> std::result::Result<Interned, E> {
+                Ok(Interned(crate::interner::with_thread_interner(|interner| interner.intern(value))))
+            }
         }
+
+        deserializer.deserialize_str(InternedVisitor)
     }
 }
 
-pub struct ElementDeserializer<'e> {
-    input: Element<'e>,
+pub struct ElementDeserializer<'de> {
+    input: Element<'de>,
     value_index: Option<usize>,
 }
 
-pub fn from_element<'de, T>(input: Element) -> Result<T>
+/// Deserialize `T` from `input`
+///
+/// String values can be borrowed from the element, e.g. as `&'de str` or
+/// with `#[serde(borrow)] Cow<'de, str>`, as long as `T` doesn't outlive
+/// the message of `input`.
+pub fn from_element<'de, T>(input: Element<'de>) -> Result<T>
     where T: Deserialize<'de>
 {
     let mut deserializer = ElementDeserializer { input, value_index: None };
@@ -165,7 +411,33 @@ macro_rules! impl_deserialize {
     };
 }
 
-impl ElementDeserializer<'_> {
+/// Extend the lifetime of a sub-element to the one of its root element
+///
+/// # Safety
+///
+/// `element` must be a sub-element (or value) of an element living for
+/// `'de`: sub-elements share the storage of their root element, so they,
+/// and their string values, are valid for as long as the root element is.
+unsafe fn rebind<'de>(element: Element<'_>) -> Element<'de> {
+    Element { ptr: element.ptr, _marker: std::marker::PhantomData }
+}
+
+impl<'de> ElementDeserializer<'de> {
+    /// # Safety
+    ///
+    /// `input` must be a sub-element of the root element, see `rebind`
+    unsafe fn new(input: Element<'_>, value_index: Option<usize>) -> Self {
+        ElementDeserializer { input: rebind(input), value_index }
+    }
+
+    /// The string value at `index`, borrowed for the lifetime of the root
+    /// element
+    fn borrowed_cstr(&self, index: usize) -> Result<&'de CStr> {
+        let value = self.input.get_at::<&CStr>(index).map_err(Error::BlpApiError)?;
+        // SAFETY: see `rebind`, the value lives in the root element storage
+        Ok(unsafe { &*(value as *const CStr) })
+    }
+
     fn is_null(&self) -> Result<bool> {
         match self.value_index {
             Some(index) => self.input.is_null_value(index).map_err(|err| Error::BlpApiError(err)),
@@ -174,7 +446,7 @@ impl ElementDeserializer<'_> {
     }
 }
 
-impl<'de, 'a> serde::Deserializer<'de> for &'a mut ElementDeserializer<'a> {
+impl<'de> serde::Deserializer<'de> for &mut ElementDeserializer<'de> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
@@ -183,16 +455,25 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut ElementDeserializer<'a> {
             return visitor.visit_none();
         }
 
+        if self.value_index.is_none() && self.input.is_array() {
+            return self.deserialize_seq(visitor);
+        }
+
         match self.input.data_type() {
             DataType::Bool => self.deserialize_bool(visitor),
-            DataType::Char => self.deserialize_i8(visitor),
+            DataType::Char => self.deserialize_char(visitor),
+            DataType::Byte => self.deserialize_u8(visitor),
+            DataType::ByteArray => self.deserialize_byte_buf(visitor),
             DataType::Int32 => self.deserialize_i32(visitor),
             DataType::Int64 => self.deserialize_i64(visitor),
             DataType::Float32 => self.deserialize_f32(visitor),
             DataType::Float64 => self.deserialize_f64(visitor),
-            DataType::String => self.deserialize_string(visitor),
-            DataType::Sequence => self.deserialize_seq(visitor),
-            DataType::Choice => self.deserialize_seq(visitor),
+            // as a string, so decimal types don't lose precision
+            DataType::Decimal => self.deserialize_string(visitor),
+            DataType::String => self.deserialize_str(visitor),
+            DataType::Date | DataType::Time | DataType::DateTime => self.deserialize_string(visitor),
+            // sub-elements are named, keep their names, e.g. for flattened structs
+            DataType::Sequence | DataType::Choice => self.deserialize_map(visitor),
             _ => Err(Error::UnsupportedType),
         }
     }
@@ -202,8 +483,8 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut ElementDeserializer<'a> {
     impl_deserialize!(deserialize_i32(self) => visit_i32(i32));
     impl_deserialize!(deserialize_i64(self) => visit_i64(i64));
 
-    impl_deserialize!(deserialize_u8(self) => visit_u8(i8 as u8));
-    impl_deserialize!(deserialize_u16(self) => Err(Error::UnsupportedType));
+    impl_deserialize!(deserialize_u8(self) => visit_u8(u8));
+    impl_deserialize!(deserialize_u16(self) => visit_u16(u16));
     impl_deserialize!(deserialize_u32(self) => visit_u32(i32 as u32));
     impl_deserialize!(deserialize_u64(self) => visit_u64(i64 as u64));
 
@@ -211,13 +492,67 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut ElementDeserializer<'a> {
     impl_deserialize!(deserialize_f64(self) => visit_f64(f64));
 
     impl_deserialize!(deserialize_bool(self) => visit_bool(bool));
-//impl_deserialize!(deserialize_char(self) => visit_char(i8 as char);
-    impl_deserialize!(deserialize_char(self) => Err(Error::UnsupportedType));
-    impl_deserialize!(deserialize_str(self) => visit_string(String));
-    impl_deserialize!(deserialize_string(self) => visit_string(String));
+    fn deserialize_char<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
+        V: Visitor<'de> {
+        let value = self.input
+            .get_at::<i8>(self.value_index.unwrap_or(0))
+            .map_err(Error::BlpApiError)?;
+        visitor.visit_char(char::from(value as u8))
+    }
 
-    impl_deserialize!(deserialize_bytes(self) => Err(Error::UnsupportedType));
-    impl_deserialize!(deserialize_byte_buf(self) => Err(Error::UnsupportedType));
+    fn deserialize_str<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
+        V: Visitor<'de> {
+        // other types are converted to temporary strings, which cannot be borrowed
+        if self.input.data_type() == DataType::String {
+            let value = self.borrowed_cstr(self.value_index.unwrap_or(0))?;
+            match value.to_str() {
+                Ok(value) => visitor.visit_borrowed_str(value),
+                // invalid characters are replaced, as for `String` values
+                Err(_) => visitor.visit_string(value.to_string_lossy().into_owned()),
+            }
+        } else {
+            self.deserialize_string(visitor)
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
+        V: Visitor<'de> {
+        let index = self.value_index.unwrap_or(0);
+        match self.input.data_type() {
+            // e.g. for chrono types, which are deserialized from strings:
+            // naive targets need elements without offset, see `BbgDatetime`
+            DataType::Date | DataType::Time | DataType::DateTime => visitor.visit_string(
+                self.input
+                    .get_at::<Datetime>(index)
+                    .map_err(Error::BlpApiError)?
+                    .to_iso8601()
+            ),
+            _ => visitor.visit_string(
+                self.input
+                    .get_at::<String>(index)
+                    .map_err(Error::BlpApiError)?
+            ),
+        }
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
+        V: Visitor<'de> {
+        visitor.visit_bytes(
+            self.input
+                .get_at::<&[u8]>(self.value_index.unwrap_or(0))
+                .map_err(Error::BlpApiError)?
+        )
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
+        V: Visitor<'de> {
+        visitor.visit_byte_buf(
+            self.input
+                .get_at::<ByteArray>(self.value_index.unwrap_or(0))
+                .map_err(Error::BlpApiError)?
+                .0
+        )
+    }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
         V: Visitor<'de> {
@@ -242,9 +577,19 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut ElementDeserializer<'a> {
         self.deserialize_unit(visitor)
     }
 
-    fn deserialize_newtype_struct<V>(self, _: &'static str, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
         V: Visitor<'de> {
-        visitor.visit_newtype_struct(self)
+        if name == FIELD_VALUE && self.is_null().unwrap_or(false) {
+            visitor.visit_unit()
+        } else if name == NAME && !self.input.is_complex_type() {
+            let value = self.input.get_at::<Name>(self.value_index.unwrap_or(0)).map_err(Error::BlpApiError)?;
+            visit_name(value, visitor)
+        } else if name == DATETIME && matches!(self.input.data_type(), DataType::Date | DataType::Time | DataType::DateTime) {
+            let value = self.input.get_at::<Datetime>(self.value_index.unwrap_or(0)).map_err(Error::BlpApiError)?;
+            visit_datetime(value, visitor)
+        } else {
+            visitor.visit_newtype_struct(self)
+        }
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
@@ -278,11 +623,19 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut ElementDeserializer<'a> {
 
     fn deserialize_map<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
         V: Visitor<'de> {
-        if !self.input.is_complex_type() {
+        let element = match self.value_index {
+            Some(index) => self.input
+                .get_at::<Element>(index)
+                .map_err(|err| Error::BlpApiError(err))?,
+            None => self.input.clone(),
+        };
+        if !element.is_complex_type() {
             return Err(Error::UnsupportedType);
         }
 
-        visitor.visit_map(ElementsIterator { it: self.input.elements(), current_element: None })
+        let indices = 0..element.num_elements();
+        // SAFETY: the element is the input or one of its values
+        visitor.visit_map(ElementsIterator { element: unsafe { rebind(element) }, indices, current_element: None })
     }
 
     fn deserialize_struct<V>(self, _: &'static str, fields: &'static [&'static str], visitor: V) -> Result<<V as Visitor<'de>>::Value> where
@@ -293,12 +646,41 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut ElementDeserializer<'a> {
                 .map_err(|err| Error::BlpApiError(err))?,
             None => self.input.clone(),
         };
-        visitor.visit_seq(FieldBased { element, fields: fields.iter() })
+        // SAFETY: the element is the input or one of its values
+        visitor.visit_seq(FieldBased { element: unsafe { rebind(element) }, fields: fields.iter() })
     }
 
-    fn deserialize_enum<V>(self, _: &'static str, variants: &'static [&'static str], visitor: V) -> Result<<V as Visitor<'de>>::Value> where
+    /// Enums are externally tagged: the variant is picked by the selection
+    /// of a choice element, by the name of a sequence element (e.g. the
+    /// message type for `message.element()`) or by the value of a simple
+    /// element for unit variants
+    fn deserialize_enum<V>(self, _: &'static str, _: &'static [&'static str], visitor: V) -> Result<<V as Visitor<'de>>::Value> where
         V: Visitor<'de> {
-        visitor.visit_seq(FieldBased { element: self.input.clone(), fields: variants.iter() })
+        let element = match self.value_index {
+            Some(index) => match self.input.data_type() {
+                DataType::Sequence | DataType::Choice => self.input
+                    .get_at::<Element>(index)
+                    .map_err(Error::BlpApiError)?,
+                _ => {
+                    let value = self.input.get_at::<String>(index).map_err(Error::BlpApiError)?;
+                    return visitor.visit_enum(value.into_deserializer());
+                },
+            },
+            None => self.input.clone(),
+        };
+        match element.data_type() {
+            DataType::Choice => {
+                let selection = element.get_choice().map_err(Error::BlpApiError)?;
+                // SAFETY: the selection is a sub-element of the input
+                visitor.visit_enum(VariantElement { element: unsafe { rebind(selection) } })
+            },
+            // SAFETY: the element is the input or one of its values
+            DataType::Sequence => visitor.visit_enum(VariantElement { element: unsafe { rebind(element) } }),
+            _ => {
+                let value = element.get_at::<String>(0).map_err(Error::BlpApiError)?;
+                visitor.visit_enum(value.into_deserializer())
+            },
+        }
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
@@ -319,7 +701,11 @@ struct NameDeserializer {
 impl<'de, 'a> serde::Deserializer<'de> for &'a mut NameDeserializer {
     type Error = Error;
 
-    impl_deserialize!(deserialize_any(self) => Err(Error::UnsupportedType));
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value> where
+        V: Visitor<'de> {
+        self.deserialize_str(visitor)
+    }
+
     impl_deserialize!(deserialize_bool(self) => Err(Error::UnsupportedType));
     impl_deserialize!(deserialize_i8(self) => Err(Error::UnsupportedType));
     impl_deserialize!(deserialize_i16(self) => Err(Error::UnsupportedType));
@@ -339,12 +725,13 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut NameDeserializer {
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value> where
         V: Visitor<'de> {
-        visitor.visit_str(
-            self.input
-                .to_cstr()
-                .to_str()
-                .map_err(|err| Error::ExpectedValidString(err))?
-        )
+        let value = self.input
+            .to_cstr()
+            .to_str()
+            .map_err(|err| Error::ExpectedValidString(err))?;
+        // SAFETY: names are never destroyed, so their strings can be borrowed
+        // for any lifetime
+        visitor.visit_borrowed_str(unsafe { &*(value as *const str) })
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value> where
@@ -360,12 +747,28 @@ impl<'de, 'a> serde::Deserializer<'de> for &'a mut NameDeserializer {
 
     impl_deserialize!(deserialize_seq(self) => Err(Error::UnsupportedType));
     impl_deserialize!(deserialize_unit_struct(self, &'static str) => Err(Error::UnsupportedType));
-    impl_deserialize!(deserialize_newtype_struct(self, &'static str) => Err(Error::UnsupportedType));
     impl_deserialize!(deserialize_map(self) => Err(Error::UnsupportedType));
     impl_deserialize!(deserialize_tuple(self, usize) => Err(Error::UnsupportedType));
     impl_deserialize!(deserialize_tuple_struct(self, &'static str, usize) => Err(Error::UnsupportedType));
-    impl_deserialize!(deserialize_identifier(self) => Err(Error::UnsupportedType));
-    impl_deserialize!(deserialize_ignored_any(self) => Err(Error::UnsupportedType));
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value> where
+        V: Visitor<'de> {
+        if name == NAME {
+            visit_name(self.input, visitor)
+        } else {
+            Err(Error::UnsupportedType)
+        }
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value> where
+        V: Visitor<'de> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value> where
+        V: Visitor<'de> {
+        visitor.visit_unit()
+    }
     impl_deserialize!(deserialize_struct(self, &'static str, &'static [&'static str]) => Err(Error::UnsupportedType));
     impl_deserialize!(deserialize_enum(self, &'static str, &'static [&'static str]) => Err(Error::UnsupportedType));
 }
@@ -408,7 +811,6 @@ impl<'de, 'a, F> serde::Deserializer<'de> for &'a mut ErrorDeserializer<F>
     impl_deserialize!(deserialize_string(self) => Err(self.generate_error()));
     impl_deserialize!(deserialize_seq(self) => Err(self.generate_error()));
     impl_deserialize!(deserialize_unit_struct(self, &'static str) => Err(self.generate_error()));
-    impl_deserialize!(deserialize_newtype_struct(self, &'static str) => Err(self.generate_error()));
     impl_deserialize!(deserialize_map(self) => Err(self.generate_error()));
     impl_deserialize!(deserialize_tuple(self, usize) => Err(self.generate_error()));
     impl_deserialize!(deserialize_tuple_struct(self, &'static str, usize) => Err(self.generate_error()));
@@ -416,21 +818,33 @@ impl<'de, 'a, F> serde::Deserializer<'de> for &'a mut ErrorDeserializer<F>
     impl_deserialize!(deserialize_ignored_any(self) => Err(self.generate_error()));
     impl_deserialize!(deserialize_struct(self, &'static str, &'static [&'static str]) => Err(self.generate_error()));
     impl_deserialize!(deserialize_enum(self, &'static str, &'static [&'static str]) => Err(self.generate_error()));
+
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<<V as Visitor<'de>>::Value> where
+        V: Visitor<'de> {
+        if name == FIELD_VALUE {
+            visitor.visit_none()
+        } else {
+            Err(self.generate_error())
+        }
+    }
 }
 
-struct ElementsIterator<'e> {
-    it: Elements<'e>,
-    current_element: Option<Element<'e>>,
+struct ElementsIterator<'de> {
+    element: Element<'de>,
+    indices: std::ops::Range<usize>,
+    current_element: Option<Element<'de>>,
 }
 
-impl<'e, 'de> MapAccess<'de> for ElementsIterator<'e> {
+impl<'de> MapAccess<'de> for ElementsIterator<'de> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<<K as DeserializeSeed<'de>>::Value>> where
         K: DeserializeSeed<'de> {
-        match self.it.next() {
-            Some(element) => {
-                self.current_element = Some(element.clone());
+        match self.indices.next() {
+            Some(index) => {
+                let element = self.element.get_element_at(index).map_err(Error::BlpApiError)?;
+                // SAFETY: the element is a sub-element of `self.element`
+                self.current_element = Some(unsafe { rebind(element.clone()) });
                 let mut de = NameDeserializer { input: element.name() };
                 seed.deserialize(&mut de).map(Some)
             },
@@ -453,17 +867,17 @@ impl<'e, 'de> MapAccess<'de> for ElementsIterator<'e> {
     }
 
     fn size_hint(&self) -> Option<usize> {
-        self.it.size_hint().1
+        self.indices.size_hint().1
     }
 }
 
-struct FieldBased<'e> {
-    element: Element<'e>,
+struct FieldBased<'de> {
+    element: Element<'de>,
     // TODO: this should use Name instead
     fields: std::slice::Iter<'static, &'static str>,
 }
 
-impl<'a, 'de> SeqAccess<'de> for FieldBased<'a> {
+impl<'de> SeqAccess<'de> for FieldBased<'de> {
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<<T as DeserializeSeed<'de>>::Value>> where
@@ -471,20 +885,17 @@ impl<'a, 'de> SeqAccess<'de> for FieldBased<'a> {
     {
         match self.fields.next() {
             Some(field) => {
-                if !self.element.has_element(field, false) {
-                    let mut de = ErrorDeserializer {
-                        error_generator_fn: || Error::ElementNotFoundAtField(format!("{:?}", self.element), Name::new(field)),
-                    };
-                    return seed.deserialize(&mut de).map(Some);
-                }
-
-                match self.element.get_element(field) {
-                    Ok(element) => {
-                        let mut de = ElementDeserializer { input: element, value_index: None };
+                match self.element.try_get_element(field) {
+                    Some(element) => {
+                        // SAFETY: the element is a sub-element of `self.element`
+                        let mut de = unsafe { ElementDeserializer::new(element, None) };
                         seed.deserialize(&mut de).map(Some)
                     },
-                    Err(err) => {
-                        Err(Error::BlpApiError(err))
+                    None => {
+                        let mut de = ErrorDeserializer {
+                            error_generator_fn: || Error::ElementNotFoundAtField(format!("{:?}", self.element), Name::new(field)),
+                        };
+                        seed.deserialize(&mut de).map(Some)
                     },
                 }
             },
@@ -497,13 +908,61 @@ impl<'a, 'de> SeqAccess<'de> for FieldBased<'a> {
     }
 }
 
-struct IndexBased<'a> {
-    de: &'a mut ElementDeserializer<'a>,
+/// The variant of an enum, named after `element`, with the content of
+/// `element`
+struct VariantElement<'de> {
+    element: Element<'de>,
+}
+
+impl<'de> EnumAccess<'de> for VariantElement<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self)> where
+        V: DeserializeSeed<'de>
+    {
+        let mut de = NameDeserializer { input: self.element.name() };
+        let variant = seed.deserialize(&mut de)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantElement<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value> where
+        T: DeserializeSeed<'de>
+    {
+        let mut de = ElementDeserializer { input: self.element, value_index: None };
+        seed.deserialize(&mut de)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value> where
+        V: Visitor<'de>
+    {
+        let mut de = ElementDeserializer { input: self.element, value_index: None };
+        serde::Deserializer::deserialize_tuple(&mut de, len, visitor)
+    }
+
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value> where
+        V: Visitor<'de>
+    {
+        let mut de = ElementDeserializer { input: self.element, value_index: None };
+        serde::Deserializer::deserialize_struct(&mut de, "", fields, visitor)
+    }
+}
+
+struct IndexBased<'a, 'de> {
+    de: &'a mut ElementDeserializer<'de>,
     indices: std::ops::Range<usize>,
     use_values: bool,
 }
 
-impl<'de, 'a> SeqAccess<'de> for IndexBased<'a> {
+impl<'de, 'a> SeqAccess<'de> for IndexBased<'a, 'de> {
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<<T as DeserializeSeed<'de>>::Value>> where
@@ -517,7 +976,8 @@ impl<'de, 'a> SeqAccess<'de> for IndexBased<'a> {
                 } else {
                     match self.de.input.get_element_at(index) {
                         Ok(element) => {
-                            let mut de = ElementDeserializer { input: element, value_index: None };
+                            // SAFETY: the element is a sub-element of the input
+                            let mut de = unsafe { ElementDeserializer::new(element, None) };
                             seed.deserialize(&mut de).map(Some)
                         },
                         Err(err) => {
@@ -633,6 +1093,30 @@ mod tests {
         Ok(())
     }
 
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum AdminMessage {
+        SubscriptionStarted {
+            #[serde(rename="resubscriptionId")]
+            resubscription_id: i32,
+        },
+        SubscriptionTerminated {
+            reason: String,
+        },
+    }
+
+    #[test]
+    fn test_enum_by_message_type() -> Result<(), Error> {
+        let event = EventBuilder::new(EventType::SubscriptionData)?
+            .append_message_from_json(Name::new("SubscriptionStarted"), None, r#"{ "resubscriptionId": 123 }"#)?
+            .build();
+
+        let msg = event.messages().next().unwrap();
+        let result = from_element::<AdminMessage>(msg.element()).unwrap();
+        assert_eq!(result, AdminMessage::SubscriptionStarted { resubscription_id: 123 });
+
+        Ok(())
+    }
+
     #[test]
     fn test_subelement() -> Result<(), Error> {
         let msg_contents = r#"
@@ -760,6 +1244,163 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_name_keys() -> Result<(), Error> {
+        let msg_contents = r#"
+            {
+                "receivedFrom": { "address": "12.34.56.78:8194" },
+                "reason": "TestUtil"
+            }
+        "#;
+
+        let event = EventBuilder::new(EventType::SubscriptionData)?
+            .append_message_from_json(Name::new("SubscriptionStarted"), None, msg_contents)?
+            .build();
+
+        let msg = event.messages().next().unwrap();
+        let fields = from_element::<HashMap<Name, Interned>>(msg.element().get_element("receivedFrom").unwrap()).unwrap();
+        assert_eq!(&*fields[&Name::new("address")], "12.34.56.78:8194");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_borrowed_str() -> Result<(), Error> {
+        let msg_contents = r#"
+            {
+                "receivedFrom": { "address": "12.34.56.78:8194" },
+                "reason": "TestUtil"
+            }
+        "#;
+
+        let event = EventBuilder::new(EventType::SubscriptionData)?
+            .append_message_from_json(Name::new("SubscriptionStarted"), None, msg_contents)?
+            .build();
+
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct Borrowed<'a> {
+            #[serde(rename="receivedFrom")]
+            received_from: HashMap<&'a str, &'a str>,
+            #[serde(borrow)]
+            reason: std::borrow::Cow<'a, str>,
+        }
+
+        let msg = event.messages().next().unwrap();
+        let borrowed = from_element::<Borrowed>(msg.element()).unwrap();
+        assert_eq!(borrowed.received_from["address"], "12.34.56.78:8194");
+        assert!(matches!(borrowed.reason, std::borrow::Cow::Borrowed("TestUtil")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ordered_pairs() -> Result<(), Error> {
+        let event = build_subscription_data_event(r#"
+            {
+                "exceptions": [
+                    {
+                        "fieldId": "field1",
+                        "reason": {
+                            "source":      "TestUtil",
+                            "errorCode":   -1,
+                            "category":    "CATEGORY",
+                            "description": "for testing",
+                            "subcategory": "SUBCATEGORY"
+                        }
+                    }
+                ]
+            }
+        "#)?;
+
+        let msg = event.messages().next().unwrap();
+        let element = msg.element();
+        let exceptions = element.get_element("exceptions").unwrap();
+        let exception = exceptions.values::<Element>().next().unwrap();
+        let pairs = from_element::<OrderedPairs<String>>(exception.get_element("reason").unwrap())
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(
+            pairs,
+            [
+                ("source", "TestUtil"),
+                ("errorCode", "-1"),
+                ("category", "CATEGORY"),
+                ("description", "for testing"),
+                ("subcategory", "SUBCATEGORY"),
+            ].iter()
+                .map(|(k, v)| (Name::new(k), v.to_string()))
+                .collect::<Vec<_>>(),
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_flatten() -> Result<(), Error> {
+        let event = build_subscription_data_event(r#"
+            {
+                "exceptions": [
+                    {
+                        "fieldId": "field1",
+                        "reason": {
+                            "source":      "TestUtil",
+                            "errorCode":   -1,
+                            "category":    "CATEGORY",
+                            "description": "for testing",
+                            "subcategory": "SUBCATEGORY"
+                        }
+                    }
+                ],
+                "streamIds": ["1", "2"]
+            }
+        "#)?;
+
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct Source {
+            source: String,
+            #[serde(rename="errorCode")]
+            error_code: i32,
+        }
+
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct FlattenedReason {
+            #[serde(flatten)]
+            source: Source,
+            #[serde(flatten)]
+            others: HashMap<String, String>,
+        }
+
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct FlattenedException {
+            #[serde(rename="fieldId")]
+            field_id: String,
+            reason: FlattenedReason,
+        }
+
+        #[derive(Deserialize, PartialEq, Debug)]
+        struct Started {
+            exceptions: Vec<FlattenedException>,
+            #[serde(flatten)]
+            others: HashMap<String, FieldValue<Vec<String>>>,
+        }
+
+        let msg = event.messages().next().unwrap();
+        let started = from_element::<Started>(msg.element()).unwrap();
+
+        let exception = &started.exceptions[0];
+        assert_eq!(exception.field_id, "field1");
+        assert_eq!(exception.reason.source, Source { source: "TestUtil".to_string(), error_code: -1 });
+        assert_eq!(exception.reason.others.get("category").map(String::as_str), Some("CATEGORY"));
+        assert!(!exception.reason.others.contains_key("source"));
+        assert_eq!(
+            started.others.get("streamIds"),
+            Some(&FieldValue::Present(vec!["1".to_string(), "2".to_string()])),
+        );
+
+        Ok(())
+    }
+
     fn build_subscription_data_event(msg_contents: &str) -> Result<Event, Error> {
         let event = EventBuilder::new(EventType::SubscriptionData)?
             .append_message_from_json(Name::new("SubscriptionStarted"), None, msg_contents)?
@@ -801,11 +1442,80 @@ mod tests {
                 exceptions: FieldValue::Missing,
                 resubscription_id: FieldValue::Present(123),
                 stream_ids: FieldValue::Present(vec!["123".to_string(), "456".to_string()]),
-                received_from: FieldValue::Present(None),
+                received_from: FieldValue::Null,
                 reason: FieldValue::Present("TestUtil".to_string()),
             }
         );
 
         Ok(())
     }
+
+    fn build_tick_event(json: &str) -> Result<Event, Error> {
+        crate::testutil::test_event(EventType::SubscriptionData, "Tick", None, json)
+    }
+
+    #[test]
+    fn test_bbg_datetime() -> Result<(), Error> {
+        #[derive(Deserialize)]
+        struct Tick {
+            time: BbgDatetime,
+        }
+
+        for (json, offset) in [
+            (r#"{ "time": "2020-01-01T08:05:10.250" }"#, None),
+            (r#"{ "time": "2020-01-01T08:05:10.250+01:00" }"#, Some(60)),
+        ] {
+            let event = build_tick_event(json)?;
+            let msg = event.messages().next().unwrap();
+            let expected = msg.element().get_element("time")?.value::<Datetime>()?;
+            let tick = from_element::<Tick>(msg.element()).unwrap();
+            assert_eq!(tick.time.0.offset(), offset);
+            assert_eq!(format!("{:?}", tick.time.0), format!("{:?}", expected));
+            assert_eq!(tick.time.0.to_iso8601(), expected.to_iso8601());
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "dates")]
+    #[test]
+    fn test_chrono_datetime() -> Result<(), Error> {
+        use chrono::{DateTime, FixedOffset, NaiveDateTime};
+        use std::convert::TryInto;
+
+        #[derive(Deserialize)]
+        struct Tick<T> {
+            time: T,
+        }
+
+        let event = build_tick_event(r#"{ "time": "2020-01-01T08:05:10.250" }"#)?;
+        let msg = event.messages().next().unwrap();
+        let expected: NaiveDateTime = msg.element().get_element("time")?.value::<Datetime>()?.try_into().unwrap();
+        let tick = from_element::<Tick<NaiveDateTime>>(msg.element()).unwrap();
+        assert_eq!(tick.time, expected);
+
+        let event = build_tick_event(r#"{ "time": "2020-01-01T08:05:10.250+01:00" }"#)?;
+        let msg = event.messages().next().unwrap();
+        let expected: DateTime<FixedOffset> = msg.element().get_element("time")?.value::<Datetime>()?.try_into().unwrap();
+        let tick = from_element::<Tick<DateTime<FixedOffset>>>(msg.element()).unwrap();
+        assert_eq!(tick.time, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_field_value_errors() {
+        #[derive(Deserialize, Debug)]
+        struct Fields {
+            value: FieldValue<i32>,
+        }
+
+        let empty = serde::de::value::MapDeserializer::<_, super::Error>::new(std::iter::empty::<(&str, i32)>());
+        let fields = Fields::deserialize(empty).unwrap();
+        assert!(matches!(fields.value, FieldValue::Missing));
+
+        let invalid = IntoDeserializer::<super::Error>::into_deserializer(true);
+        let error = FieldValue::<i32>::deserialize(invalid).unwrap_err();
+        assert!(!matches!(error, super::Error::MissingField(_)));
+    }
 }
@@ -1 +1,2 @@
-pub mod deserialization;
\ No newline at end of file
+pub mod deserialization;
+pub mod serialization;
@@ -0,0 +1,536 @@
+use serde::Serialize;
+use serde::ser::{self, Impossible};
+use crate::element::{Element, SetValue};
+use crate::request::Request;
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+pub use super::deserialization::{Error, Result};
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Message(msg.to_string())
+    }
+}
+
+/// Where a value is written, relative to `ElementSerializer::element`
+enum Slot {
+    /// The element itself, only complex values can be written there
+    Root,
+    /// The sub-element with the given name
+    Field(String),
+    /// A new value appended to the (array) element
+    Append,
+}
+
+pub struct ElementSerializer<'e> {
+    element: Element<'e>,
+    slot: Slot,
+}
+
+/// Write `value` into `element`.
+///
+/// Struct fields and map entries are written to the sub-elements of the same
+/// name, sequences are appended to array elements and `None` values are
+/// skipped, leaving the element untouched.
+pub fn to_element<T>(value: &T, element: &mut Element) -> Result<()>
+    where T: Serialize
+{
+    let serializer = ElementSerializer { element: element.clone(), slot: Slot::Root };
+    value.serialize(serializer)
+}
+
+/// Write `value` into the root element of `request`
+pub fn apply_to_request<T>(value: &T, request: &mut Request) -> Result<()>
+    where T: Serialize
+{
+    to_element(value, &mut request.element())
+}
+
+/// Sub-elements are owned by the root element, not by the element they are
+/// retrieved from, so they can outlive the latter
+fn detach<'e>(element: Element) -> Element<'e> {
+    Element { ptr: element.ptr, _marker: PhantomData }
+}
+
+impl<'e> ElementSerializer<'e> {
+    fn set_value<V: SetValue>(self, value: V) -> Result<()> {
+        let mut element = self.element;
+        match self.slot {
+            Slot::Root => return Err(Error::UnsupportedType),
+            Slot::Field(name) => element.set(&name, value),
+            Slot::Append => element.append(value),
+        }.map_err(Error::BlpApiError)
+    }
+
+    fn complex(self) -> Result<Element<'e>> {
+        let mut element = self.element;
+        match self.slot {
+            Slot::Root => Ok(element),
+            Slot::Field(name) => element.get_element(&name).map(detach),
+            Slot::Append => element.append_element().map(detach),
+        }.map_err(Error::BlpApiError)
+    }
+
+    fn array(self) -> Result<Element<'e>> {
+        match self.slot {
+            Slot::Field(name) => self.element.get_element(&name).map(detach).map_err(Error::BlpApiError),
+            // arrays of arrays and top-level arrays do not exist in bloomberg schemas
+            Slot::Root | Slot::Append => Err(Error::ExpectedArrayOrComplexType),
+        }
+    }
+}
+
+impl<'e> ser::Serializer for ElementSerializer<'e> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = SeqSerializer<'e>;
+    type SerializeTuple = SeqSerializer<'e>;
+    type SerializeTupleStruct = SeqSerializer<'e>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = MapSerializer<'e>;
+    type SerializeStruct = StructSerializer<'e>;
+    type SerializeStructVariant = StructSerializer<'e>;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.set_value(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.set_value(v as i32)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.set_value(v as i32)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.set_value(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.set_value(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.set_value(v as i32)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.set_value(v as i32)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.set_value(v as i64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        let v = i64::try_from(v).map_err(|_| Error::Message(format!("{} does not fit in an int64", v)))?;
+        self.set_value(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.set_value(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        self.set_value(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        let mut buffer = [0; 4];
+        self.set_value(&*v.encode_utf8(&mut buffer))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.set_value(v)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<()> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str) -> Result<()> {
+        // enumerations are set by their string value
+        self.set_value(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        // choices are set through their selected alternative
+        let element = self.complex()?;
+        value.serialize(ElementSerializer { element, slot: Slot::Field(variant.to_string()) })
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(SeqSerializer { array: self.array()? })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(MapSerializer { element: self.complex()?, key: None })
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Ok(StructSerializer { element: self.complex()? })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        let element = self.complex()?;
+        let element = element.get_element(variant).map(detach).map_err(Error::BlpApiError)?;
+        Ok(StructSerializer { element })
+    }
+}
+
+pub struct SeqSerializer<'e> {
+    array: Element<'e>,
+}
+
+impl<'e> SeqSerializer<'e> {
+    fn append<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        value.serialize(ElementSerializer { element: self.array.clone(), slot: Slot::Append })
+    }
+}
+
+impl<'e> ser::SerializeSeq for SeqSerializer<'e> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.append(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'e> ser::SerializeTuple for SeqSerializer<'e> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.append(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'e> ser::SerializeTupleStruct for SeqSerializer<'e> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.append(value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub struct MapSerializer<'e> {
+    element: Element<'e>,
+    key: Option<String>,
+}
+
+impl<'e> ser::SerializeMap for MapSerializer<'e> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        let key = self.key.take().ok_or(Error::ExpectedValue)?;
+        value.serialize(ElementSerializer { element: self.element.clone(), slot: Slot::Field(key) })
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub struct StructSerializer<'e> {
+    element: Element<'e>,
+}
+
+impl<'e> StructSerializer<'e> {
+    fn field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        value.serialize(ElementSerializer { element: self.element.clone(), slot: Slot::Field(key.to_string()) })
+    }
+}
+
+impl<'e> ser::SerializeStruct for StructSerializer<'e> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'e> ser::SerializeStructVariant for StructSerializer<'e> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Map keys are element names, so only strings are supported
+struct KeySerializer;
+
+macro_rules! unsupported_key {
+    ($($serialize:ident($($arg:ty),*);)*) => {
+        $(
+            fn $serialize(self, $(_: $arg),*) -> Result<String> {
+                Err(Error::UnsupportedType)
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_str(self, v: &str) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str) -> Result<String> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<String> {
+        value.serialize(self)
+    }
+
+    unsupported_key! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<String> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<String> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeTupleStruct> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant> {
+        Err(Error::UnsupportedType)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::test_service;
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Override {
+        #[serde(rename = "fieldId")]
+        field_id: &'static str,
+        value: &'static str,
+    }
+
+    #[derive(Serialize)]
+    struct Options {
+        #[serde(rename = "maxPoints")]
+        max_points: u16,
+        adjusted: bool,
+    }
+
+    #[derive(Serialize)]
+    enum Periodicity {
+        #[serde(rename = "DAILY")]
+        Daily,
+    }
+
+    #[derive(Serialize)]
+    struct PriceRequest {
+        ticker: &'static str,
+        fields: Vec<&'static str>,
+        overrides: Vec<Override>,
+        periodicity: Option<Periodicity>,
+        options: Options,
+    }
+
+    fn string(element: &Element, name: &str) -> std::result::Result<String, crate::Error> {
+        element.get_element(name)?.value::<String>()
+    }
+
+    #[test]
+    fn test_apply_to_request() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let service = test_service()?;
+        let mut request = service.create_request("PriceRequest")?;
+        let value = PriceRequest {
+            ticker: "IBM US Equity",
+            fields: vec!["PX_LAST", "VOLUME"],
+            overrides: vec![Override { field_id: "EQY_FUND_CRNCY", value: "EUR" }],
+            periodicity: None,
+            options: Options { max_points: 10, adjusted: true },
+        };
+        apply_to_request(&value, &mut request)?;
+
+        let element = request.element();
+        assert_eq!(string(&element, "ticker")?, "IBM US Equity");
+        let fields = element.get_element("fields")?;
+        assert_eq!(fields.values::<String>().collect::<Vec<_>>(), ["PX_LAST", "VOLUME"]);
+        let overrides = element.get_element("overrides")?;
+        assert_eq!(overrides.num_values(), 1);
+        let item = overrides.get_at::<Element>(0)?;
+        assert_eq!(string(&item, "fieldId")?, "EQY_FUND_CRNCY");
+        assert_eq!(string(&item, "value")?, "EUR");
+        let options = element.get_element("options")?;
+        assert_eq!(options.get_element("maxPoints")?.value::<i32>()?, 10);
+        assert!(options.get_element("adjusted")?.value::<bool>()?);
+        // None values leave the element untouched
+        assert!(string(&element, "periodicity").is_err());
+
+        let mut periodicity = BTreeMap::new();
+        periodicity.insert("periodicity", Periodicity::Daily);
+        apply_to_request(&periodicity, &mut request)?;
+        assert_eq!(string(&request.element(), "periodicity")?, "DAILY");
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported_values() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let service = test_service()?;
+        let mut request = service.create_request("PriceRequest")?;
+        // a scalar or an array cannot be the root of a request
+        assert!(matches!(apply_to_request(&1, &mut request), Err(Error::UnsupportedType)));
+        assert!(matches!(apply_to_request(&vec![1], &mut request), Err(Error::ExpectedArrayOrComplexType)));
+
+        let mut unknown = BTreeMap::new();
+        unknown.insert("unknown", 1);
+        assert!(apply_to_request(&unknown, &mut request).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!("ticker".serialize(KeySerializer).unwrap(), "ticker");
+        assert_eq!(Periodicity::Daily.serialize(KeySerializer).unwrap(), "DAILY");
+        assert!(matches!(1.serialize(KeySerializer), Err(Error::UnsupportedType)));
+        assert!(matches!(Some("ticker").serialize(KeySerializer), Err(Error::UnsupportedType)));
+    }
+}
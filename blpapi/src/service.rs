@@ -1,7 +1,16 @@
-use crate::{request::Request, Error};
+use crate::{
+    correlation_id::CorrelationId,
+    errors::ErrorInfo,
+    event::Event,
+    message::Message,
+    request::Request,
+    schema::{Operation, SchemaElementDefinition},
+    Error,
+};
 use blpapi_sys::*;
 use std::ffi::{CString, CStr};
 use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
 use std::os::raw::c_int;
 
 /// A `Service`
@@ -21,6 +30,74 @@ impl Service {
         description.to_string_lossy().into_owned()
     }
 
+    /// Return the name of the service which must be used in order to
+    /// authorize access to restricted operations on this service, or None
+    /// if no authorization is required.
+    pub fn authorization_service_name(&self) -> Option<String> {
+        let name = unsafe { blpapi_Service_authorizationServiceName(self.0) };
+        if name.is_null() {
+            return None;
+        }
+        let name = unsafe { CStr::from_ptr(name) }.to_string_lossy().into_owned();
+        if name.is_empty() { None } else { Some(name) }
+    }
+
+    /// Return true if users must be authorized before using this service
+    pub fn requires_authorization(&self) -> bool {
+        self.authorization_service_name().is_some()
+    }
+
+    /// Return true if this service defines the specified 'operation'
+    pub fn has_operation(&self, operation: &str) -> bool {
+        let operation = match CString::new(operation) {
+            Ok(operation) => operation,
+            Err(_) => return false,
+        };
+        let mut ptr = std::ptr::null_mut();
+        let res = unsafe { blpapi_Service_getOperation(self.0, &mut ptr, operation.as_ptr(), std::ptr::null()) };
+        res == 0
+    }
+
+    /// Get the number of request operations defined by this service
+    pub fn num_operations(&self) -> usize {
+        unsafe { blpapi_Service_numOperations(self.0) }.max(0) as usize
+    }
+
+    /// Get the operation at `index`
+    pub fn operation(&self, index: usize) -> Result<Operation<'_>, Error> {
+        let mut ptr = std::ptr::null_mut();
+        let res = unsafe { blpapi_Service_getOperationAt(self.0, &mut ptr, index) };
+        Error::check(res)?;
+        Ok(Operation { ptr, _marker: PhantomData })
+    }
+
+    /// Get all the operations defined by this service
+    pub fn operations(&self) -> Vec<Operation<'_>> {
+        (0..self.num_operations())
+            .filter_map(|index| self.operation(index).ok())
+            .collect()
+    }
+
+    /// Get the number of unsolicited events defined by this service
+    pub fn num_event_definitions(&self) -> usize {
+        unsafe { blpapi_Service_numEventDefinitions(self.0) }.max(0) as usize
+    }
+
+    /// Get the definition of the event at `index`
+    pub fn event_definition(&self, index: usize) -> Result<SchemaElementDefinition<'_>, Error> {
+        let mut ptr = std::ptr::null_mut();
+        let res = unsafe { blpapi_Service_getEventDefinitionAt(self.0, &mut ptr, index) };
+        Error::check(res)?;
+        Ok(SchemaElementDefinition::new(ptr))
+    }
+
+    /// Get the definitions of all the events of this service
+    pub fn event_definitions(&self) -> Vec<SchemaElementDefinition<'_>> {
+        (0..self.num_event_definitions())
+            .filter_map(|index| self.event_definition(index).ok())
+            .collect()
+    }
+
     /// Create a new request
     pub fn create_request(&self, operation: &str) -> Result<Request, Error> {
         let operation = CString::new(operation)
@@ -60,6 +137,36 @@ impl Service {
         Ok(unsafe { Request::new(ptr) })
     }
 
+    /// Create an empty Event suitable for publishing to this Service.
+    /// Use an EventFormatter to add Messages to the Event and set fields.
+    pub fn create_publish_event(&self) -> Result<Event, Error> {
+        let mut event = std::ptr::null_mut();
+        let res = unsafe { blpapi_Service_createPublishEvent(self.0, &mut event) };
+        Error::check(res)?;
+
+        Ok(Event(event))
+    }
+
+    /// Create an empty Event suitable for publishing admin messages (such
+    /// as service status) to this Service.
+    pub fn create_admin_event(&self) -> Result<Event, Error> {
+        let mut event = std::ptr::null_mut();
+        let res = unsafe { blpapi_Service_createAdminEvent(self.0, &mut event) };
+        Error::check(res)?;
+
+        Ok(Event(event))
+    }
+
+    /// Create an empty Event suitable for sending the response to the
+    /// request identified by the specified 'correlation_id'.
+    pub fn create_response_event(&self, correlation_id: &CorrelationId) -> Result<Event, Error> {
+        let mut event = std::ptr::null_mut();
+        let res = unsafe { blpapi_Service_createResponseEvent(self.0, &correlation_id.0, &mut event) };
+        Error::check(res)?;
+
+        Ok(Event(event))
+    }
+
     /// Format this Service schema to the specified formatter' at
     /// (absolute value specified for) the optionally specified indentation
     /// 'indent_level'. If 'level' is specified, optionally specify 'spaces_per_level',
@@ -109,4 +216,71 @@ impl Display for Service {
 }
 
 unsafe impl Send for Service {}
-unsafe impl Sync for Service {}
\ No newline at end of file
+unsafe impl Sync for Service {}
+
+/// The outcome of `Session::open_service_async`, as reported by a
+/// SERVICE_STATUS message
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServiceOpenStatus {
+    /// 'ServiceOpened', the service can be retrieved with
+    /// `Session::get_service`
+    Opened {
+        service_name: String,
+        correlation_id: Option<CorrelationId>,
+    },
+    /// 'ServiceOpenFailure'
+    OpenFailure {
+        service_name: String,
+        correlation_id: Option<CorrelationId>,
+        reason: ErrorInfo,
+    },
+}
+
+impl ServiceOpenStatus {
+    /// Parse a 'ServiceOpened' or 'ServiceOpenFailure' message, returning
+    /// None for other messages
+    pub fn from_message(message: &Message) -> Option<Self> {
+        let element = message.element();
+        let service_name = element
+            .get_element("serviceName")
+            .and_then(|name| name.value::<String>())
+            .unwrap_or_default();
+        let correlation_id = if message.num_correlation_ids() > 0 {
+            message.correlation_id(0)
+        } else {
+            None
+        };
+
+        let message_type = message.message_type();
+        if message_type == "ServiceOpened" {
+            Some(ServiceOpenStatus::Opened { service_name, correlation_id })
+        } else if message_type == "ServiceOpenFailure" {
+            let reason = element
+                .get_element("reason")
+                .map(|reason| ErrorInfo::from_element(&reason))
+                .unwrap_or_default();
+            Some(ServiceOpenStatus::OpenFailure { service_name, correlation_id, reason })
+        } else {
+            None
+        }
+    }
+
+    /// The name of the service being opened
+    pub fn service_name(&self) -> &str {
+        match self {
+            ServiceOpenStatus::Opened { service_name, .. } | ServiceOpenStatus::OpenFailure { service_name, .. } => service_name,
+        }
+    }
+
+    /// The correlation id given to `Session::open_service_async`
+    pub fn correlation_id(&self) -> Option<&CorrelationId> {
+        match self {
+            ServiceOpenStatus::Opened { correlation_id, .. } | ServiceOpenStatus::OpenFailure { correlation_id, .. } => correlation_id.as_ref(),
+        }
+    }
+
+    /// Return true if the service was opened
+    pub fn is_opened(&self) -> bool {
+        matches!(self, ServiceOpenStatus::Opened { .. })
+    }
+}
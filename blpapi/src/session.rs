@@ -1,8 +1,13 @@
 use crate::{
+    auth_options::AuthOptions,
+    cancellation::CancellationToken,
     correlation_id::CorrelationId,
-    event::{Event, EventQueue},
+    errors::ErrorInfo,
+    event::{Event, EventQueue, EventSource, EventType},
     eventdispatcher::EventDispatcher,
+    fields::FieldValidator,
     identity::Identity,
+    quiesce::Outstanding,
     request::Request,
     service::Service,
     session_options::SessionOptions,
@@ -10,12 +15,14 @@ use crate::{
 };
 use blpapi_sys::*;
 use std::{ffi::CString, ptr};
-use std::os::raw::{c_void, c_int, c_char};
+use std::os::raw::{c_void, c_int, c_char, c_uint};
 use std::panic::{catch_unwind, AssertUnwindSafe};
 use crate::subscriptionlist::SubscriptionList;
 use std::marker::PhantomData;
 use std::ffi::CStr;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
 
 type EventHandlerFn<'a> = dyn FnMut(&Event) -> () + 'a + Send;
 type EventHandlerCallback = unsafe extern "C" fn(*mut blpapi_Event_t, *mut blpapi_Session_t, *mut c_void);
@@ -23,6 +30,8 @@ type EventHandlerCallback = unsafe extern "C" fn(*mut blpapi_Event_t, *mut blpap
 unsafe extern "C" fn event_handler_callback(event: *mut blpapi_Event_t, _: *mut blpapi_Session_t, user_data: *mut c_void) {
     let event_handler: &mut Box<EventHandlerFn> = std::mem::transmute(user_data);
     let event = Event(event);
+    #[cfg(feature = "tracing")]
+    crate::spans::record(&event);
     if let Err(err) = catch_unwind(AssertUnwindSafe(move || (*event_handler)(&event))) {
         eprintln!("{:?}", err);
         std::process::abort();
@@ -83,6 +92,11 @@ pub struct Session<'a>
 {
     pub(crate) ptr: *mut blpapi_Session_t,
     event_handler_fn: Option<Box<EventHandlerFn<'a>>>,
+    pub(crate) field_validator: Option<FieldValidator>,
+    pub(crate) quiescing: bool,
+    pub(crate) outstanding: Outstanding,
+    state: Arc<AtomicU8>,
+    pub(crate) cancellation_token: Option<CancellationToken>,
 }
 
 impl<'a> Session<'a> {
@@ -120,9 +134,25 @@ impl<'a> Session<'a> {
     /// messages and processes each one very quickly then give each
     /// one a separate EventDispatcher.
     pub fn create(options: SessionOptions, event_handler: Option<impl FnMut(&Event) -> () + Send + 'a>, event_dispatcher: Option<&EventDispatcher>) -> Pin<Box<Self>> {
+        let outstanding = Outstanding::default();
+        let state = Arc::new(AtomicU8::new(SessionState::Created as u8));
+        let event_handler_fn = event_handler.map(|mut event_handler_fn| {
+            let outstanding = outstanding.clone();
+            let state = state.clone();
+            Box::new(move |event: &Event| {
+                outstanding.complete(event);
+                SessionState::update(&state, event);
+                event_handler_fn(event)
+            }) as _
+        });
         let mut session = Box::pin(Session {
             ptr: ptr::null_mut(),
-            event_handler_fn: event_handler.map(|event_handler_fn| Box::new(event_handler_fn) as _)
+            event_handler_fn,
+            field_validator: None,
+            quiescing: false,
+            outstanding,
+            state,
+            cancellation_token: None,
         });
         session.ptr = unsafe {
             match (session.event_handler_fn.as_ref(), event_dispatcher) {
@@ -158,7 +188,15 @@ impl<'a> Session<'a> {
     /// only be started once.
     pub fn start(&mut self) -> bool {
         let res = unsafe { blpapi_Session_start(self.ptr) };
-        res != 0
+        let state = if res == 0 { SessionState::Started } else { SessionState::Failed };
+        // an asynchronous session may already have handled its SESSION_STATUS
+        let _ = self.state.compare_exchange(
+            SessionState::Created as u8,
+            state as u8,
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+        res == 0
     }
 
     /// Attempt to begin the process to start this Session and
@@ -171,7 +209,7 @@ impl<'a> Session<'a> {
     /// only be started once.
     pub fn start_async(&mut self) -> bool {
         let res = unsafe { blpapi_Session_startAsync(self.ptr) };
-        res != 0
+        res == 0
     }
 
     /// Stop operation of this session and block until all callbacks to
@@ -184,6 +222,7 @@ impl<'a> Session<'a> {
     /// Session has been stopped it can only be destroyed.
     pub fn stop(&mut self) {
         unsafe { blpapi_Session_stop(self.ptr) };
+        self.state.store(SessionState::Stopped as u8, Ordering::SeqCst);
     }
 
     /// Begin the process to stop this Session and return immediately. The
@@ -194,10 +233,42 @@ impl<'a> Session<'a> {
     /// from within an EventHandler callback to stop Sessions using
     /// non-default (external) EventDispatcher. Once a Session has been
     /// stopped it can only be destroyed.
+    ///
+    /// 'state()' reports 'Stopped' once the SessionTerminated message has
+    /// been handled.
     pub fn stop_async(&mut self) {
         unsafe { blpapi_Session_stopAsync(self.ptr) };
     }
 
+    /// Return the next available Event for this session, waiting at most
+    /// 'timeout' milliseconds (forever if None). If no Event is available
+    /// within the 'timeout', an Event with a type() of TIMEOUT is returned.
+    /// Only valid for synchronous sessions, created without event handler.
+    pub fn next_event(&mut self, timeout: Option<isize>) -> Result<Event, Error> {
+        let mut event = ptr::null_mut();
+        let timeout = timeout.unwrap_or(0).max(0) as c_uint;
+        let res = unsafe { blpapi_Session_nextEvent(self.ptr, &mut event, timeout) };
+        Error::check(res)?;
+
+        let event = Event(event);
+        #[cfg(feature = "tracing")]
+        crate::spans::record(&event);
+        Ok(event)
+    }
+
+    /// Return the next Event if one is immediately available, or an error
+    /// without waiting otherwise. Only valid for synchronous sessions.
+    pub fn try_next_event(&mut self) -> Result<Event, Error> {
+        let mut event = ptr::null_mut();
+        let res = unsafe { blpapi_Session_tryNextEvent(self.ptr, &mut event) };
+        Error::check(res)?;
+
+        let event = Event(event);
+        #[cfg(feature = "tracing")]
+        crate::spans::record(&event);
+        Ok(event)
+    }
+
     /// Attempt to open the service identified by the specified
     /// 'serviceIdentifier' and block until the service is either opened
     /// successfully or has failed to be opened. Return 'true' if
@@ -230,12 +301,17 @@ impl<'a> Session<'a> {
     /// The application must monitor events for a SERVICE_STATUS
     /// Event which will be generated once the service has been
     /// successfully opened or the opening has failed.
+    /// `ServiceOpenStatus::from_message` reads the outcome from its
+    /// messages.
     pub fn open_service_async(&mut self, service: &str, correlation_id: Option<CorrelationId>) -> Result<CorrelationId, Error> {
         let service = CString::new(service).unwrap();
         let mut correlation_id = correlation_id.unwrap_or_else(|| CorrelationId::new_empty());
         let res = unsafe { blpapi_Session_openServiceAsync(self.ptr, service.as_ptr(), &mut correlation_id.0) };
         Error::check(res)?;
 
+        #[cfg(feature = "tracing")]
+        crate::spans::open("open_service", &correlation_id, &service.to_string_lossy());
+
         Ok(correlation_id)
     }
 
@@ -258,6 +334,39 @@ impl<'a> Session<'a> {
         Ok(result)
     }
 
+    /// Return the service identified by 'serviceIdentifier', opening
+    /// it first if it is not open yet.
+    pub(crate) fn get_or_open_service(&mut self, name: &str) -> Result<Service, Error> {
+        if let Ok(Some(service)) = self.get_service(name) {
+            return Ok(service);
+        }
+        self.open_service(name);
+        self.get_service(name)?
+            .ok_or_else(|| Error::RequestFailure(ErrorInfo {
+                message: format!("cannot open service {}", name),
+                ..ErrorInfo::default()
+            }))
+    }
+
+    /// Return the service which must be used to authorize users of the
+    /// specified 'service', opening it if needed, or None if 'service'
+    /// does not require authorization.
+    pub fn get_authorization_service(&mut self, service: &Service) -> Result<Option<Service>, Error> {
+        match service.authorization_service_name() {
+            Some(name) => self.get_or_open_service(&name).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Generate a new correlation id, unique in the process.
+    ///
+    /// Generated ids have the class id `SESSION_CLASS_ID` so they can't
+    /// collide with the integer correlation ids of the application, which
+    /// would fail with a DuplicateCorrelationId error.
+    pub fn next_correlation_id(&self) -> CorrelationId {
+        CorrelationId::next_session_id()
+    }
+
     /// Return a Identity which is valid but has not been
     /// authorized.
     pub fn create_identity(&mut self) -> Identity {
@@ -286,9 +395,82 @@ impl<'a> Session<'a> {
             )
         };
         Error::check(res)?;
+
+        #[cfg(feature = "tracing")]
+        crate::spans::open("generate_token", &correlation_id, "");
         Ok(correlation_id)
     }
 
+    /// Generate a token and wait for its TOKEN_STATUS event.
+    ///
+    /// Return the token string on 'TokenGenerationSuccess' or the reason
+    /// of the failure on 'TokenGenerationFailure'.
+    pub fn generate_token_string(&mut self, timeout: Option<isize>) -> Result<String, Error> {
+        let token = self.cancellation_token.clone();
+        let mut queue = EventQueue::new();
+        self.generate_token(None, Some(&queue))?;
+
+        loop {
+            let event = queue.next_event_cancellable(timeout, token.as_ref())?;
+            match event.event_type() {
+                EventType::TokenStatus => {
+                    for message in event.messages() {
+                        let element = message.element();
+                        if message.message_type() == "TokenGenerationSuccess" {
+                            return element.get_element("token")?.value::<String>();
+                        }
+                        if let Ok(reason) = element.get_element("reason") {
+                            return Err(Error::RequestFailure(ErrorInfo::from_element(&reason)));
+                        }
+                    }
+                    return Err(Error::RequestFailure(ErrorInfo::default()));
+                }
+                EventType::Timeout => return Err(Error::TimeOut),
+                _ => (),
+            }
+        }
+    }
+
+    /// Asynchronously generate an authorized 'Identity' with the specified
+    /// 'auth_options'. The result is delivered as an
+    /// 'AuthorizationSuccess' or 'AuthorizationFailure' message in an
+    /// AUTHORIZATION_STATUS event, after which the identity can be
+    /// retrieved with 'get_authorized_identity'.
+    pub fn generate_authorized_identity(
+        &mut self,
+        auth_options: &AuthOptions,
+        correlation_id: Option<CorrelationId>
+    ) -> Result<CorrelationId, Error> {
+        let mut correlation_id = correlation_id.unwrap_or_else(|| CorrelationId::new_empty());
+        let res = unsafe {
+            blpapi_Session_generateAuthorizedIdentityAsync(
+                self.ptr,
+                auth_options.0,
+                &mut correlation_id.0
+            )
+        };
+        Error::check(res)?;
+
+        #[cfg(feature = "tracing")]
+        crate::spans::open("generate_authorized_identity", &correlation_id, "");
+        Ok(correlation_id)
+    }
+
+    /// Return the identity authorized by 'generate_authorized_identity'
+    /// with the specified 'correlation_id'.
+    pub fn get_authorized_identity(&self, correlation_id: &CorrelationId) -> Result<Identity, Error> {
+        let mut identity = ptr::null_mut();
+        let res = unsafe {
+            blpapi_Session_getAuthorizedIdentity(
+                self.ptr,
+                &correlation_id.0,
+                &mut identity
+            )
+        };
+        Error::check(res)?;
+        Ok(Identity(identity))
+    }
+
     /// Send the specified 'request' using the specified 'identity' for
     /// authorization. If the optionally specified 'correlationId' is
     /// supplied use it otherwise create a CorrelationId. The actual
@@ -313,11 +495,54 @@ impl<'a> Session<'a> {
         event_queue: Option<&EventQueue>,
         correlation_id: Option<CorrelationId>,
     ) -> Result<CorrelationId, Error> {
+        self.send_request_with_label(request, identity, event_queue, correlation_id, None)
+    }
+
+    /// Send `request` with all its events delivered to `event_queue`,
+    /// returning the correlation id of the request.
+    ///
+    /// The events are then read with `EventQueue::responses`, e.g.
+    ///
+    /// ```ignore
+    /// let mut queue = EventQueue::new();
+    /// session.send_to_queue(request, &mut queue)?;
+    /// for event in queue.responses(Some(5000)) {
+    ///     for message in event? {
+    ///         println!("{:?}", message);
+    ///     }
+    /// }
+    /// ```
+    pub fn send_to_queue(&mut self, request: Request, event_queue: &mut EventQueue) -> Result<CorrelationId, Error> {
+        self.send_request(request, None, Some(event_queue), None)
+    }
+
+    /// Same as 'send_request', recording the optionally specified
+    /// 'request_label' along with any diagnostics for this operation.
+    pub fn send_request_with_label(
+        &mut self,
+        request: Request,
+        identity: Option<&Identity>,
+        event_queue: Option<&EventQueue>,
+        correlation_id: Option<CorrelationId>,
+        request_label: Option<&str>,
+    ) -> Result<CorrelationId, Error> {
+        if self.quiescing {
+            return Err(Error::Quiescing);
+        }
         let mut correlation_id = correlation_id.unwrap_or_else(|| CorrelationId::new_empty());
         let identity = identity.map_or(ptr::null_mut(), |identity| identity.0);
+        // only the responses delivered to the event handler can be tracked
+        let tracked = event_queue.is_none() && self.event_handler_fn.is_some();
         let event_queue = event_queue.map_or(ptr::null_mut(), |event_queue| event_queue.0);
-        let request_label = ptr::null_mut();
-        let request_label_len = 0;
+        let request_label = request_label
+            .map(CString::new)
+            .transpose()
+            .map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let request_label_len = request_label.as_ref().map_or(0, |label| label.as_bytes().len() as c_int);
+        let request_label = request_label.as_ref().map_or(ptr::null(), |label| label.as_ptr());
+        // held while sending so the response cannot be handled before the
+        // request is recorded
+        let mut outstanding = self.outstanding.lock();
         let res = unsafe {
             blpapi_Session_sendRequest(
                 self.ptr,
@@ -330,6 +555,13 @@ impl<'a> Session<'a> {
             )
         };
         Error::check(res)?;
+        if tracked {
+            outstanding.insert(correlation_id.clone());
+        }
+        drop(outstanding);
+
+        #[cfg(feature = "tracing")]
+        crate::spans::open("request", &correlation_id, "");
 
         Ok(correlation_id)
     }
@@ -364,10 +596,31 @@ impl<'a> Session<'a> {
         correlation_id: Option<CorrelationId>,
         event_queue: Option<&EventQueue>
     ) -> Result<CorrelationId, Error> {
+        self.send_authorization_request_with_label(request, identity, correlation_id, event_queue, None)
+    }
+
+    /// Same as 'send_authorization_request', recording the optionally
+    /// specified 'request_label' along with any diagnostics for this
+    /// operation.
+    pub fn send_authorization_request_with_label(
+        &mut self,
+        request: &Request,
+        identity: &Identity,
+        correlation_id: Option<CorrelationId>,
+        event_queue: Option<&EventQueue>,
+        request_label: Option<&str>,
+    ) -> Result<CorrelationId, Error> {
+        if self.quiescing {
+            return Err(Error::Quiescing);
+        }
         let mut correlation_id = correlation_id.unwrap_or_else(|| CorrelationId::new_empty());
         let event_queue = event_queue.map_or(ptr::null_mut(), |event_queue| event_queue.0);
-        let request_label = ptr::null_mut();
-        let request_label_len = 0;
+        let request_label = request_label
+            .map(CString::new)
+            .transpose()
+            .map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let request_label_len = request_label.as_ref().map_or(0, |label| label.as_bytes().len() as c_int);
+        let request_label = request_label.as_ref().map_or(ptr::null(), |label| label.as_ptr());
         let res = unsafe {
             blpapi_Session_sendAuthorizationRequest(
                 self.ptr,
@@ -381,6 +634,9 @@ impl<'a> Session<'a> {
         };
         Error::check(res)?;
 
+        #[cfg(feature = "tracing")]
+        crate::spans::open("authorization", &correlation_id, "");
+
         Ok(correlation_id)
     }
 
@@ -395,6 +651,9 @@ impl<'a> Session<'a> {
     /// A SUBSCRIPTION_STATUS Event will be generated for each
     /// entry in the 'subscriptionList'.
     pub fn subscribe(&mut self, subscription_list: &SubscriptionList, identity: Option<&Identity>) -> Result<(), Error> {
+        if self.quiescing {
+            return Err(Error::Quiescing);
+        }
         let identity = identity.map_or(ptr::null_mut(), |identity| identity.0);
         let request_label = ptr::null_mut();
         let request_label_len = 0;
@@ -407,7 +666,13 @@ impl<'a> Session<'a> {
                 request_label_len,
             )
         };
-        Error::check(res)
+        Error::check(res)?;
+
+        #[cfg(feature = "tracing")]
+        for (correlation_id, topic) in subscription_list.correlation_ids().zip(subscription_list.topic_strings()) {
+            crate::spans::open("subscription", &correlation_id, &topic.to_string_lossy());
+        }
+        Ok(())
     }
 
     /// Modify each subscription in the specified
@@ -469,6 +734,53 @@ impl<'a> Session<'a> {
         Error::check(res)
     }
 
+    /// Cancel outstanding requests represented by the specified
+    /// 'correlation_ids'. If the optional 'request_label' is provided it
+    /// is recorded along with any diagnostics for this operation.
+    ///
+    /// Once this call returns the specified correlation ids will not be
+    /// seen in any subsequent Message obtained from a MessageIterator by
+    /// calling next(). However, any Message currently pointed to by a
+    /// MessageIterator when cancel() is called is not affected even if it
+    /// has one of the specified correlation ids. Also any Message where a
+    /// reference has been retained by the application may still contain
+    /// one of the correlation ids. For these reasons, although technically
+    /// an application is free to re-use the correlation ids as soon as
+    /// this method returns it is preferable not to aggressively re-use
+    /// correlation ids, particularly with an asynchronous Session.
+    pub fn cancel(&mut self, correlation_ids: &[CorrelationId], request_label: Option<&str>) -> Result<(), Error> {
+        let correlation_ids: Vec<blpapi_CorrelationId_t> = correlation_ids.iter().map(|id| id.0).collect();
+        let request_label = request_label
+            .map(CString::new)
+            .transpose()
+            .map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let request_label_len = request_label.as_ref().map_or(0, |label| label.as_bytes().len() as c_int);
+        let request_label = request_label.as_ref().map_or(ptr::null(), |label| label.as_ptr());
+        let res = unsafe {
+            blpapi_Session_cancel(
+                self.ptr,
+                correlation_ids.as_ptr(),
+                correlation_ids.len(),
+                request_label,
+                request_label_len,
+            )
+        };
+        Error::check(res)
+    }
+
+    /// Cancel the outstanding request represented by the specified
+    /// 'correlation_id'.
+    pub fn cancel_one(&mut self, correlation_id: &CorrelationId) -> Result<(), Error> {
+        self.cancel(std::slice::from_ref(correlation_id), None)
+    }
+
+    /// The state of this session, maintained from the SESSION_STATUS
+    /// events delivered to the event handler. A synchronous session only
+    /// reflects the results of 'start()' and 'stop()'.
+    pub fn state(&self) -> SessionState {
+        SessionState::from(self.state.load(Ordering::SeqCst))
+    }
+
     /// Iterate through all subscriptions in this session
     pub fn subscriptions(&self) -> SubscriptionIterator {
         let ptr = unsafe { blpapi_SubscriptionItr_create(self.ptr) };
@@ -476,6 +788,16 @@ impl<'a> Session<'a> {
     }
 }
 
+impl EventSource for Session<'_> {
+    fn next_event(&mut self, timeout: Option<isize>) -> Result<Event, Error> {
+        Session::next_event(self, timeout)
+    }
+
+    fn try_next_event(&mut self) -> Result<Event, Error> {
+        Session::try_next_event(self)
+    }
+}
+
 impl Drop for Session<'_> {
     fn drop(&mut self) {
         unsafe { blpapi_Session_destroy(self.ptr) }
@@ -485,6 +807,51 @@ impl Drop for Session<'_> {
 unsafe impl Send for Session<'_> {}
 unsafe impl Sync for Session<'_> {}
 
+/// The state of a session, see 'Session::state()'
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionState {
+    /// Created but not started yet
+    Created = 0,
+    /// Started, the connection may be temporarily down
+    Started = 1,
+    /// Terminated, by the application or by the API
+    Stopped = 2,
+    /// The session could not be started
+    Failed = 3,
+}
+
+impl SessionState {
+    fn update(state: &AtomicU8, event: &Event) {
+        if event.event_type() != EventType::SessionStatus {
+            return;
+        }
+        for message in event.messages() {
+            let message_type = message.message_type();
+            let new_state = if message_type == "SessionStarted" {
+                SessionState::Started
+            } else if message_type == "SessionStartupFailure" {
+                SessionState::Failed
+            } else if message_type == "SessionTerminated" {
+                SessionState::Stopped
+            } else {
+                continue;
+            };
+            state.store(new_state as u8, Ordering::SeqCst);
+        }
+    }
+}
+
+impl From<u8> for SessionState {
+    fn from(state: u8) -> Self {
+        match state {
+            0 => SessionState::Created,
+            1 => SessionState::Started,
+            2 => SessionState::Stopped,
+            _ => SessionState::Failed,
+        }
+    }
+}
+
 #[derive(Debug, PartialOrd, PartialEq)]
 pub enum SubscriptionStatus {
     /// No longer active, terminated by API.
@@ -538,10 +905,12 @@ impl<'a> Iterator for SubscriptionIterator<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut subscription_string: *const c_char = ptr::null();
-        let mut correlation_id = CorrelationId::new_empty();
+        let mut correlation_id = blpapi_CorrelationId_t::default();
         let mut status: c_int = 0;
-        let res = unsafe { blpapi_SubscriptionItr_next(self.ptr, &mut subscription_string, &mut correlation_id.0, &mut status) };
+        let res = unsafe { blpapi_SubscriptionItr_next(self.ptr, &mut subscription_string, &mut correlation_id, &mut status) };
         if res == 0 {
+            // the correlation id is still owned by the session
+            let correlation_id = unsafe { CorrelationId::from_raw(correlation_id) };
             let subscription_string = unsafe { CStr::from_ptr(subscription_string) };
             Some((subscription_string.to_string_lossy().to_string(), correlation_id, SubscriptionStatus::from(status as u32)))
         } else {
@@ -1,8 +1,13 @@
 use crate::Error;
+use crate::auth_options::AuthOptions;
+use crate::correlation_id::CorrelationId;
 use crate::tls_options::TlsOptions;
 use blpapi_sys::*;
 use std::ffi::{CStr, CString};
-use std::os::raw::c_int;
+use std::fmt::{Debug, Display, Formatter};
+use std::os::raw::{c_int, c_uint};
+#[cfg(feature = "sdk-3-18")]
+use std::os::raw::c_char;
 
 /// A SessionOptions
 ///
@@ -60,6 +65,292 @@ impl SessionOptions {
         Ok(self)
     }
 
+    /// Set the server address at the specified 'index' to 'host' and
+    /// 'port'. Addresses are tried in order of their index when
+    /// connecting, which allows failover to backup endpoints.
+    pub fn set_server_address(&mut self, host: &str, port: u16, index: usize) -> Result<(), Error> {
+        let chost = CString::new(host).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let res = unsafe { blpapi_SessionOptions_setServerAddress(self.0, chost.as_ptr(), port, index) };
+        Error::check(res)
+    }
+
+    /// Set the server addresses to 'addresses', in failover order
+    pub fn with_server_addresses(mut self, addresses: &[(&str, u16)]) -> Result<Self, Error> {
+        for (index, (host, port)) in addresses.iter().enumerate() {
+            self.set_server_address(host, *port, index)?;
+        }
+        while self.num_server_addresses() > addresses.len() {
+            self.remove_server_address(addresses.len())?;
+        }
+        Ok(self)
+    }
+
+    /// Remove the server address at the specified 'index'
+    pub fn remove_server_address(&mut self, index: usize) -> Result<(), Error> {
+        let res = unsafe { blpapi_SessionOptions_removeServerAddress(self.0, index) };
+        Error::check(res)
+    }
+
+    /// Get the number of server addresses
+    pub fn num_server_addresses(&self) -> usize {
+        unsafe { blpapi_SessionOptions_numServerAddresses(self.0) as usize }
+    }
+
+    /// Get the host and port of the server address at the specified
+    /// 'index'
+    pub fn server_address(&self, index: usize) -> Result<(String, u16), Error> {
+        let mut host = std::ptr::null();
+        let mut port = 0;
+        let res = unsafe { blpapi_SessionOptions_getServerAddress(self.0, &mut host, &mut port, index) };
+        Error::check(res)?;
+        let host = unsafe { CStr::from_ptr(host) }.to_string_lossy().into_owned();
+        Ok((host, port))
+    }
+
+    /// Get whether the session automatically restarts on disconnection
+    pub fn auto_restart_on_disconnection(&self) -> bool {
+        unsafe { blpapi_SessionOptions_autoRestartOnDisconnection(self.0) != 0 }
+    }
+
+    /// Set whether the session automatically restarts on disconnection
+    pub fn with_auto_restart_on_disconnection(self, auto_restart: bool) -> Self {
+        unsafe { blpapi_SessionOptions_setAutoRestartOnDisconnection(self.0, auto_restart as c_int) };
+        self
+    }
+
+    /// Get the connection timeout, in milliseconds
+    pub fn connect_timeout(&self) -> u32 {
+        unsafe { blpapi_SessionOptions_connectTimeout(self.0) as u32 }
+    }
+
+    /// Set the connection timeout, in milliseconds. It must be between 1ms
+    /// and 120000ms (2 minutes), the default is 5000ms.
+    pub fn with_connect_timeout(self, timeout_ms: u32) -> Result<Self, Error> {
+        let res = unsafe { blpapi_SessionOptions_setConnectTimeout(self.0, timeout_ms as c_uint) };
+        Error::check(res)?;
+        Ok(self)
+    }
+
+    /// Get whether keep-alive packets are sent
+    pub fn keep_alive_enabled(&self) -> bool {
+        unsafe { blpapi_SessionOptions_keepAliveEnabled(self.0) != 0 }
+    }
+
+    /// Set whether keep-alive packets are sent to the server
+    pub fn with_keep_alive_enabled(self, enabled: bool) -> Result<Self, Error> {
+        let res = unsafe { blpapi_SessionOptions_setKeepAliveEnabled(self.0, enabled as c_int) };
+        Error::check(res)?;
+        Ok(self)
+    }
+
+    /// Get the time without any read from the server after which a
+    /// keep-alive request is sent, in milliseconds
+    pub fn keep_alive_inactivity_time(&self) -> i32 {
+        unsafe { blpapi_SessionOptions_defaultKeepAliveInactivityTime(self.0) }
+    }
+
+    /// Set the time without any read from the server after which a
+    /// keep-alive request is sent, in milliseconds
+    pub fn with_keep_alive_inactivity_time(self, inactivity_time_ms: i32) -> Result<Self, Error> {
+        let res = unsafe { blpapi_SessionOptions_setDefaultKeepAliveInactivityTime(self.0, inactivity_time_ms) };
+        Error::check(res)?;
+        Ok(self)
+    }
+
+    /// Get the time to wait for a keep-alive response before considering
+    /// the connection down, in milliseconds
+    pub fn keep_alive_response_timeout(&self) -> i32 {
+        unsafe { blpapi_SessionOptions_defaultKeepAliveResponseTimeout(self.0) }
+    }
+
+    /// Set the time to wait for a keep-alive response before considering
+    /// the connection down, in milliseconds
+    pub fn with_keep_alive_response_timeout(self, response_timeout_ms: i32) -> Result<Self, Error> {
+        let res = unsafe { blpapi_SessionOptions_setDefaultKeepAliveResponseTimeout(self.0, response_timeout_ms) };
+        Error::check(res)?;
+        Ok(self)
+    }
+
+    /// Get the default service for subscriptions which do not specify one
+    pub fn default_subscription_service(&self) -> String {
+        let service = unsafe { CStr::from_ptr(blpapi_SessionOptions_defaultSubscriptionService(self.0)) };
+        service.to_string_lossy().into_owned()
+    }
+
+    /// Set the default service for subscriptions which do not specify
+    /// one, '//blp/mktdata' by default
+    pub fn with_default_subscription_service(self, service: &str) -> Result<Self, Error> {
+        let service = CString::new(service).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let res = unsafe { blpapi_SessionOptions_setDefaultSubscriptionService(self.0, service.as_ptr()) };
+        Error::check(res)?;
+        Ok(self)
+    }
+
+    /// Get the default topic prefix
+    pub fn default_topic_prefix(&self) -> String {
+        let prefix = unsafe { CStr::from_ptr(blpapi_SessionOptions_defaultTopicPrefix(self.0)) };
+        prefix.to_string_lossy().into_owned()
+    }
+
+    /// Set the prefix added to topics which do not start with one, e.g.
+    /// 'ticker' turns 'IBM US Equity' into '/ticker/IBM US Equity'
+    pub fn with_default_topic_prefix(self, prefix: &str) -> Result<Self, Error> {
+        let prefix = CString::new(prefix).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        unsafe { blpapi_SessionOptions_setDefaultTopicPrefix(self.0, prefix.as_ptr()) };
+        Ok(self)
+    }
+
+    /// Get the maximum number of events buffered by the session
+    pub fn max_event_queue_size(&self) -> usize {
+        unsafe { blpapi_SessionOptions_maxEventQueueSize(self.0) }
+    }
+
+    /// Set the maximum number of events buffered by the session before
+    /// the oldest ones are dropped, 10000 by default
+    pub fn with_max_event_queue_size(self, max_event_queue_size: usize) -> Self {
+        unsafe { blpapi_SessionOptions_setMaxEventQueueSize(self.0, max_event_queue_size) };
+        self
+    }
+
+    /// Get the slow consumer warning high water mark
+    pub fn slow_consumer_warning_hi_water_mark(&self) -> f32 {
+        unsafe { blpapi_SessionOptions_slowConsumerWarningHiWaterMark(self.0) }
+    }
+
+    /// Set the fraction of 'max_event_queue_size' above which a
+    /// SlowConsumerWarning event is generated, 0.75 by default. It must
+    /// be greater than the low water mark.
+    pub fn with_slow_consumer_warning_hi_water_mark(self, hi_water_mark: f32) -> Result<Self, Error> {
+        let res = unsafe { blpapi_SessionOptions_setSlowConsumerWarningHiWaterMark(self.0, hi_water_mark) };
+        Error::check(res)?;
+        Ok(self)
+    }
+
+    /// Get the slow consumer warning low water mark
+    pub fn slow_consumer_warning_lo_water_mark(&self) -> f32 {
+        unsafe { blpapi_SessionOptions_slowConsumerWarningLoWaterMark(self.0) }
+    }
+
+    /// Set the fraction of 'max_event_queue_size' below which a
+    /// SlowConsumerWarningCleared event is generated, 0.5 by default. It
+    /// must be lower than the high water mark.
+    pub fn with_slow_consumer_warning_lo_water_mark(self, lo_water_mark: f32) -> Result<Self, Error> {
+        let res = unsafe { blpapi_SessionOptions_setSlowConsumerWarningLoWaterMark(self.0, lo_water_mark) };
+        Error::check(res)?;
+        Ok(self)
+    }
+
+    /// Get the maximum number of requests which can be pending
+    pub fn max_pending_requests(&self) -> i32 {
+        unsafe { blpapi_SessionOptions_maxPendingRequests(self.0) }
+    }
+
+    /// Set the maximum number of requests which can be pending, 1024 by
+    /// default
+    pub fn with_max_pending_requests(self, max_pending_requests: i32) -> Self {
+        unsafe { blpapi_SessionOptions_setMaxPendingRequests(self.0, max_pending_requests) };
+        self
+    }
+
+    /// Get the number of start attempts
+    pub fn num_start_attempts(&self) -> i32 {
+        unsafe { blpapi_SessionOptions_numStartAttempts(self.0) }
+    }
+
+    /// Set the number of times the session tries to start before giving
+    /// up, 1 by default
+    pub fn with_num_start_attempts(self, num_start_attempts: i32) -> Self {
+        unsafe { blpapi_SessionOptions_setNumStartAttempts(self.0, num_start_attempts) };
+        self
+    }
+
+    /// Get whether the receive times of subscription data are recorded
+    pub fn record_subscription_data_receive_times(&self) -> bool {
+        unsafe { blpapi_SessionOptions_recordSubscriptionDataReceiveTimes(self.0) != 0 }
+    }
+
+    /// Set whether the receive times of subscription data are recorded
+    pub fn with_record_subscription_data_receive_times(self, record: bool) -> Self {
+        unsafe { blpapi_SessionOptions_setRecordSubscriptionDataReceiveTimes(self.0, record as c_int) };
+        self
+    }
+
+    /// Get whether the bandwidth save mode is disabled
+    pub fn bandwidth_save_mode_disabled(&self) -> bool {
+        unsafe { blpapi_SessionOptions_bandwidthSaveModeDisabled(self.0) != 0 }
+    }
+
+    /// Set whether the bandwidth save mode is disabled
+    pub fn with_bandwidth_save_mode_disabled(self, disabled: bool) -> Result<Self, Error> {
+        let res = unsafe { blpapi_SessionOptions_setBandwidthSaveModeDisabled(self.0, disabled as c_int) };
+        Error::check(res)?;
+        Ok(self)
+    }
+
+    /// Get the session name
+    ///
+    /// Requires the `sdk-3-18` feature
+    pub fn session_name(&self) -> Result<String, Error> {
+        #[cfg(not(feature = "sdk-3-18"))]
+        return Err(Error::MissingSdkFeature("sdk-3-18"));
+        #[cfg(feature = "sdk-3-18")]
+        {
+            let mut name = std::ptr::null();
+            let mut size = 0;
+            let res = unsafe { blpapi_SessionOptions_sessionName(&mut name, &mut size, self.0) };
+            Error::check(res)?;
+            Ok(string_from_parts(name, size))
+        }
+    }
+
+    /// Set the session name, used to attribute the logs of the underlying
+    /// library to this session
+    ///
+    /// Requires the `sdk-3-18` feature
+    #[cfg_attr(not(feature = "sdk-3-18"), allow(unused_variables))]
+    pub fn with_session_name(self, name: &str) -> Result<Self, Error> {
+        #[cfg(not(feature = "sdk-3-18"))]
+        return Err(Error::MissingSdkFeature("sdk-3-18"));
+        #[cfg(feature = "sdk-3-18")]
+        {
+            let res = unsafe { blpapi_SessionOptions_setSessionName(self.0, name.as_ptr() as *const c_char, name.len()) };
+            Error::check(res)?;
+            Ok(self)
+        }
+    }
+
+    /// Get the application identity key (AIK)
+    ///
+    /// Requires the `sdk-3-20` feature
+    pub fn application_identity_key(&self) -> Result<String, Error> {
+        #[cfg(not(feature = "sdk-3-20"))]
+        return Err(Error::MissingSdkFeature("sdk-3-20"));
+        #[cfg(feature = "sdk-3-20")]
+        {
+            let mut key = std::ptr::null();
+            let mut size = 0;
+            let res = unsafe { blpapi_SessionOptions_applicationIdentityKey(&mut key, &mut size, self.0) };
+            Error::check(res)?;
+            Ok(string_from_parts(key, size))
+        }
+    }
+
+    /// Set the application identity key (AIK), used for entitlement
+    /// tracking
+    ///
+    /// Requires the `sdk-3-20` feature
+    #[cfg_attr(not(feature = "sdk-3-20"), allow(unused_variables))]
+    pub fn with_application_identity_key(self, key: &str) -> Result<Self, Error> {
+        #[cfg(not(feature = "sdk-3-20"))]
+        return Err(Error::MissingSdkFeature("sdk-3-20"));
+        #[cfg(feature = "sdk-3-20")]
+        {
+            let res = unsafe { blpapi_SessionOptions_setApplicationIdentityKey(self.0, key.as_ptr() as *const c_char, key.len()) };
+            Error::check(res)?;
+            Ok(self)
+        }
+    }
+
     /// Set TLS options
     pub fn with_tls_options(self, tls_options: &TlsOptions) -> Self {
         unsafe { blpapi_SessionOptions_setTlsOptions(self.0, tls_options.0) }
@@ -72,6 +363,65 @@ impl SessionOptions {
         unsafe { blpapi_SessionOptions_setAuthenticationOptions(self.0, auth_options.as_ptr()) };
         self
     }
+
+    /// Authorize the session identity with the specified 'auth_options'
+    /// when the session starts, instead of the raw authentication options
+    /// string. The authorization status messages are delivered with the
+    /// optionally specified 'correlation_id'.
+    pub fn with_session_identity_options(
+        self,
+        auth_options: &AuthOptions,
+        correlation_id: Option<CorrelationId>,
+    ) -> Result<Self, Error> {
+        let mut correlation_id = correlation_id.unwrap_or_else(CorrelationId::new_empty);
+        let res = unsafe { blpapi_SessionOptions_setSessionIdentityOptions(self.0, auth_options.0, &mut correlation_id.0) };
+        Error::check(res)?;
+        Ok(self)
+    }
+}
+
+impl SessionOptions {
+    /// Format all the options to the specified 'f' at (absolute value
+    /// specified for) the optionally specified indentation 'indent_level'.
+    /// If 'spaces_per_level' is negative, format the entire output on one
+    /// line, suppressing all but the initial indentation (as governed by
+    /// 'indent_level').
+    pub fn print(&self, f: &mut Formatter<'_>, indent_level: isize, spaces_per_level: isize) -> Result<(), Error> {
+        let res = unsafe {
+            let stream = std::mem::transmute(f);
+            blpapi_SessionOptions_print(
+                self.0,
+                Some(crate::utils::stream_writer),
+                stream,
+                indent_level as c_int,
+                spaces_per_level as c_int
+            )
+        };
+        Error::check(res)
+    }
+}
+
+impl Debug for SessionOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.print(f, 0, -1).map_err(|_| std::fmt::Error)
+    }
+}
+
+impl Display for SessionOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.print(f, 0, 4).map_err(|_| std::fmt::Error)
+    }
+}
+
+/// Read a string given as a pointer and a size, which may not be null
+/// terminated
+#[cfg(feature = "sdk-3-18")]
+fn string_from_parts(ptr: *const c_char, size: usize) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    let bytes = unsafe { std::slice::from_raw_parts(ptr as *const u8, size) };
+    String::from_utf8_lossy(bytes).into_owned()
 }
 
 impl Drop for SessionOptions {
@@ -120,4 +470,13 @@ mod tests {
         let options = SessionOptions::default().with_server_host(host).unwrap();
         assert_eq!(host, options.server_host());
     }
+
+    #[test]
+    fn test_server_addresses() {
+        let options = SessionOptions::default()
+            .with_server_addresses(&[("primary", 8194), ("backup", 8196)])
+            .unwrap();
+        assert_eq!(options.num_server_addresses(), 2);
+        assert_eq!(options.server_address(1).unwrap(), ("backup".to_string(), 8196));
+    }
 }
@@ -0,0 +1,141 @@
+//! Tracing spans scoped by `CorrelationId`
+//!
+//! A span is opened whenever a request, subscription, service opening or
+//! token generation is issued and lives until the matching terminal
+//! message (final response, failure, subscription termination...) is seen.
+//! Every message received for the correlation id is recorded as an event
+//! within the span, so the whole round trip shows up as a single trace.
+//!
+//! Spans are kept in a process wide registry: events are seen both from
+//! session handlers and from `EventQueue`s, which do not know the session
+//! which created them.
+use crate::correlation_id::CorrelationId;
+use crate::event::{Event, EventType};
+use crate::message::Message;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use tracing::{debug, error, info_span, trace, Span};
+
+fn registry() -> MutexGuard<'static, HashMap<CorrelationId, Span>> {
+    static SPANS: OnceLock<Mutex<HashMap<CorrelationId, Span>>> = OnceLock::new();
+    SPANS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// Open a span for the operation identified by `correlation_id`
+pub(crate) fn open(operation: &'static str, correlation_id: &CorrelationId, topic: &str) {
+    let span = info_span!("blpapi", operation, topic, correlation_id = ?correlation_id);
+    registry().insert(correlation_id.clone(), span);
+}
+
+/// Record all messages of `event` in their spans, closing the spans of
+/// completed operations
+pub(crate) fn record(event: &Event) {
+    let event_type = event.event_type();
+    for message in event.messages() {
+        let terminal = is_terminal(event_type, &message);
+        for correlation_id in message.correlation_ids() {
+            let mut spans = registry();
+            let span = if terminal {
+                match spans.remove(&correlation_id) {
+                    Some(span) => span,
+                    None => continue,
+                }
+            } else {
+                match spans.get(&correlation_id) {
+                    Some(span) => span.clone(),
+                    None => continue,
+                }
+            };
+            drop(spans);
+
+            let message_type = message.message_type().to_string_lossy();
+            span.in_scope(|| match event_type {
+                EventType::SubscriptionData => trace!(?event_type, %message_type, "update"),
+                EventType::PartialResponse => debug!(?event_type, %message_type, "partial response"),
+                _ if is_failure(&message_type) => error!(?event_type, %message_type, "failure"),
+                _ if terminal => debug!(?event_type, %message_type, "completed"),
+                _ => debug!(?event_type, %message_type),
+            });
+        }
+    }
+}
+
+fn is_failure(message_type: &str) -> bool {
+    message_type.ends_with("Failure") || message_type.ends_with("Failed")
+}
+
+fn is_terminal(event_type: EventType, message: &Message) -> bool {
+    match event_type {
+        EventType::Response | EventType::RequestStatus | EventType::TokenStatus => true,
+        EventType::SubscriptionStatus => {
+            let message_type = message.message_type();
+            message_type == "SubscriptionFailure" || message_type == "SubscriptionTerminated"
+        }
+        EventType::ServiceStatus => {
+            let message_type = message.message_type();
+            message_type == "ServiceOpened" || message_type == "ServiceOpenFailure"
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::name::Name;
+    use crate::testutil::{EventBuilder, MessageProperties};
+    use crate::Error;
+
+    fn build_event(event_type: EventType, message_type: &str, correlation_id: &CorrelationId) -> Result<Event, Error> {
+        let properties = MessageProperties::new()?.with_correlation_ids(std::slice::from_ref(correlation_id))?;
+        Ok(EventBuilder::new(event_type)?
+            .append_message_from_json(Name::new(message_type), Some(properties), "{}")?
+            .build())
+    }
+
+    fn is_open(correlation_id: &CorrelationId) -> bool {
+        registry().contains_key(correlation_id)
+    }
+
+    #[test]
+    fn test_subscription_span() -> Result<(), Error> {
+        let correlation_id = CorrelationId::new_int(10051, None);
+        open("subscribe", &correlation_id, "IBM US Equity");
+        assert!(is_open(&correlation_id));
+
+        record(&build_event(EventType::SubscriptionStatus, "SubscriptionStarted", &correlation_id)?);
+        assert!(is_open(&correlation_id));
+
+        record(&build_event(EventType::SubscriptionStatus, "SubscriptionTerminated", &correlation_id)?);
+        assert!(!is_open(&correlation_id));
+        Ok(())
+    }
+
+    #[test]
+    fn test_request_span() -> Result<(), Error> {
+        let correlation_id = CorrelationId::new_int(10052, None);
+        open("request", &correlation_id, "");
+
+        record(&build_event(EventType::PartialResponse, "SessionStarted", &correlation_id)?);
+        assert!(is_open(&correlation_id));
+
+        record(&build_event(EventType::Response, "SessionStarted", &correlation_id)?);
+        assert!(!is_open(&correlation_id));
+
+        // messages of unknown correlation ids are ignored
+        record(&build_event(EventType::Response, "SessionStarted", &correlation_id)?);
+        assert!(!is_open(&correlation_id));
+        Ok(())
+    }
+
+    #[test]
+    fn failures() {
+        assert!(is_failure("SubscriptionFailure"));
+        assert!(is_failure("ServiceOpenFailure"));
+        assert!(is_failure("SessionConnectionFailed"));
+        assert!(!is_failure("SubscriptionStarted"));
+    }
+}
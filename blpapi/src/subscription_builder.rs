@@ -0,0 +1,191 @@
+//! Subscription strings
+//!
+//! A subscription string is made of a service, a topic, the subscribed
+//! fields and options, e.g.
+//! `//blp/mktdata/ticker/IBM US Equity?fields=BID,ASK&interval=2`.
+//! `SubscriptionBuilder` assembles it from typed parts, checking that the
+//! parts don't contain the separators of the string.
+use crate::{
+    correlation_id::CorrelationId,
+    security::Security,
+    subscriptionlist::SubscriptionList,
+    Error,
+};
+use std::fmt::Write;
+
+/// Default market data service
+pub const MKTDATA_SERVICE: &str = "//blp/mktdata";
+
+/// A subscription string being built
+#[derive(Debug, Clone)]
+pub struct SubscriptionBuilder {
+    service: String,
+    topic: String,
+    fields: Vec<String>,
+    options: Vec<(String, Option<String>)>,
+    correlation_id: Option<CorrelationId>,
+}
+
+impl SubscriptionBuilder {
+    /// Subscribe to `security` on `//blp/mktdata`
+    pub fn new(security: &Security) -> Self {
+        let topic = match security.prefix() {
+            Some(prefix) => format!("{}{}", prefix, security.id()),
+            None => format!("/ticker/{}", security.id()),
+        };
+        SubscriptionBuilder::topic(&topic)
+    }
+
+    /// Subscribe to a `topic` relative to the service, e.g.
+    /// `/ticker/IBM US Equity`
+    pub fn topic(topic: &str) -> Self {
+        SubscriptionBuilder {
+            service: MKTDATA_SERVICE.to_string(),
+            topic: topic.to_string(),
+            fields: Vec::new(),
+            options: Vec::new(),
+            correlation_id: None,
+        }
+    }
+
+    /// Subscribe on `service` instead of `//blp/mktdata`
+    pub fn with_service(mut self, service: &str) -> Self {
+        self.service = service.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Subscribe to `field`
+    pub fn with_field(mut self, field: &str) -> Self {
+        self.fields.push(field.to_string());
+        self
+    }
+
+    /// Subscribe to all `fields`
+    pub fn with_fields<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.fields.extend(fields.into_iter().map(|field| field.as_ref().to_string()));
+        self
+    }
+
+    /// Receive the updates conflated every `seconds` seconds
+    pub fn with_interval(self, seconds: f64) -> Self {
+        self.with_option("interval", Some(&seconds.to_string()))
+    }
+
+    /// Receive delayed data, e.g. when not entitled to real time data
+    pub fn delayed(self) -> Self {
+        self.with_option("delayed", None)
+    }
+
+    /// Add the option `name`, or `name=value`
+    pub fn with_option(mut self, name: &str, value: Option<&str>) -> Self {
+        self.options.push((name.to_string(), value.map(str::to_string)));
+        self
+    }
+
+    /// Associate `correlation_id` with the subscription when added to a
+    /// `SubscriptionList`
+    pub fn with_correlation_id(mut self, correlation_id: CorrelationId) -> Self {
+        self.correlation_id = Some(correlation_id);
+        self
+    }
+
+    /// Render the subscription string
+    ///
+    /// Option values are percent-encoded; an error is returned if the
+    /// other parts contain the separators of the subscription string.
+    pub fn build(&self) -> Result<String, Error> {
+        if !self.service.starts_with("//") || self.service.len() <= 2 || self.service.contains(['?', '&'].as_ref()) {
+            return Err(invalid("service", &self.service));
+        }
+        let topic = self.topic.trim_start_matches('/');
+        if topic.is_empty() || topic.contains(['?', '&'].as_ref()) {
+            return Err(invalid("topic", &self.topic));
+        }
+        let mut subscription = format!("{}/{}", self.service, topic);
+        let mut separator = '?';
+        if !self.fields.is_empty() {
+            for field in &self.fields {
+                if field.is_empty() || field.contains(['?', '&', '=', ',', ' '].as_ref()) {
+                    return Err(invalid("field", field));
+                }
+            }
+            let _ = write!(subscription, "{}fields={}", separator, self.fields.join(","));
+            separator = '&';
+        }
+        for (name, value) in &self.options {
+            if name.is_empty() || name.contains(['?', '&', '=', ',', ' '].as_ref()) {
+                return Err(invalid("option", name));
+            }
+            subscription.push(separator);
+            subscription.push_str(name);
+            if let Some(value) = value {
+                subscription.push('=');
+                percent_encode(&mut subscription, value);
+            }
+            separator = '&';
+        }
+        Ok(subscription)
+    }
+
+    /// Render the subscription string and add it to `list`
+    pub fn add_to(self, list: &mut SubscriptionList) -> Result<(), Error> {
+        let subscription = self.build()?;
+        list.add(&subscription, None, None, self.correlation_id)
+    }
+}
+
+fn invalid(part: &str, value: &str) -> Error {
+    Error::InvalidSubscription(format!("invalid {} '{}'", part, value))
+}
+
+fn percent_encode(output: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '?' | '&' | '=' | ',' | '%' | ' ' => {
+                let _ = write!(output, "%{:02X}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let ibm = Security::ticker("IBM US Equity").unwrap();
+        let subscription = SubscriptionBuilder::new(&ibm)
+            .with_fields(["BID", "ASK"].iter())
+            .with_interval(2.)
+            .delayed()
+            .build()
+            .unwrap();
+        assert_eq!(subscription, "//blp/mktdata/ticker/IBM US Equity?fields=BID,ASK&interval=2&delayed");
+
+        let isin = Security::isin("US4592001014").unwrap();
+        assert_eq!(SubscriptionBuilder::new(&isin).build().unwrap(), "//blp/mktdata/isin/US4592001014");
+
+        let subscription = SubscriptionBuilder::topic("/ticker/VOD LN Equity")
+            .with_service("//blp/mktvwap/")
+            .with_option("VWAP_START_TIME", Some("10:00"))
+            .with_option("label", Some("a&b"))
+            .build()
+            .unwrap();
+        assert_eq!(subscription, "//blp/mktvwap/ticker/VOD LN Equity?VWAP_START_TIME=10:00&label=a%26b");
+    }
+
+    #[test]
+    fn invalid() {
+        let ibm = Security::ticker("IBM US Equity").unwrap();
+        assert!(SubscriptionBuilder::new(&ibm).with_field("BID,ASK").build().is_err());
+        assert!(SubscriptionBuilder::new(&ibm).with_option("a=b", None).build().is_err());
+        assert!(SubscriptionBuilder::new(&ibm).with_service("blp/mktdata").build().is_err());
+        assert!(SubscriptionBuilder::topic("/ticker/IBM?").build().is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! Subscription state tracking
+//!
+//! The state of each subscription is only reported through the messages
+//! of SUBSCRIPTION_STATUS events. A `SubscriptionTracker` fed with all the
+//! events of a session keeps the latest state per correlation id and can
+//! notify every change, e.g. to resubscribe the failed subscriptions.
+use crate::{
+    correlation_id::CorrelationId,
+    errors::ErrorInfo,
+    event::{Event, EventType},
+    message::Message,
+    subscriptionlist::SubscriptionList,
+};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+
+/// The state of a subscription
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionState {
+    /// Subscribed, not yet started
+    Subscribing,
+    /// Started, updates are flowing
+    Active,
+    /// The subscription could not be started
+    Failed { reason: ErrorInfo },
+    /// The subscription was terminated after being started
+    Terminated { reason: Option<ErrorInfo> },
+}
+
+impl SubscriptionState {
+    /// Return true if no more updates will be received
+    pub fn is_final(&self) -> bool {
+        matches!(self, SubscriptionState::Failed { .. } | SubscriptionState::Terminated { .. })
+    }
+}
+
+type Callback = Box<dyn FnMut(&CorrelationId, &SubscriptionState) + Send>;
+
+/// The state of the subscriptions of a session, per correlation id
+#[derive(Default)]
+pub struct SubscriptionTracker {
+    states: HashMap<CorrelationId, SubscriptionState>,
+    on_change: Option<Callback>,
+}
+
+impl SubscriptionTracker {
+    pub fn new() -> Self {
+        SubscriptionTracker::default()
+    }
+
+    /// Call `f` with the new state of a subscription every time it changes
+    pub fn on_change<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&CorrelationId, &SubscriptionState) + Send + 'static,
+    {
+        self.on_change = Some(Box::new(f));
+        self
+    }
+
+    /// Track the subscriptions of `list`, once subscribed
+    pub fn subscribing(&mut self, list: &SubscriptionList) {
+        for correlation_id in list.correlation_ids() {
+            self.set(correlation_id, SubscriptionState::Subscribing);
+        }
+    }
+
+    /// Update the states from the messages of `event`, returning the number
+    /// of changes. Events other than SUBSCRIPTION_STATUS are ignored.
+    pub fn handle_event(&mut self, event: &Event) -> usize {
+        if event.event_type() != EventType::SubscriptionStatus {
+            return 0;
+        }
+        event.messages().map(|message| self.handle_message(&message)).sum()
+    }
+
+    /// Update the states from a SUBSCRIPTION_STATUS `message`, returning
+    /// the number of changes
+    pub fn handle_message(&mut self, message: &Message) -> usize {
+        let message_type = message.message_type();
+        let reason = || {
+            message
+                .element()
+                .try_get_element("reason")
+                .map(|reason| ErrorInfo::from_element(&reason))
+        };
+        let state = if message_type == "SubscriptionStarted" {
+            SubscriptionState::Active
+        } else if message_type == "SubscriptionFailure" {
+            SubscriptionState::Failed { reason: reason().unwrap_or_default() }
+        } else if message_type == "SubscriptionTerminated" {
+            SubscriptionState::Terminated { reason: reason() }
+        } else {
+            return 0;
+        };
+        let mut changes = 0;
+        for correlation_id in message.correlation_ids() {
+            if self.set(correlation_id, state.clone()) {
+                changes += 1;
+            }
+        }
+        changes
+    }
+
+    /// Get the state of the subscription `correlation_id`
+    pub fn state(&self, correlation_id: &CorrelationId) -> Option<&SubscriptionState> {
+        self.states.get(correlation_id)
+    }
+
+    /// Iterate over all the tracked subscriptions
+    pub fn states(&self) -> impl Iterator<Item = (&CorrelationId, &SubscriptionState)> {
+        self.states.iter()
+    }
+
+    /// Iterate over the subscriptions in `state`, ignoring the reasons of
+    /// failed or terminated subscriptions
+    pub fn with_state<'a>(&'a self, state: &'a SubscriptionState) -> impl Iterator<Item = &'a CorrelationId> + 'a {
+        self.states
+            .iter()
+            .filter(move |(_, s)| std::mem::discriminant(*s) == std::mem::discriminant(state))
+            .map(|(correlation_id, _)| correlation_id)
+    }
+
+    /// Iterate over the active subscriptions
+    pub fn active(&self) -> impl Iterator<Item = &CorrelationId> {
+        self.with_state(&SubscriptionState::Active)
+    }
+
+    /// Stop tracking `correlation_id`, e.g. once unsubscribed
+    pub fn remove(&mut self, correlation_id: &CorrelationId) -> Option<SubscriptionState> {
+        self.states.remove(correlation_id)
+    }
+
+    /// Stop tracking the subscriptions which won't receive updates anymore
+    pub fn remove_final(&mut self) {
+        self.states.retain(|_, state| !state.is_final());
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    fn set(&mut self, correlation_id: CorrelationId, state: SubscriptionState) -> bool {
+        if self.states.get(&correlation_id) == Some(&state) {
+            return false;
+        }
+        if let Some(on_change) = self.on_change.as_mut() {
+            on_change(&correlation_id, &state);
+        }
+        self.states.insert(correlation_id, state);
+        true
+    }
+}
+
+impl Debug for SubscriptionTracker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.states.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::name::Name;
+    use crate::testutil::{EventBuilder, MessageProperties};
+    use crate::Error;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_transitions() -> Result<(), Error> {
+        let started = CorrelationId::new_int(1, None);
+        let failed = CorrelationId::new_int(2, None);
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let mut tracker = SubscriptionTracker::new().on_change({
+            let changes = changes.clone();
+            move |id, state| changes.lock().unwrap().push((id.clone(), state.clone()))
+        });
+
+        let mut list = SubscriptionList::new();
+        list.add("//blp/mktdata/ticker/IBM US Equity", None, None, Some(started.clone()))?;
+        list.add("//blp/mktdata/ticker/XXX US Equity", None, None, Some(failed.clone()))?;
+        tracker.subscribing(&list);
+        assert_eq!(tracker.state(&started), Some(&SubscriptionState::Subscribing));
+
+        let event = EventBuilder::new(EventType::SubscriptionStatus)?
+            .append_message_from_json(
+                Name::new("SubscriptionStarted"),
+                Some(MessageProperties::new()?.with_correlation_ids(std::slice::from_ref(&started))?),
+                "{}",
+            )?
+            .append_message_from_json(
+                Name::new("SubscriptionFailure"),
+                Some(MessageProperties::new()?.with_correlation_ids(std::slice::from_ref(&failed))?),
+                r#"{ "reason": { "source": "TestUtil", "errorCode": 2, "category": "BAD_SEC", "description": "Invalid security" } }"#,
+            )?
+            .build();
+        assert_eq!(tracker.handle_event(&event), 2);
+
+        assert_eq!(tracker.active().collect::<Vec<_>>(), vec![&started]);
+        match tracker.state(&failed) {
+            Some(SubscriptionState::Failed { reason }) => assert_eq!(reason.category, "BAD_SEC"),
+            state => panic!("unexpected state {:?}", state),
+        }
+        assert_eq!(changes.lock().unwrap().len(), 4);
+
+        tracker.remove_final();
+        assert_eq!(tracker.len(), 1);
+        Ok(())
+    }
+}
@@ -115,6 +115,21 @@ impl SubscriptionList {
     pub fn topic_strings(&self) -> TopicStringIterator {
         TopicStringIterator { subscription_list: self, indices: 0..self.size() }
     }
+
+    /// Return true if the topic at the specified 'index' was added with
+    /// 'add_resolved'.
+    pub fn is_resolved_at(&self, index: usize) -> Result<bool, Error> {
+        let mut is_resolved = 0;
+        let res = unsafe { blpapi_SubscriptionList_isResolvedTopicAt(self.0, &mut is_resolved, index) };
+        Error::check(res)?;
+        Ok(is_resolved != 0)
+    }
+
+    /// Iterate over the entries of this object, e.g. for logging or to
+    /// keep track of the subscriptions to resubscribe.
+    pub fn entries(&self) -> EntriesIterator<'_> {
+        EntriesIterator { subscription_list: self, indices: 0..self.size() }
+    }
 }
 
 impl Drop for SubscriptionList {
@@ -150,10 +165,10 @@ impl<'a> Iterator for CorrelationIdsIterator<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         self.indices.next().map(|index| {
-            let mut correlation_id = CorrelationId::new_empty();
-            let res = unsafe { blpapi_SubscriptionList_correlationIdAt(self.subscription_list.0, &mut correlation_id.0, index) };
+            let mut correlation_id = blpapi_CorrelationId_t::default();
+            let res = unsafe { blpapi_SubscriptionList_correlationIdAt(self.subscription_list.0, &mut correlation_id, index) };
             Error::check(res).unwrap();
-            correlation_id
+            unsafe { CorrelationId::from_raw(correlation_id) }
         })
     }
 }
@@ -175,3 +190,42 @@ impl<'a> Iterator for TopicStringIterator<'a> {
         })
     }
 }
+
+/// An entry of a 'SubscriptionList'
+#[derive(Debug, Clone)]
+pub struct SubscriptionEntry {
+    pub topic_string: String,
+    pub correlation_id: CorrelationId,
+    /// Whether the topic was added with 'add_resolved'
+    pub is_resolved: bool,
+}
+
+pub struct EntriesIterator<'a> {
+    subscription_list: &'a SubscriptionList,
+    indices: Range<usize>,
+}
+
+impl<'a> Iterator for EntriesIterator<'a> {
+    type Item = SubscriptionEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indices.next().map(|index| {
+            let list = self.subscription_list;
+            let mut correlation_id = blpapi_CorrelationId_t::default();
+            let mut topic_string: *const c_char = ptr::null();
+            let res = unsafe { blpapi_SubscriptionList_correlationIdAt(list.0, &mut correlation_id, index) };
+            Error::check(res).unwrap();
+            let res = unsafe { blpapi_SubscriptionList_topicStringAt(list.0, &mut topic_string, index) };
+            Error::check(res).unwrap();
+            SubscriptionEntry {
+                topic_string: unsafe { CStr::from_ptr(topic_string) }.to_string_lossy().into_owned(),
+                correlation_id: unsafe { CorrelationId::from_raw(correlation_id) },
+                is_resolved: list.is_resolved_at(index).unwrap_or(false),
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
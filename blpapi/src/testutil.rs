@@ -1,10 +1,33 @@
+//! Offline testing utilities, with the `test-util` feature
+//!
+//! `EventBuilder` creates events as blpapi would deliver them, from the
+//! JSON or XML of their messages, and `MockSession` replays them to the
+//! code under test in place of a `Session` and its `EventQueue`. Services
+//! are loaded from the XML of their schema with `deserialize_service`.
+//!
+//! ```ignore
+//! let event = EventBuilder::new(EventType::SessionStatus)?
+//!     .append_message_from_json(Name::new("SessionStarted"), None, "{}")?
+//!     .build();
+//! let mut session = MockSession::new().with_event(event);
+//! assert_eq!(session.next_event(None)?.event_type(), EventType::SessionStatus);
+//! assert_eq!(session.next_event(None)?.event_type(), EventType::Timeout);
+//! ```
 use blpapi_sys::*;
+use std::collections::VecDeque;
 use std::ptr;
-use crate::event::{EventType, Event};
+use crate::correlation_id::CorrelationId;
+use crate::event::{EventType, Event, EventSource};
 use crate::Error;
 use crate::name::Name;
+use crate::schema::SchemaElementDefinition;
+use crate::service::Service;
+use crate::topic::Topic;
 use std::ffi::CString;
+use std::os::raw::c_char;
+use std::time::Duration;
 
+/// The properties of a message appended to an `EventBuilder`
 pub struct MessageProperties(pub(crate) *mut blpapi_MessageProperties_t);
 
 impl MessageProperties {
@@ -15,6 +38,14 @@ impl MessageProperties {
 
         Ok(MessageProperties(message_properties))
     }
+
+    pub fn with_correlation_ids(self, correlation_ids: &[CorrelationId]) -> Result<Self, Error> {
+        let ids: Vec<_> = correlation_ids.iter().map(|id| id.0).collect();
+        let res = unsafe { blpapi_MessageProperties_setCorrelationIds(self.0, ids.as_ptr(), ids.len()) };
+        Error::check(res)?;
+
+        Ok(self)
+    }
 }
 
 impl Drop for MessageProperties {
@@ -45,6 +76,7 @@ impl Drop for MessageFormatter {
     }
 }
 
+/// Build an event from the JSON or XML of its messages
 pub struct EventBuilder {
     event: Event,
 }
@@ -63,6 +95,10 @@ impl EventBuilder {
         let res = unsafe { blpapi_TestUtil_getAdminMessageDefinition(&mut schema_definition, message_type.0) };
         Error::check(res)?;
 
+        self.append_message_definition(schema_definition, message_properties)
+    }
+
+    fn append_message_definition(&mut self, schema_definition: *mut blpapi_SchemaElementDefinition_t, message_properties: Option<MessageProperties>) -> Result<MessageFormatter, Error> {
         let message_properties = message_properties.unwrap_or_else(|| MessageProperties::new().unwrap());
         let mut formatter: *mut blpapi_MessageFormatter_t = ptr::null_mut();
         let res = unsafe { blpapi_TestUtil_appendMessage(&mut formatter, self.event.0, schema_definition, message_properties.0) };
@@ -85,7 +121,330 @@ impl EventBuilder {
         Ok(self)
     }
 
+    /// Append a message of a service, e.g. defined by
+    /// `Service::event_definition` of a service from `deserialize_service`
+    pub fn append_service_message_from_json(mut self, definition: SchemaElementDefinition, message_properties: Option<MessageProperties>, json: &str) -> Result<Self, Error> {
+        let mut formatter = self.append_message_definition(definition.ptr, message_properties)?;
+        formatter.format_message_json(json)?;
+
+        Ok(self)
+    }
+
     pub fn build(self) -> Event {
         self.event
     }
-}
\ No newline at end of file
+}
+
+/// Create a `Service` from the XML of its schema, e.g. as dumped from a
+/// running session, to create its requests offline
+///
+/// ```ignore
+/// let service = deserialize_service(&std::fs::read_to_string("refdata.xml")?)?;
+/// let request = service.create_request("ReferenceDataRequest")?;
+/// ```
+pub fn deserialize_service(schema: &str) -> Result<Service, Error> {
+    let mut service: *mut blpapi_Service_t = ptr::null_mut();
+    let res = unsafe {
+        blpapi_TestUtil_deserializeService(schema.as_ptr() as *const c_char, schema.len(), &mut service)
+    };
+    Error::check(res)?;
+    Ok(Service(service))
+}
+
+/// Create a topic of `service`, e.g. to format publish events offline
+pub fn create_topic(service: &Service, is_active: bool) -> Result<Topic, Error> {
+    let mut topic: *mut blpapi_Topic_t = ptr::null_mut();
+    let res = unsafe { blpapi_TestUtil_createTopic(&mut topic, service.0, is_active as i32) };
+    Error::check(res)?;
+    Ok(Topic(topic))
+}
+
+/// A session replaying prebuilt events, e.g. from `EventBuilder`
+///
+/// Events are returned in order by the same methods as `EventQueue`, then
+/// TIMEOUT events once all have been replayed.
+#[derive(Default)]
+pub struct MockSession {
+    events: VecDeque<Event>,
+}
+
+impl MockSession {
+    pub fn new() -> Self {
+        MockSession::default()
+    }
+
+    /// Queue `event` to be replayed
+    pub fn with_event(mut self, event: Event) -> Self {
+        self.push_event(event);
+        self
+    }
+
+    /// Queue `event` to be replayed
+    pub fn push_event(&mut self, event: Event) {
+        self.events.push_back(event);
+    }
+
+    /// Number of events not replayed yet
+    pub fn pending(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns the next event, or a TIMEOUT event if all have been
+    /// replayed. The `timeout` is ignored.
+    pub fn next_event(&mut self, _timeout: Option<isize>) -> Result<Event, Error> {
+        match self.events.pop_front() {
+            Some(event) => {
+                #[cfg(feature = "tracing")]
+                crate::spans::record(&event);
+                Ok(event)
+            }
+            None => Ok(EventBuilder::new(EventType::Timeout)?.build()),
+        }
+    }
+
+    /// Returns the next event, or None if all have been replayed
+    pub fn next_event_timeout(&mut self, _timeout: Duration) -> Result<Option<Event>, Error> {
+        Ok(self.events.pop_front())
+    }
+
+    /// Returns the next event, or an error if all have been replayed
+    pub fn try_next_event(&mut self) -> Result<Event, Error> {
+        self.events.pop_front().ok_or(Error::TimeOut)
+    }
+
+    /// Pass all the pending events to `handler`, as the event handler of a
+    /// `Session` would get them, returning their number
+    pub fn replay<F: FnMut(&Event)>(&mut self, mut handler: F) -> usize {
+        let mut count = 0;
+        while let Some(event) = self.events.pop_front() {
+            handler(&event);
+            count += 1;
+        }
+        count
+    }
+}
+
+impl EventSource for MockSession {
+    fn next_event(&mut self, timeout: Option<isize>) -> Result<Event, Error> {
+        MockSession::next_event(self, timeout)
+    }
+
+    fn try_next_event(&mut self) -> Result<Event, Error> {
+        MockSession::try_next_event(self)
+    }
+}
+
+/// Schema of the `//blp/test` service shared by the tests of the crate:
+/// one event per message shape they need, and the operations they fill
+#[cfg(test)]
+pub(crate) const TEST_SCHEMA: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<ServiceDefinition name="blp.test" version="1.0.0.0">
+    <service name="//blp/test" version="1.0.0.0">
+        <event name="MarketDataEvents" eventType="MarketDataEvents"/>
+        <event name="Status" eventType="Status"/>
+        <event name="Tick" eventType="Tick"/>
+        <event name="Ticks" eventType="Ticks"/>
+        <event name="ReferenceDataResponse" eventType="ReferenceDataResponse"/>
+        <event name="HistoricalDataResponse" eventType="HistoricalDataResponse"/>
+        <event name="fieldResponse" eventType="fieldResponse"/>
+        <operation name="PriceRequest" serviceId="1">
+            <request>PriceRequest</request>
+            <response>PriceResponse</response>
+        </operation>
+        <operation name="HistoricalDataRequest" serviceId="2">
+            <request>HistoricalDataRequest</request>
+            <response>HistoricalDataResponse</response>
+        </operation>
+    </service>
+    <schema>
+        <sequenceType name="MarketDataEvents">
+            <element name="LAST_PRICE" type="Float64" minOccurs="0"/>
+            <element name="VOLUME" type="Int64" minOccurs="0"/>
+            <element name="ACTIVE" type="Boolean" minOccurs="0"/>
+            <element name="TICKER" type="String" minOccurs="0"/>
+            <element name="MARKET_STATUS" type="String" minOccurs="0"/>
+            <element name="EXCH_CODE_LAST" type="String" minOccurs="0"/>
+        </sequenceType>
+        <sequenceType name="Status">
+            <element name="active" type="Boolean" minOccurs="0"/>
+            <element name="ticker" type="String" minOccurs="0"/>
+        </sequenceType>
+        <sequenceType name="Tick">
+            <element name="time" type="Datetime"/>
+        </sequenceType>
+        <sequenceType name="Ticks">
+            <element name="ticker" type="String"/>
+            <element name="prices" type="Float64" minOccurs="0" maxOccurs="unbounded"/>
+            <element name="trades" type="Trade" minOccurs="0" maxOccurs="unbounded"/>
+        </sequenceType>
+        <sequenceType name="Trade">
+            <element name="size" type="Int64"/>
+        </sequenceType>
+        <sequenceType name="PriceRequest">
+            <element name="ticker" type="String" minOccurs="0"/>
+            <element name="fields" type="String" minOccurs="0" maxOccurs="unbounded"/>
+            <element name="overrides" type="Override" minOccurs="0" maxOccurs="unbounded"/>
+            <element name="periodicity" type="String" minOccurs="0"/>
+            <element name="options" type="Options" minOccurs="0"/>
+        </sequenceType>
+        <sequenceType name="Options">
+            <element name="maxPoints" type="Int32" minOccurs="0"/>
+            <element name="adjusted" type="Boolean" minOccurs="0"/>
+        </sequenceType>
+        <sequenceType name="PriceResponse">
+            <element name="price" type="Float64"/>
+        </sequenceType>
+        <sequenceType name="HistoricalDataRequest">
+            <element name="startDate" type="String" minOccurs="0"/>
+            <element name="endDate" type="String" minOccurs="0"/>
+            <element name="periodicitySelection" type="String" minOccurs="0"/>
+            <element name="periodicityAdjustment" type="String" minOccurs="0"/>
+            <element name="currency" type="String" minOccurs="0"/>
+            <element name="maxDataPoints" type="Int32" minOccurs="0"/>
+            <element name="overrides" type="Override" minOccurs="0" maxOccurs="unbounded"/>
+        </sequenceType>
+        <sequenceType name="Override">
+            <element name="fieldId" type="String"/>
+            <element name="value" type="String"/>
+        </sequenceType>
+        <sequenceType name="ReferenceDataResponse">
+            <element name="securityData" type="ReferenceSecurityData" minOccurs="0" maxOccurs="unbounded"/>
+        </sequenceType>
+        <sequenceType name="ReferenceSecurityData">
+            <element name="security" type="String"/>
+            <element name="securityError" type="ErrorInfo" minOccurs="0"/>
+            <element name="fieldExceptions" type="FieldException" minOccurs="0" maxOccurs="unbounded"/>
+            <element name="fieldData" type="ReferenceFieldData" minOccurs="0"/>
+        </sequenceType>
+        <sequenceType name="ReferenceFieldData">
+            <element name="PX_LAST" type="Float64" minOccurs="0"/>
+            <element name="NAME" type="String" minOccurs="0"/>
+        </sequenceType>
+        <sequenceType name="HistoricalDataResponse">
+            <element name="securityData" type="HistoricalSecurityData"/>
+        </sequenceType>
+        <sequenceType name="HistoricalSecurityData">
+            <element name="security" type="String"/>
+            <element name="securityError" type="ErrorInfo" minOccurs="0"/>
+            <element name="fieldData" type="HistoricalFieldData" minOccurs="0" maxOccurs="unbounded"/>
+        </sequenceType>
+        <sequenceType name="HistoricalFieldData">
+            <element name="date" type="String"/>
+            <element name="PX_LAST" type="Float64" minOccurs="0"/>
+        </sequenceType>
+        <sequenceType name="FieldException">
+            <element name="fieldId" type="String"/>
+            <element name="errorInfo" type="ErrorInfo"/>
+        </sequenceType>
+        <sequenceType name="ErrorInfo">
+            <element name="source" type="String" minOccurs="0"/>
+            <element name="code" type="Int32" minOccurs="0"/>
+            <element name="category" type="String" minOccurs="0"/>
+            <element name="message" type="String" minOccurs="0"/>
+            <element name="subcategory" type="String" minOccurs="0"/>
+        </sequenceType>
+        <sequenceType name="fieldResponse">
+            <element name="fieldData" type="FieldDefinition" minOccurs="0" maxOccurs="unbounded"/>
+        </sequenceType>
+        <sequenceType name="FieldDefinition">
+            <element name="id" type="String"/>
+            <element name="fieldInfo" type="FieldInfo" minOccurs="0"/>
+        </sequenceType>
+        <sequenceType name="FieldInfo">
+            <element name="mnemonic" type="String"/>
+        </sequenceType>
+    </schema>
+</ServiceDefinition>"#;
+
+/// The `//blp/test` service of `TEST_SCHEMA`
+#[cfg(test)]
+pub(crate) fn test_service() -> Result<Service, Error> {
+    deserialize_service(TEST_SCHEMA)
+}
+
+/// Build an event of `event_type` holding a `message_type` message of
+/// `//blp/test`, filled from `json`
+#[cfg(test)]
+pub(crate) fn test_event(
+    event_type: EventType,
+    message_type: &str,
+    message_properties: Option<MessageProperties>,
+    json: &str,
+) -> Result<Event, Error> {
+    let service = test_service()?;
+    let definition = service
+        .event_definitions()
+        .into_iter()
+        .find(|definition| definition.name() == message_type)
+        .expect("unknown //blp/test event");
+    Ok(EventBuilder::new(event_type)?
+        .append_service_message_from_json(definition, message_properties, json)?
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_session() -> Result<(), Error> {
+        let started = EventBuilder::new(EventType::SessionStatus)?
+            .append_message_from_json(Name::new("SessionStarted"), None, "{}")?
+            .build();
+        let mut session = MockSession::new().with_event(started);
+        assert_eq!(session.pending(), 1);
+        assert_eq!(session.next_event(None)?.event_type(), EventType::SessionStatus);
+        assert_eq!(session.next_event(None)?.event_type(), EventType::Timeout);
+        assert!(session.try_next_event().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_deserialize_service() -> Result<(), Error> {
+        let schema = r#"<?xml version="1.0" encoding="UTF-8" ?>
+<ServiceDefinition name="blp.test" version="1.0.0.0">
+    <service name="//blp/test" version="1.0.0.0">
+        <operation name="PriceRequest" serviceId="1">
+            <request>PriceRequest</request>
+            <response>PriceResponse</response>
+        </operation>
+    </service>
+    <schema>
+        <sequenceType name="PriceRequest">
+            <element name="ticker" type="String"/>
+        </sequenceType>
+        <sequenceType name="PriceResponse">
+            <element name="price" type="Float64"/>
+        </sequenceType>
+    </schema>
+</ServiceDefinition>"#;
+        let service = deserialize_service(schema)?;
+        assert_eq!(service.name(), "//blp/test");
+        assert_eq!(service.num_operations(), 1);
+        assert!(service.has_operation("PriceRequest"));
+        assert_eq!(service.operation(0)?.name(), "PriceRequest");
+        assert!(service.create_request("PriceRequest").is_ok());
+        assert!(deserialize_service("<ServiceDefinition/>").is_err());
+        Ok(())
+    }
+
+    /// Application code written against any event source
+    fn count_events<S: EventSource>(source: &mut S) -> Result<usize, Error> {
+        let mut count = 0;
+        while source.next_event(None)?.event_type() != EventType::Timeout {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    #[test]
+    fn test_event_source() -> Result<(), Error> {
+        let started = EventBuilder::new(EventType::SessionStatus)?
+            .append_message_from_json(Name::new("SessionStarted"), None, "{}")?
+            .build();
+        let mut session = MockSession::new().with_event(started.clone()).with_event(started);
+        assert_eq!(count_events(&mut session)?, 2);
+        assert_eq!(session.pending(), 0);
+        Ok(())
+    }
+}
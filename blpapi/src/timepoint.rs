@@ -0,0 +1,62 @@
+//! High resolution time points
+//!
+//! `TimePoint`s come from the high resolution clock of blpapi, e.g. the
+//! receive time of subscription data messages recorded with
+//! `SessionOptions::with_record_subscription_data_receive_times`. They can
+//! be compared with `TimePoint::now()` to measure latencies.
+use crate::{datetime::Datetime, Error};
+use blpapi_sys::*;
+use std::fmt::{Debug, Formatter};
+
+/// A point in time of the high resolution clock
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimePoint(pub(crate) i64);
+
+impl TimePoint {
+    /// The current time of the high resolution clock
+    pub fn now() -> Self {
+        let mut time_point = blpapi_TimePoint_t::default();
+        unsafe { blpapi_HighResolutionClock_now(&mut time_point) };
+        TimePoint(time_point.d_value)
+    }
+
+    pub(crate) fn to_blpapi(self) -> blpapi_TimePoint_t {
+        blpapi_TimePoint_t { d_value: self.0 }
+    }
+
+    /// Number of nanoseconds elapsed from `self` to `end`, negative if
+    /// `end` is earlier
+    pub fn nanoseconds_until(&self, end: &TimePoint) -> i64 {
+        unsafe { blpapi_TimePointUtil_nanosecondsBetween(&self.to_blpapi(), &end.to_blpapi()) }
+    }
+
+    /// Time elapsed since `self`, or zero if `self` is in the future
+    pub fn elapsed(&self) -> std::time::Duration {
+        let nanoseconds = self.nanoseconds_until(&TimePoint::now());
+        std::time::Duration::from_nanos(nanoseconds.max(0) as u64)
+    }
+
+    /// Convert to a UTC datetime with picoseconds
+    pub fn to_datetime(&self) -> Result<HighPrecisionDatetime, Error> {
+        let mut datetime = blpapi_HighPrecisionDatetime_t::default();
+        let res = unsafe { blpapi_HighPrecisionDatetime_fromTimePoint(&mut datetime, &self.to_blpapi(), 0) };
+        Error::check(res)?;
+        Ok(HighPrecisionDatetime {
+            datetime: Datetime(datetime.datetime),
+            picoseconds: datetime.picoseconds,
+        })
+    }
+}
+
+impl Debug for TimePoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("TimePoint[{}]", self.0))
+    }
+}
+
+/// A datetime with the picoseconds elapsed since its milliseconds
+#[derive(Clone, Debug)]
+pub struct HighPrecisionDatetime {
+    pub datetime: Datetime,
+    pub picoseconds: u32,
+}
@@ -1,13 +1,63 @@
+#[cfg(any(feature = "rustls", feature = "native-tls"))]
+use crate::Error;
 use blpapi_sys::*;
 use std::ffi::{CString};
+use std::fmt::{Debug, Formatter};
 
-pub struct TlsOptions(pub(crate) *mut blpapi_TlsOptions_t);
+/// Default TLS handshake timeout of blpapi
+const DEFAULT_TLS_HANDSHAKE_TIMEOUT_MS: i32 = 10_000;
+
+/// Default CRL fetch timeout of blpapi
+const DEFAULT_CRL_FETCH_TIMEOUT_MS: i32 = 20_000;
+
+/// Password of the PKCS#12 blobs built from rustls credentials, which never
+/// leave the process
+#[cfg(feature = "rustls")]
+const BLOB_PASSWORD: &str = "blpapi";
+
+pub struct TlsOptions(pub(crate) *mut blpapi_TlsOptions_t, Settings);
+
+/// What was configured, as blpapi doesn't expose it
+#[derive(Debug, Clone)]
+struct Settings {
+    credentials: Credentials,
+    tls_handshake_timeout_ms: i32,
+    crl_fetch_timeout_ms: i32,
+}
+
+impl Settings {
+    fn new(credentials: Credentials) -> Self {
+        Settings {
+            credentials,
+            tls_handshake_timeout_ms: DEFAULT_TLS_HANDSHAKE_TIMEOUT_MS,
+            crl_fetch_timeout_ms: DEFAULT_CRL_FETCH_TIMEOUT_MS,
+        }
+    }
+}
+
+/// Where the credentials come from, without the secrets
+#[derive(Debug, Clone)]
+enum Credentials {
+    None,
+    Files {
+        client_credentials: String,
+        trusted_certificates: String,
+    },
+    Blobs {
+        client_credentials_len: usize,
+        trusted_certificates_len: usize,
+    },
+}
 
 impl TlsOptions {
     /// Creates a TlsOptions using a DER encoded client credentials in
     /// PKCS#12 format and DER encoded trust material in PKCS#7 format from
     /// the specified files.
     pub fn create_from_files(client_credentials_file_name: &str, client_credentials_password: &str, trusted_certificates_file_name: &str) -> Option<TlsOptions> {
+        let credentials = Credentials::Files {
+            client_credentials: client_credentials_file_name.to_string(),
+            trusted_certificates: trusted_certificates_file_name.to_string(),
+        };
         let client_credentials_file_name = CString::new(client_credentials_file_name).unwrap();
         let client_credentials_password = CString::new(client_credentials_password).unwrap();
         let trusted_certificates_file_name = CString::new(trusted_certificates_file_name).unwrap();
@@ -19,7 +69,7 @@ impl TlsOptions {
         if ret.is_null() {
             None
         } else {
-            Some(TlsOptions(ret))
+            Some(TlsOptions(ret, Settings::new(credentials)))
         }
     }
 
@@ -41,16 +91,88 @@ impl TlsOptions {
         if ret.is_null() {
             None
         } else {
-            Some(TlsOptions(ret))
+            let credentials = Credentials::Blobs {
+                client_credentials_len: client_credentials_raw_data_length,
+                trusted_certificates_len: trusted_certificates_raw_data_length,
+            };
+            Some(TlsOptions(ret, Settings::new(credentials)))
         }
     }
 
+    /// Create a TlsOptions from the credentials of rustls, e.g. as read
+    /// with `rustls-pemfile`: the client 'cert_chain', leaf certificate
+    /// first, its PKCS#8 private 'key' and the 'trusted_certificates'.
+    ///
+    /// They are converted in memory to the PKCS#12 and PKCS#7 blobs
+    /// expected by blpapi.
+    #[cfg(feature = "rustls")]
+    pub fn from_rustls(
+        cert_chain: &[rustls_pki_types::CertificateDer<'_>],
+        key: &rustls_pki_types::PrivateKeyDer<'_>,
+        trusted_certificates: &[rustls_pki_types::CertificateDer<'_>],
+    ) -> Result<TlsOptions, Error> {
+        use p12_keystore::{Certificate, KeyStore, KeyStoreEntry, PrivateKeyChain};
+
+        let key = match key {
+            rustls_pki_types::PrivateKeyDer::Pkcs8(key) => key.secret_pkcs8_der(),
+            _ => return Err(Error::InvalidTlsMaterial("private key must be in PKCS#8 format".to_string())),
+        };
+        let chain = cert_chain
+            .iter()
+            .map(|cert| Certificate::from_der(cert))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| Error::InvalidTlsMaterial(err.to_string()))?;
+        let mut key_store = KeyStore::new();
+        key_store.add_entry("client", KeyStoreEntry::PrivateKeyChain(PrivateKeyChain::new(key, b"client", chain)));
+        let credentials = key_store
+            .writer(BLOB_PASSWORD)
+            .write()
+            .map_err(|err| Error::InvalidTlsMaterial(err.to_string()))?;
+
+        let trusted = pkcs7_certificates(trusted_certificates.iter().map(|cert| cert.as_ref()));
+        TlsOptions::create_from_blobs(&credentials, BLOB_PASSWORD, &trusted)
+            .ok_or_else(|| Error::InvalidTlsMaterial("rejected by blpapi".to_string()))
+    }
+
+    /// Create a TlsOptions from the credentials of native-tls: the client
+    /// identity as the DER encoded 'pkcs12' archive, protected by
+    /// 'password', given to `native_tls::Identity::from_pkcs12`, and the
+    /// 'trusted_certificates'.
+    ///
+    /// The trusted certificates are converted in memory to the PKCS#7 blob
+    /// expected by blpapi.
+    #[cfg(feature = "native-tls")]
+    pub fn from_native_tls(
+        pkcs12: &[u8],
+        password: &str,
+        trusted_certificates: &[native_tls::Certificate],
+    ) -> Result<TlsOptions, Error> {
+        let trusted = trusted_certificates
+            .iter()
+            .map(|cert| cert.to_der())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| Error::InvalidTlsMaterial(err.to_string()))?;
+        let trusted = pkcs7_certificates(trusted.iter().map(Vec::as_slice));
+        TlsOptions::create_from_blobs(pkcs12, password, &trusted)
+            .ok_or_else(|| Error::InvalidTlsMaterial("rejected by blpapi".to_string()))
+    }
+
     /// Set the TLS handshake timeout to the specified
     /// 'tls_handshake_timeout_ms'. The default is 10,000 milliseconds.
     /// The TLS handshake timeout will be set to the default if
     /// the specified 'tls_handshake_timeout_ms' is not positive.
     pub fn set_tls_handshake_timeout_ms(&mut self, tls_handshake_timeout_ms: i32) {
         unsafe { blpapi_TlsOptions_setTlsHandshakeTimeoutMs(self.0, tls_handshake_timeout_ms) }
+        self.1.tls_handshake_timeout_ms = if tls_handshake_timeout_ms > 0 {
+            tls_handshake_timeout_ms
+        } else {
+            DEFAULT_TLS_HANDSHAKE_TIMEOUT_MS
+        };
+    }
+
+    /// Return the TLS handshake timeout in milliseconds
+    pub fn tls_handshake_timeout_ms(&self) -> i32 {
+        self.1.tls_handshake_timeout_ms
     }
 
     /// Set the CRL fetch timeout to the specified
@@ -59,6 +181,16 @@ impl TlsOptions {
     /// the specified 'crl_fetch_timeout_ms' is not positive.
     pub fn set_crl_fetch_timeout_ms(&mut self, crl_fetch_timeout_ms: i32) {
         unsafe { blpapi_TlsOptions_setCrlFetchTimeoutMs(self.0, crl_fetch_timeout_ms) }
+        self.1.crl_fetch_timeout_ms = if crl_fetch_timeout_ms > 0 {
+            crl_fetch_timeout_ms
+        } else {
+            DEFAULT_CRL_FETCH_TIMEOUT_MS
+        };
+    }
+
+    /// Return the CRL fetch timeout in milliseconds
+    pub fn crl_fetch_timeout_ms(&self) -> i32 {
+        self.1.crl_fetch_timeout_ms
     }
 }
 
@@ -71,17 +203,77 @@ impl Drop for TlsOptions {
 impl Clone for TlsOptions {
     fn clone(&self) -> Self {
         let inner = unsafe { blpapi_TlsOptions_duplicate(self.0) };
-        TlsOptions(inner)
+        TlsOptions(inner, self.1.clone())
     }
 }
 
 impl Default for TlsOptions {
     fn default() -> Self {
         let inner = unsafe { blpapi_TlsOptions_create() };
-        TlsOptions(inner)
+        TlsOptions(inner, Settings::new(Credentials::None))
     }
 }
 
+/// Summarizes the configuration: file names or blob sizes of the
+/// credentials, never their content or password
+impl Debug for TlsOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("TlsOptions");
+        match &self.1.credentials {
+            Credentials::None => (),
+            Credentials::Files { client_credentials, trusted_certificates } => {
+                debug.field("client_credentials_file", client_credentials);
+                debug.field("trusted_certificates_file", trusted_certificates);
+            }
+            Credentials::Blobs { client_credentials_len, trusted_certificates_len } => {
+                debug.field("client_credentials_len", client_credentials_len);
+                debug.field("trusted_certificates_len", trusted_certificates_len);
+            }
+        }
+        debug
+            .field("tls_handshake_timeout_ms", &self.1.tls_handshake_timeout_ms)
+            .field("crl_fetch_timeout_ms", &self.1.crl_fetch_timeout_ms)
+            .finish()
+    }
+}
+
+/// Encode DER 'certificates' as a degenerate PKCS#7 SignedData, holding
+/// only certificates, as found in `.p7b` files
+#[cfg_attr(not(any(feature = "rustls", feature = "native-tls", test)), allow(dead_code))]
+fn pkcs7_certificates<'c>(certificates: impl Iterator<Item = &'c [u8]>) -> Vec<u8> {
+    const SIGNED_DATA: &[u8] = &[0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+    const DATA: &[u8] = &[0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01];
+    const VERSION: &[u8] = &[0x02, 0x01, 0x01];
+    const EMPTY_SET: &[u8] = &[0x31, 0x00];
+
+    let certificates: Vec<u8> = certificates.flat_map(|cert| cert.iter().copied()).collect();
+    let signed_data = [
+        VERSION,
+        EMPTY_SET,
+        &der(0x30, DATA),
+        &der(0xa0, &certificates),
+        EMPTY_SET,
+    ]
+    .concat();
+    der(0x30, &[SIGNED_DATA, &der(0xa0, &der(0x30, &signed_data))].concat())
+}
+
+/// Encode a DER value of 'tag'
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        encoded.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        encoded.push(0x80 | (bytes.len() - skip) as u8);
+        encoded.extend_from_slice(&bytes[skip..]);
+    }
+    encoded.extend_from_slice(content);
+    encoded
+}
+
 unsafe impl Send for TlsOptions {}
 unsafe impl Sync for TlsOptions {}
 
@@ -93,4 +285,57 @@ mod tests {
     fn test_set_tls_handshake_timeout_ms() {
         TlsOptions::default().set_tls_handshake_timeout_ms(5000);
     }
+
+    #[test]
+    fn test_timeouts() {
+        let mut options = TlsOptions::default();
+        assert_eq!(options.tls_handshake_timeout_ms(), DEFAULT_TLS_HANDSHAKE_TIMEOUT_MS);
+        assert_eq!(options.crl_fetch_timeout_ms(), DEFAULT_CRL_FETCH_TIMEOUT_MS);
+
+        options.set_tls_handshake_timeout_ms(5000);
+        options.set_crl_fetch_timeout_ms(15000);
+        assert_eq!(options.tls_handshake_timeout_ms(), 5000);
+        assert_eq!(options.crl_fetch_timeout_ms(), 15000);
+        assert_eq!(options.clone().tls_handshake_timeout_ms(), 5000);
+
+        // not positive timeouts reset to the defaults
+        options.set_tls_handshake_timeout_ms(0);
+        options.set_crl_fetch_timeout_ms(-1);
+        assert_eq!(options.tls_handshake_timeout_ms(), DEFAULT_TLS_HANDSHAKE_TIMEOUT_MS);
+        assert_eq!(options.crl_fetch_timeout_ms(), DEFAULT_CRL_FETCH_TIMEOUT_MS);
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut options = TlsOptions::default();
+        options.set_tls_handshake_timeout_ms(5000);
+        assert_eq!(
+            format!("{:?}", options),
+            "TlsOptions { tls_handshake_timeout_ms: 5000, crl_fetch_timeout_ms: 20000 }"
+        );
+
+        options.1.credentials = Credentials::Blobs { client_credentials_len: 1024, trusted_certificates_len: 512 };
+        assert_eq!(
+            format!("{:?}", options),
+            "TlsOptions { client_credentials_len: 1024, trusted_certificates_len: 512, \
+            tls_handshake_timeout_ms: 5000, crl_fetch_timeout_ms: 20000 }"
+        );
+    }
+
+    #[test]
+    fn test_pkcs7_certificates() {
+        assert_eq!(der(0x04, &[0; 3]), vec![0x04, 0x03, 0, 0, 0]);
+        assert_eq!(&der(0x04, &[0; 300])[..4], &[0x04, 0x82, 0x01, 0x2c]);
+
+        let cert: &[u8] = &[0x30, 0x03, 0x02, 0x01, 0x07];
+        let encoded = pkcs7_certificates(vec![cert, cert].into_iter());
+        let mut expected = vec![0x30, 0x2f, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+        expected.extend_from_slice(&[0xa0, 0x22, 0x30, 0x20, 0x02, 0x01, 0x01, 0x31, 0x00]);
+        expected.extend_from_slice(&[0x30, 0x0b, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01]);
+        expected.extend_from_slice(&[0xa0, 0x0a]);
+        expected.extend_from_slice(cert);
+        expected.extend_from_slice(cert);
+        expected.extend_from_slice(&[0x31, 0x00]);
+        assert_eq!(encoded, expected);
+    }
 }
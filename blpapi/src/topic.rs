@@ -0,0 +1,85 @@
+use crate::service::Service;
+use blpapi_sys::*;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Formatter};
+
+/// Used to identify the stream on which a message is published
+///
+/// Topic objects are obtained from 'createTopic()' on ProviderSession.
+/// They are used when adding a message to an Event for publishing using
+/// 'appendMessage()' on EventFormatter.
+pub struct Topic(pub(crate) *mut blpapi_Topic_t);
+
+impl Topic {
+    /// Returns true if this topic was elected by the platform to become the
+    /// primary publisher.
+    pub fn is_active(&self) -> bool {
+        unsafe { blpapi_Topic_isActive(self.0) != 0 }
+    }
+
+    /// Return the service for which this topic was created.
+    pub fn service(&self) -> Service {
+        let service = unsafe { blpapi_Topic_service(self.0) };
+        unsafe { blpapi_Service_addRef(service) };
+        Service(service)
+    }
+}
+
+impl Clone for Topic {
+    fn clone(&self) -> Self {
+        Topic(unsafe { blpapi_Topic_create(self.0) })
+    }
+}
+
+impl Drop for Topic {
+    fn drop(&mut self) {
+        unsafe { blpapi_Topic_destroy(self.0) }
+    }
+}
+
+impl PartialEq for Topic {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Topic {}
+
+impl PartialOrd for Topic {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Topic {
+    fn cmp(&self, other: &Self) -> Ordering {
+        unsafe { blpapi_Topic_compare(self.0, other.0) }.cmp(&0)
+    }
+}
+
+impl Debug for Topic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("Topic[active={}]", self.is_active()))
+    }
+}
+
+unsafe impl Send for Topic {}
+unsafe impl Sync for Topic {}
+
+#[cfg(test)]
+mod tests {
+    use crate::errors::Error;
+    use crate::testutil::{create_topic, test_service};
+
+    #[test]
+    fn test_topic() -> Result<(), Error> {
+        let service = test_service()?;
+        let active = create_topic(&service, true)?;
+        assert!(active.is_active());
+        assert!(!create_topic(&service, false)?.is_active());
+        assert_eq!(active.service().name(), "//blp/test");
+        assert_eq!(active.clone(), active);
+        assert_eq!(format!("{:?}", active), "Topic[active=true]");
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+use crate::correlation_id::CorrelationId;
+use crate::errors::Error;
+use crate::message::Message;
+use blpapi_sys::*;
+use std::ffi::{CString, CStr};
+use std::fmt::{Debug, Formatter};
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+/// The creation status of a topic in a `TopicList`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopicStatus {
+    NotCreated,
+    Created,
+    Failure,
+}
+
+impl From<c_int> for TopicStatus {
+    fn from(status: c_int) -> Self {
+        match status as u32 {
+            BLPAPI_TOPICLIST_CREATED => TopicStatus::Created,
+            BLPAPI_TOPICLIST_FAILURE => TopicStatus::Failure,
+            _ => TopicStatus::NotCreated,
+        }
+    }
+}
+
+/// Contains a list of topics which require creation.
+///
+/// Created from topic strings or from TOPIC_SUBSCRIBED or
+/// RESOLUTION_SUCCESS messages. This is passed to a
+/// 'createTopics()' call or 'createTopicsAsync()' call on a
+/// ProviderSession. It is updated and returned by the
+/// 'createTopics()' call.
+pub struct TopicList(pub(crate) *mut blpapi_TopicList_t);
+
+impl TopicList {
+    /// Create an empty 'TopicList'.
+    pub fn new() -> Self {
+        let ptr = unsafe { blpapi_TopicList_create(ptr::null_mut()) };
+        TopicList(ptr)
+    }
+
+    /// Add the specified 'topic' to this list, associating the specified
+    /// 'correlationId' with it. The status of the new entry is
+    /// NOT_CREATED.
+    pub fn add(&mut self, topic: &str, correlation_id: Option<CorrelationId>) -> Result<(), Error> {
+        let topic = CString::new(topic).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+        let correlation_id = correlation_id.unwrap_or_else(|| CorrelationId::new_empty());
+        let res = unsafe { blpapi_TopicList_add(self.0, topic.as_ptr(), &correlation_id.0) };
+        Error::check(res)
+    }
+
+    /// Add the topic contained in the specified 'topicSubscribedMessage'
+    /// or 'resolutionSuccessMessage' to this list, associating the
+    /// specified 'correlationId' with it. The status of the new entry is
+    /// NOT_CREATED.
+    pub fn add_from_message(&mut self, message: &Message, correlation_id: Option<CorrelationId>) -> Result<(), Error> {
+        let correlation_id = correlation_id.unwrap_or_else(|| CorrelationId::new_empty());
+        let res = unsafe { blpapi_TopicList_addFromMessage(self.0, message.0, &correlation_id.0) };
+        Error::check(res)
+    }
+
+    /// Return the number of entries in this list.
+    pub fn size(&self) -> usize {
+        unsafe { blpapi_TopicList_size(self.0) as usize }
+    }
+
+    /// Return the CorrelationId of the specified 'index'th entry.
+    pub fn correlation_id_at(&self, index: usize) -> Result<CorrelationId, Error> {
+        let mut correlation_id = blpapi_CorrelationId_t::default();
+        let res = unsafe { blpapi_TopicList_correlationIdAt(self.0, &mut correlation_id, index) };
+        Error::check(res)?;
+        Ok(unsafe { CorrelationId::from_raw(correlation_id) })
+    }
+
+    /// Return the topic of the specified 'index'th entry.
+    pub fn topic_string_at(&self, index: usize) -> Result<String, Error> {
+        let mut topic: *const c_char = ptr::null();
+        let res = unsafe { blpapi_TopicList_topicStringAt(self.0, &mut topic, index) };
+        Error::check(res)?;
+        Ok(unsafe { CStr::from_ptr(topic) }.to_string_lossy().into_owned())
+    }
+
+    /// Return the status of the specified 'index'th entry.
+    pub fn status_at(&self, index: usize) -> Result<TopicStatus, Error> {
+        let mut status: c_int = 0;
+        let res = unsafe { blpapi_TopicList_statusAt(self.0, &mut status, index) };
+        Error::check(res)?;
+        Ok(TopicStatus::from(status))
+    }
+
+    /// Return the message received during creation of the topic of the
+    /// specified 'index'th entry.
+    ///
+    /// Only valid once the topic has been created or failed creation.
+    pub fn message_at(&self, index: usize) -> Result<Message, Error> {
+        let mut message = ptr::null_mut();
+        let res = unsafe { blpapi_TopicList_messageAt(self.0, &mut message, index) };
+        Error::check(res)?;
+        unsafe { blpapi_Message_addRef(message) };
+        Ok(Message(message))
+    }
+}
+
+impl Drop for TopicList {
+    fn drop(&mut self) {
+        unsafe { blpapi_TopicList_destroy(self.0) }
+    }
+}
+
+impl Clone for TopicList {
+    fn clone(&self) -> Self {
+        TopicList(unsafe { blpapi_TopicList_create(self.0) })
+    }
+}
+
+impl Debug for TopicList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("TopicList[size={}]", self.size()))
+    }
+}
+
+unsafe impl Send for TopicList {}
+unsafe impl Sync for TopicList {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_list() -> Result<(), Error> {
+        let mut list = TopicList::new();
+        list.add("//blp/test/IBM", Some(CorrelationId::new_int(1, None)))?;
+        list.add("//blp/test/MSFT", None)?;
+        assert_eq!(list.size(), 2);
+        assert_eq!(list.topic_string_at(0)?, "//blp/test/IBM");
+        assert_eq!(list.topic_string_at(1)?, "//blp/test/MSFT");
+        assert_eq!(list.correlation_id_at(0)?, CorrelationId::new_int(1, None));
+        assert_eq!(list.status_at(0)?, TopicStatus::NotCreated);
+        assert!(list.topic_string_at(2).is_err());
+        assert!(list.status_at(2).is_err());
+
+        let copy = list.clone();
+        assert_eq!(copy.size(), 2);
+        assert_eq!(format!("{:?}", copy), "TopicList[size=2]");
+        Ok(())
+    }
+}
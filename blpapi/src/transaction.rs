@@ -0,0 +1,186 @@
+//! Publishing transactions for provider sessions
+//!
+//! Publishing new topics requires a strict order of operations: the topics
+//! must be created before anything is published, the
+//! initial paint (recap) must be published before any update, and topics
+//! left behind by a failure should be deleted. A `PublishTransaction`
+//! collects the topics and messages first and then performs these steps in
+//! order.
+use crate::{
+    errors::ErrorInfo,
+    event::Event,
+    eventformatter::EventFormatter,
+    identity::Identity,
+    provider::{ProviderSession, ResolveMode},
+    service::Service,
+    topic::Topic,
+    topiclist::{TopicList, TopicStatus},
+    Error,
+};
+
+type FormatFn<'t> = Box<dyn FnOnce(&mut EventFormatter) -> Result<(), Error> + 't>;
+
+struct PendingTopic<'t> {
+    topic: String,
+    recap: FormatFn<'t>,
+}
+
+struct PendingUpdate<'t> {
+    topic: String,
+    message_type: String,
+    update: FormatFn<'t>,
+}
+
+/// A batch of topic creations, initial paints and updates published in
+/// order by `commit`
+pub struct PublishTransaction<'s, 'a, 't> {
+    session: &'s mut ProviderSession<'a>,
+    service: Service,
+    topics: Vec<PendingTopic<'t>>,
+    updates: Vec<PendingUpdate<'t>>,
+}
+
+impl<'s, 'a, 't> PublishTransaction<'s, 'a, 't> {
+    /// Create the `topic` of the service, its initial paint being
+    /// formatted by `recap` in a recap message
+    pub fn topic<F>(mut self, topic: &str, recap: F) -> Self
+    where
+        F: FnOnce(&mut EventFormatter) -> Result<(), Error> + 't,
+    {
+        self.topics.push(PendingTopic {
+            topic: topic.to_string(),
+            recap: Box::new(recap),
+        });
+        self
+    }
+
+    /// Publish a `message_type` message on `topic`, one of the topics of
+    /// this transaction, once all the initial paints are published
+    pub fn update<F>(mut self, topic: &str, message_type: &str, update: F) -> Self
+    where
+        F: FnOnce(&mut EventFormatter) -> Result<(), Error> + 't,
+    {
+        self.updates.push(PendingUpdate {
+            topic: topic.to_string(),
+            message_type: message_type.to_string(),
+            update: Box::new(update),
+        });
+        self
+    }
+
+    /// Create the topics, then publish the initial paints and finally the
+    /// updates, returning the created topics in the order they were added.
+    ///
+    /// If a topic cannot be created or a message cannot be formatted,
+    /// nothing is published and the topics created so far are deleted.
+    pub fn commit(self, identity: Option<&Identity>) -> Result<Vec<Topic>, Error> {
+        let PublishTransaction { session, service, topics: pending, updates } = self;
+
+        let topics = create_topics(session, &pending, identity)?;
+        let events = format_events(&service, &topics, pending, updates);
+        let (recaps, updates) = match events {
+            Ok(events) => events,
+            Err(err) => {
+                // best effort, the formatting error is more relevant
+                let _ = session.delete_topics(&topics);
+                return Err(err);
+            }
+        };
+
+        if let Err(err) = session.publish(&recaps) {
+            let _ = session.delete_topics(&topics);
+            return Err(err);
+        }
+        if let Some(updates) = updates {
+            session.publish(&updates)?;
+        }
+        Ok(topics)
+    }
+}
+
+/// Create all the `pending` topics, deleting the created ones if any fails
+fn create_topics(
+    session: &mut ProviderSession,
+    pending: &[PendingTopic],
+    identity: Option<&Identity>,
+) -> Result<Vec<Topic>, Error> {
+    let mut topic_list = TopicList::new();
+    for topic in pending {
+        topic_list.add(&topic.topic, None)?;
+    }
+    session.create_topics(&mut topic_list, ResolveMode::AutoRegisterServices, identity)?;
+
+    let mut topics = Vec::with_capacity(pending.len());
+    let mut failure = None;
+    for index in 0..topic_list.size() {
+        if topic_list.status_at(index)? == TopicStatus::Created {
+            let message = topic_list.message_at(index)?;
+            topics.push(session.get_topic(&message)?);
+        } else if failure.is_none() {
+            failure = Some(ErrorInfo {
+                message: format!("cannot create topic {}", topic_list.topic_string_at(index)?),
+                ..ErrorInfo::default()
+            });
+        }
+    }
+
+    match failure {
+        Some(error_info) => {
+            let _ = session.delete_topics(&topics);
+            Err(Error::RequestFailure(error_info))
+        }
+        None => Ok(topics),
+    }
+}
+
+/// Format the recap event and, if there are updates, the update event
+fn format_events(
+    service: &Service,
+    topics: &[Topic],
+    pending: Vec<PendingTopic>,
+    updates: Vec<PendingUpdate>,
+) -> Result<(Event, Option<Event>), Error> {
+    let names: Vec<String> = pending.iter().map(|pending| pending.topic.clone()).collect();
+
+    let mut recaps = service.create_publish_event()?;
+    {
+        let mut formatter = EventFormatter::new(&mut recaps);
+        for (pending, topic) in pending.into_iter().zip(topics) {
+            formatter.append_recap_message(topic, None)?;
+            (pending.recap)(&mut formatter)?;
+        }
+    }
+
+    if updates.is_empty() {
+        return Ok((recaps, None));
+    }
+    let mut event = service.create_publish_event()?;
+    {
+        let mut formatter = EventFormatter::new(&mut event);
+        for update in updates {
+            let topic = names
+                .iter()
+                .position(|name| *name == update.topic)
+                .map(|index| &topics[index])
+                .ok_or_else(|| Error::RequestFailure(ErrorInfo {
+                    message: format!("topic {} is not part of the transaction", update.topic),
+                    ..ErrorInfo::default()
+                }))?;
+            formatter.append_message(&update.message_type, topic)?;
+            (update.update)(&mut formatter)?;
+        }
+    }
+    Ok((recaps, Some(event)))
+}
+
+impl<'a> ProviderSession<'a> {
+    /// Start a transaction publishing new topics of `service`
+    pub fn transaction<'s, 't>(&'s mut self, service: &Service) -> PublishTransaction<'s, 'a, 't> {
+        PublishTransaction {
+            session: self,
+            service: service.clone(),
+            topics: Vec::new(),
+            updates: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Typed request/response services
+//!
+//! A `TypedService` declares an operation of a service once, with the serde
+//! types of its request and of its responses, e.g. for an in-house B-PIPE
+//! service:
+//! `const PRICING: TypedService<PriceRequest, PriceResponse> = TypedService::new("//acme/pricing", "PriceRequest");`
+//!
+//! The request is written into the blpapi request with
+//! `serde::serialization::apply_to_request`, and every (partial) response
+//! message is read with `serde::deserialization::from_element`.
+use crate::{
+    correlation_id::CorrelationId,
+    errors::ErrorInfo,
+    event::EventQueue,
+    identity::Identity,
+    message::Message,
+    request::Request,
+    serde::{deserialization::from_element, serialization::apply_to_request},
+    session::Session,
+    Error,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::marker::PhantomData;
+
+/// An `operation` of `service` taking `TReq` requests and answering with
+/// `TResp` responses
+pub struct TypedService<TReq, TResp> {
+    service: &'static str,
+    operation: &'static str,
+    _marker: PhantomData<fn(TReq) -> TResp>,
+}
+
+impl<TReq, TResp> TypedService<TReq, TResp> {
+    /// Declare the `operation` of the `service`
+    pub const fn new(service: &'static str, operation: &'static str) -> Self {
+        TypedService {
+            service,
+            operation,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn service(&self) -> &'static str {
+        self.service
+    }
+
+    pub fn operation(&self) -> &'static str {
+        self.operation
+    }
+}
+
+impl<TReq: Serialize, TResp: DeserializeOwned> TypedService<TReq, TResp> {
+    /// Check that the service can be opened and defines the operation
+    pub fn validate(&self, session: &mut Session) -> Result<(), Error> {
+        let service = session.get_or_open_service(self.service)?;
+        if service.has_operation(self.operation) {
+            Ok(())
+        } else {
+            Err(Error::RequestFailure(ErrorInfo {
+                message: format!("service {} has no operation {}", self.service, self.operation),
+                ..ErrorInfo::default()
+            }))
+        }
+    }
+
+    /// Create the blpapi request of `request`
+    pub fn create_request(&self, session: &mut Session, request: &TReq) -> Result<Request, Error> {
+        let service = session.get_or_open_service(self.service)?;
+        let mut blpapi_request = service.create_request(self.operation)?;
+        apply_to_request(request, &mut blpapi_request).map_err(|err| Error::Serialization(Box::new(err)))?;
+        Ok(blpapi_request)
+    }
+
+    /// Read a (partial) response message
+    pub fn parse(&self, message: &Message) -> Result<TResp, Error> {
+        from_element(message.element()).map_err(|err| Error::Serialization(Box::new(err)))
+    }
+}
+
+impl<'a> Session<'a> {
+    /// Send `request` to `typed`, the responses are delivered to the
+    /// session event handler (or `event_queue` if any) and can be read with
+    /// `TypedService::parse`
+    pub fn send_typed<TReq, TResp>(
+        &mut self,
+        typed: &TypedService<TReq, TResp>,
+        request: &TReq,
+        identity: Option<&Identity>,
+        event_queue: Option<&EventQueue>,
+        correlation_id: Option<CorrelationId>,
+    ) -> Result<CorrelationId, Error>
+    where
+        TReq: Serialize,
+        TResp: DeserializeOwned,
+    {
+        let request = typed.create_request(self, request)?;
+        self.send_request(request, identity, event_queue, correlation_id)
+    }
+
+    /// Send `request` to `typed` and block until the final response is
+    /// received, returning every (partial) response
+    pub fn call<TReq, TResp>(&mut self, typed: &TypedService<TReq, TResp>, request: &TReq) -> Result<Vec<TResp>, Error>
+    where
+        TReq: Serialize,
+        TResp: DeserializeOwned,
+    {
+        let request = typed.create_request(self, request)?;
+        let mut responses = Vec::new();
+        self.collect_messages(request, |message| {
+            if let Ok(error) = message.element().get_element("responseError") {
+                return Err(Error::RequestFailure(ErrorInfo::from_element(&error)));
+            }
+            responses.push(typed.parse(message)?);
+            Ok(())
+        })?;
+        Ok(responses)
+    }
+}
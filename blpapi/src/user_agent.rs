@@ -0,0 +1,33 @@
+//! Application identifying metadata sent to bloomberg
+//!
+//! blpapi has no per session user agent: the values are process wide and
+//! are attached to every session started afterwards, so they must be set
+//! before starting any session. Sessions already started keep the values
+//! they were started with.
+//!
+//! Requires the `user-agent` feature, i.e. blpapi 3.24 or later.
+use crate::Error;
+use blpapi_sys::*;
+use std::ffi::CString;
+
+/// Set the name of the application (task) using the api, as reported in
+/// bloomberg side logs for all the sessions started afterwards
+pub fn set_user_task_name(name: &str) -> Result<(), Error> {
+    let name = CString::new(name).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+    let res = unsafe { blpapi_UserAgentInfo_setUserTaskName(name.as_ptr()) };
+    Error::check(res)
+}
+
+/// Set the language and version of the sdk wrapping blpapi, for all the
+/// sessions started afterwards
+pub fn set_native_sdk_language_and_version(language: &str, version: &str) -> Result<(), Error> {
+    let language = CString::new(language).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+    let version = CString::new(version).map_err(|err| Error::StringConversionError(Box::new(err)))?;
+    let res = unsafe { blpapi_UserAgentInfo_setNativeSdkLanguageAndVersion(language.as_ptr(), version.as_ptr()) };
+    Error::check(res)
+}
+
+/// Report this crate as the sdk, i.e. `Rust` with the version of this crate
+pub fn set_rust_sdk_version() -> Result<(), Error> {
+    set_native_sdk_language_and_version("Rust", env!("CARGO_PKG_VERSION"))
+}
@@ -0,0 +1,210 @@
+//! Dynamically typed element values
+//!
+//! `Element::get_value_dyn` reads a value according to the data type of the
+//! element, for consumers which do not know the schema beforehand.
+use crate::{
+    datetime::Datetime,
+    element::{DataType, Element, SetValue},
+    errors::BlpApiError,
+    name::Name,
+    Error,
+};
+use std::convert::TryFrom;
+
+/// A value of any scalar data type
+#[derive(Debug, Clone)]
+pub enum Value {
+    Bool(bool),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Datetime(Datetime),
+    Bytes(Vec<u8>),
+    /// Enumeration values, and values given as a `Name`
+    Name(Name),
+    Null,
+}
+
+impl Value {
+    /// Name of the variant, used in conversion errors
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Bool(_) => "Bool",
+            Value::I32(_) => "I32",
+            Value::I64(_) => "I64",
+            Value::F32(_) => "F32",
+            Value::F64(_) => "F64",
+            Value::String(_) => "String",
+            Value::Datetime(_) => "Datetime",
+            Value::Bytes(_) => "Bytes",
+            Value::Name(_) => "Name",
+            Value::Null => "Null",
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+}
+
+fn invalid_conversion(message: String) -> Error {
+    Error::BlpApiError(BlpApiError::InvalidConversion(message))
+}
+
+impl Element<'_> {
+    /// Get the value at `index` as the variant matching the data type of
+    /// this element. Null values are returned as `Value::Null`, sequences
+    /// and choices cannot be converted.
+    pub fn get_value_dyn(&self, index: usize) -> Result<Value, Error> {
+        if self.is_null_value(index)? {
+            return Ok(Value::Null);
+        }
+        let value = match self.data_type() {
+            DataType::Bool => Value::Bool(self.get_at(index)?),
+            DataType::Char => {
+                let c: i8 = self.get_at(index)?;
+                Value::String(char::from(c as u8).to_string())
+            }
+            DataType::Byte | DataType::Int32 => Value::I32(self.get_at(index)?),
+            DataType::Int64 => Value::I64(self.get_at(index)?),
+            DataType::Float32 => Value::F32(self.get_at(index)?),
+            DataType::Float64 | DataType::Decimal => Value::F64(self.get_at(index)?),
+            DataType::String => Value::String(self.get_at(index)?),
+            DataType::Date | DataType::Time | DataType::DateTime => Value::Datetime(self.get_at(index)?),
+            DataType::ByteArray => Value::Bytes(self.get_at::<&[u8]>(index)?.to_vec()),
+            DataType::Enumeration => Value::Name(self.get_at(index)?),
+            data_type @ DataType::Sequence | data_type @ DataType::Choice | data_type @ DataType::CorrelationId => {
+                return Err(invalid_conversion(format!(
+                    "cannot read a {:?} element as a value",
+                    data_type
+                )));
+            }
+        };
+        Ok(value)
+    }
+}
+
+impl SetValue for Value {
+    fn set_at(self, element: &mut Element, index: usize) -> Result<(), Error> {
+        match self {
+            Value::Bool(v) => v.set_at(element, index),
+            Value::I32(v) => v.set_at(element, index),
+            Value::I64(v) => v.set_at(element, index),
+            Value::F32(v) => v.set_at(element, index),
+            Value::F64(v) => v.set_at(element, index),
+            Value::String(v) => v.as_str().set_at(element, index),
+            Value::Datetime(v) => (&v).set_at(element, index),
+            Value::Name(v) => v.set_at(element, index),
+            Value::Bytes(v) => v.as_slice().set_at(element, index),
+            Value::Null => Err(invalid_conversion("cannot set a null value".to_string())),
+        }
+    }
+    fn set(self, element: &mut Element, name: &str) -> Result<(), Error> {
+        match self {
+            Value::Bool(v) => v.set(element, name),
+            Value::I32(v) => v.set(element, name),
+            Value::I64(v) => v.set(element, name),
+            Value::F32(v) => v.set(element, name),
+            Value::F64(v) => v.set(element, name),
+            Value::String(v) => v.as_str().set(element, name),
+            Value::Datetime(v) => (&v).set(element, name),
+            Value::Name(v) => v.set(element, name),
+            Value::Bytes(v) => v.as_slice().set(element, name),
+            Value::Null => Err(invalid_conversion("cannot set a null value".to_string())),
+        }
+    }
+    fn set_named(self, element: &mut Element, named_element: &Name) -> Result<(), Error> {
+        match self {
+            Value::Bool(v) => v.set_named(element, named_element),
+            Value::I32(v) => v.set_named(element, named_element),
+            Value::I64(v) => v.set_named(element, named_element),
+            Value::F32(v) => v.set_named(element, named_element),
+            Value::F64(v) => v.set_named(element, named_element),
+            Value::String(v) => v.as_str().set_named(element, named_element),
+            Value::Datetime(v) => (&v).set_named(element, named_element),
+            Value::Name(v) => v.set_named(element, named_element),
+            Value::Bytes(v) => v.as_slice().set_named(element, named_element),
+            Value::Null => Err(invalid_conversion("cannot set a null value".to_string())),
+        }
+    }
+}
+
+macro_rules! impl_conversions {
+    ($ty:ty, $variant:ident) => {
+        impl From<$ty> for Value {
+            fn from(value: $ty) -> Self {
+                Value::$variant(value)
+            }
+        }
+
+        impl TryFrom<Value> for $ty {
+            type Error = Error;
+
+            fn try_from(value: Value) -> Result<Self, Error> {
+                match value {
+                    Value::$variant(value) => Ok(value),
+                    other => Err(invalid_conversion(format!(
+                        "cannot convert a {} value to {}",
+                        other.kind(),
+                        stringify!($ty)
+                    ))),
+                }
+            }
+        }
+    };
+}
+
+impl_conversions!(bool, Bool);
+impl_conversions!(i32, I32);
+impl_conversions!(i64, I64);
+impl_conversions!(f32, F32);
+impl_conversions!(f64, F64);
+impl_conversions!(String, String);
+impl_conversions!(Datetime, Datetime);
+impl_conversions!(Vec<u8>, Bytes);
+impl_conversions!(Name, Name);
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::I32(v) => write!(f, "{}", v),
+            Value::I64(v) => write!(f, "{}", v),
+            Value::F32(v) => write!(f, "{}", v),
+            Value::F64(v) => write!(f, "{}", v),
+            Value::String(v) => f.write_str(v),
+            Value::Datetime(v) => write!(f, "{}", v),
+            Value::Bytes(v) => write!(f, "{:?}", v),
+            Value::Name(v) => f.write_str(&v.to_string_lossy()),
+            Value::Null => f.write_str("null"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert!(matches!(Value::from(3), Value::I32(3)));
+        assert!(matches!(Value::from("AAPL US Equity"), Value::String(ref s) if s == "AAPL US Equity"));
+        assert!(Value::from(None::<f64>).is_null());
+        assert_eq!(i64::try_from(Value::I64(42)).unwrap(), 42);
+        assert!(i64::try_from(Value::F64(1.5)).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+//! Version of the blpapi library loaded at runtime
+//!
+//! The bindings are generated from the headers of the SDK used to build,
+//! which may be newer than the library found at runtime. `require` checks
+//! the runtime version, e.g. to report a clear error at startup.
+//!
+//! The functions of newer SDKs are only bound with the `sdk-*` features,
+//! which must not be enabled beyond the oldest library the application
+//! runs with: a missing symbol fails when linking, or when loading the
+//! library with `dlopen` (see `dlopen::has_function`).
+//!
+//! ```ignore
+//! blpapi::version::require(Version::new(3, 19, 0, 0))?;
+//! ```
+use crate::Error;
+use blpapi_sys::*;
+use std::ffi::CStr;
+use std::fmt::{Display, Formatter};
+
+/// A blpapi version, ordered by major, minor, patch then build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: i32,
+    pub minor: i32,
+    pub patch: i32,
+    pub build: i32,
+}
+
+impl Version {
+    pub fn new(major: i32, minor: i32, patch: i32, build: i32) -> Self {
+        Version { major, minor, patch, build }
+    }
+
+    /// The version of the blpapi library in use
+    pub fn current() -> Self {
+        let mut version = Version::new(0, 0, 0, 0);
+        unsafe {
+            blpapi_getVersionInfo(&mut version.major, &mut version.minor, &mut version.patch, &mut version.build)
+        };
+        version
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}.{}", self.major, self.minor, self.patch, self.build)
+    }
+}
+
+/// First version of the `sdk-3-18` feature
+pub const SDK_3_18: Version = Version { major: 3, minor: 18, patch: 0, build: 0 };
+/// First version of the `sdk-3-20` feature
+pub const SDK_3_20: Version = Version { major: 3, minor: 20, patch: 0, build: 0 };
+/// First version of the `sdk-3-24` feature
+pub const SDK_3_24: Version = Version { major: 3, minor: 24, patch: 0, build: 0 };
+
+/// The full version identifier of the blpapi library in use, including
+/// its build details
+pub fn version_identifier() -> String {
+    let identifier = unsafe { blpapi_getVersionIdentifier() };
+    if identifier.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(identifier) }.to_string_lossy().into_owned()
+}
+
+/// Check that the blpapi library in use is at least `minimum`
+pub fn require(minimum: Version) -> Result<(), Error> {
+    let actual = Version::current();
+    if actual >= minimum {
+        Ok(())
+    } else {
+        Err(Error::UnsupportedVersion { required: minimum, actual })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordering() {
+        let version = Version::new(3, 19, 1, 1);
+        assert_eq!(version.to_string(), "3.19.1.1");
+        assert!(version > Version::new(3, 18, 9, 9));
+        assert!(version < Version::new(3, 19, 2, 0));
+        assert!(version >= Version::new(3, 19, 1, 1));
+    }
+}
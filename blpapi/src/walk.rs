@@ -0,0 +1,62 @@
+//! Depth first walk of an element tree
+//!
+//! Shared by the conversions of elements to JSON, which only differ by what
+//! they do with each node: `Element::to_json` builds a `serde_json::Value`
+//! while `Element::print_json` writes the text directly.
+use crate::{
+    element::{DataType, Element},
+    value::Value,
+    Error,
+};
+
+/// Receives the nodes of an element tree, in document order
+pub(crate) trait Visitor {
+    /// A value, `Value::Null` for null elements and elements without value
+    fn value(&mut self, value: Value) -> Result<(), Error>;
+    fn start_array(&mut self) -> Result<(), Error>;
+    fn end_array(&mut self) -> Result<(), Error>;
+    /// Start a sequence, or a choice with its selected element as only field
+    fn start_object(&mut self) -> Result<(), Error>;
+    /// Name of the next field of the current object
+    fn field(&mut self, name: &str) -> Result<(), Error>;
+    fn end_object(&mut self) -> Result<(), Error>;
+}
+
+/// Walk `element` and all its sub-elements with `visitor`
+pub(crate) fn walk<V: Visitor>(element: &Element, visitor: &mut V) -> Result<(), Error> {
+    let data_type = element.data_type();
+    let is_complex = data_type == DataType::Sequence || data_type == DataType::Choice;
+    if element.is_array() {
+        visitor.start_array()?;
+        for index in 0..element.num_values() {
+            if is_complex {
+                walk(&element.get_at::<Element>(index)?, visitor)?;
+            } else {
+                visitor.value(element.get_value_dyn(index)?)?;
+            }
+        }
+        return visitor.end_array();
+    }
+    if element.is_null()? {
+        return visitor.value(Value::Null);
+    }
+    match data_type {
+        DataType::Sequence => {
+            visitor.start_object()?;
+            for child in element.elements() {
+                visitor.field(&child.string_name())?;
+                walk(&child, visitor)?;
+            }
+            visitor.end_object()
+        }
+        DataType::Choice => {
+            let choice = element.get_choice()?;
+            visitor.start_object()?;
+            visitor.field(&choice.string_name())?;
+            walk(&choice, visitor)?;
+            visitor.end_object()
+        }
+        _ if element.num_values() == 0 => visitor.value(Value::Null),
+        _ => visitor.value(element.get_value_dyn(0)?),
+    }
+}
@@ -0,0 +1,37 @@
+//! Zero Footprint (ZFP) connections over leased lines
+//!
+//! Leased-line users connect to the ZFP endpoints rather than to a local
+//! terminal. `SessionOptions::for_zfp_leased_lines` fills the server
+//! addresses of the session options accordingly, TLS options are required.
+use crate::{session_options::SessionOptions, tls_options::TlsOptions, Error};
+use blpapi_sys::*;
+use std::os::raw::c_int;
+
+/// The remote port of the ZFP endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Remote {
+    /// Connect to port 8194
+    Remote8194,
+    /// Connect to port 8196
+    Remote8196,
+}
+
+impl From<Remote> for c_int {
+    fn from(remote: Remote) -> Self {
+        match remote {
+            Remote::Remote8194 => BLPAPI_ZFPUTIL_REMOTE_8194 as c_int,
+            Remote::Remote8196 => BLPAPI_ZFPUTIL_REMOTE_8196 as c_int,
+        }
+    }
+}
+
+impl SessionOptions {
+    /// Create session options connecting to the ZFP endpoints over leased
+    /// lines on the specified 'remote' port, using 'tls_options'
+    pub fn for_zfp_leased_lines(tls_options: &TlsOptions, remote: Remote) -> Result<Self, Error> {
+        let options = SessionOptions::default();
+        let res = unsafe { blpapi_ZfpUtil_getOptionsForLeasedLines(options.0, tls_options.0, remote.into()) };
+        Error::check(res)?;
+        Ok(options)
+    }
+}